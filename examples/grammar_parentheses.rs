@@ -0,0 +1,32 @@
+use pilator::{
+    components::{
+        grammar::{Grammar, Symbol},
+        items::RegexComponent,
+    },
+    parser::{llparsers::LLParser, naive_parser::NaiveParser, slrparsers::SLRParser, Parser},
+};
+
+fn main() {
+    // list -> "(" list ")" list | ε
+    let mut grammar = Grammar::new("list");
+    grammar.add_production(
+        "list",
+        vec![
+            Symbol::Terminal(RegexComponent::Literal("(".to_string())),
+            Symbol::NonTerminal("list".to_string()),
+            Symbol::Terminal(RegexComponent::Literal(")".to_string())),
+            Symbol::NonTerminal("list".to_string()),
+        ],
+    );
+    grammar.add_production("list", vec![]);
+
+    let input = "(()) ()";
+    let naive = NaiveParser::with_grammar(grammar.clone());
+    let ll = LLParser::new(&grammar).unwrap_or_else(|e| panic!("Error: {}", e));
+    let slr = SLRParser::new(&grammar).unwrap_or_else(|e| panic!("Error: {}", e));
+    println!("Naive: {:?}", naive.parse(input, None));
+    println!("LL(1): {:?}", ll.parse(input, None));
+    println!("SLR(1): {:?}", slr.parse(input, None));
+}
+
+// cargo run --example grammar_parentheses
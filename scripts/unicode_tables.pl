@@ -0,0 +1,109 @@
+#!/usr/bin/env perl
+# Generates src/components/unicode_tables.rs, the general category and script tables of the
+# Unicode Character Database shipped with Perl (see Unicode::UCD):
+#     perl scripts/unicode_tables.pl > src/components/unicode_tables.rs
+use strict;
+use warnings;
+use Unicode::UCD qw(prop_invlist prop_value_aliases prop_values);
+
+my @categories = qw(L LC Lu Ll Lt Lm Lo M Mn Mc Me N Nd Nl No P Pc Pd Ps Pe Pi Pf Po
+                    S Sm Sc Sk So Z Zs Zl Zp C Cc Cf Cs Co Cn);
+my @scripts = sort map { (prop_value_aliases("sc", $_))[1] } prop_values("sc");
+my $version = Unicode::UCD::UnicodeVersion();
+
+# Sorted inclusive ranges of the characters with the property value, without the surrogates, a
+# range ending at U+D7FF being merged with the one starting at U+E000 as in components::class
+sub ranges {
+    my @list = prop_invlist($_[0]);
+    push @list, 0x110000 if @list % 2;
+    my @ranges;
+    while (my ($start, $end) = splice(@list, 0, 2)) {
+        $end--;
+        $start = 0xe000 if $start >= 0xd800 && $start < 0xe000;
+        $end = 0xd7ff if $end >= 0xd800 && $end < 0xe000;
+        next if $start > $end;
+        if (@ranges && $ranges[-1][1] == 0xd7ff && $start == 0xe000) {
+            $ranges[-1][1] = $end;
+        } else {
+            push @ranges, [$start, $end];
+        }
+    }
+    return @ranges;
+}
+
+# Table entries: the variant, its names and its ranges, the ranges being packed in lines
+sub entry {
+    my ($variant, $names, $ranges) = @_;
+    my $out = "    (\n        $variant,\n        &[" . join(", ", map { "\"$_\"" } @$names) . "],\n        &[";
+    my $line = "";
+    for my $range (@$ranges) {
+        my $item = sprintf("('\\u{%x}', '\\u{%x}'),", @$range);
+        if (length($line) + length($item) > 106) {
+            $out .= "\n            $line";
+            $line = "";
+        }
+        $line .= ($line eq "" ? "" : " ") . $item;
+    }
+    $out .= "\n            $line" if $line ne "";
+    $out .= @$ranges ? "\n        ],\n    ),\n" : "],\n    ),\n";
+    return $out;
+}
+
+my %seen;
+sub check_names {
+    for my $name (@_) {
+        die "duplicate name $name" if $seen{$name}++;
+    }
+}
+
+print <<"END";
+// Generated by scripts/unicode_tables.pl from the Unicode Character Database $version, do not edit
+
+/// enum GeneralCategory
+/// Unicode general categories, the two letter ones and the one letter groups of the two letter
+/// ones starting with the same letter, with LC for the cased letters Lu, Ll and Lt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GeneralCategory {
+END
+print "    $_,\n" for @categories;
+print <<"END";
+}
+
+/// enum Script
+/// Unicode scripts, Common being the script of the characters used by several scripts, Inherited
+/// the one of the marks taking the script of the character they follow, and Unknown the one of
+/// the unassigned characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+END
+for my $script (@scripts) {
+    (my $variant = $script) =~ s/_//g;
+    print "    $variant,\n";
+}
+print "}\n\n";
+
+print "/// Table entry: a value, the names it can be written with, and the sorted ranges of its characters\n";
+print "pub(crate) type Table<T> = (T, &'static [&'static str], &'static [(char, char)]);\n\n";
+print "/// Every GeneralCategory, in declaration order, with its short and long names and its ranges\n";
+print "#[rustfmt::skip]\n";
+printf "pub(crate) const GENERAL_CATEGORIES: [Table<GeneralCategory>; %d] = [\n",
+    scalar(@categories);
+for my $category (@categories) {
+    my ($short, $long) = prop_value_aliases("gc", $category);
+    check_names($short, $long);
+    print entry("GeneralCategory::$short", [$short, $long], [ranges("gc=$short")]);
+}
+print "];\n\n";
+
+print "/// Every Script, in declaration order, with its long name, its other names and its ranges\n";
+print "#[rustfmt::skip]\n";
+printf "pub(crate) const SCRIPTS: [Table<Script>; %d] = [\n", scalar(@scripts);
+for my $script (@scripts) {
+    my ($short, $long, @others) = prop_value_aliases("sc", $script);
+    my @names = grep { $_ ne $long } ($long, $short, @others);
+    @names = ($long, @names);
+    check_names(@names);
+    (my $variant = $long) =~ s/_//g;
+    print entry("Script::$variant", \@names, [ranges("sc=$long")]);
+}
+print "];\n";
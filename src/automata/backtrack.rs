@@ -0,0 +1,814 @@
+use std::fmt;
+
+use crate::automata::nfa::{CompileError, Nfa, TokenizeError};
+use crate::components::captures::Captures;
+use crate::components::grammar::Symbol;
+use crate::components::items::{match_text_prefix, Assertion, Flags, Greediness, RegexComponent};
+use crate::components::regex::Regex;
+use crate::components::token::TokenSlice;
+
+/// Backtracker:
+/// Matcher walking the Regex depth first, trying the alternatives of every choice in order (first
+/// arm of an Or, more iterations of a greedy quantifier, fewer of a lazy one) and going back to the
+/// last choice when the rest of the Regex fails. A possessive repetition matches as many iterations
+/// as it can and never gives any of them back, and a lookaround matches its Regex on its own from
+/// the current position, or ending there for a lookbehind, which only this engine supports.
+/// Unlike the automata, the number of steps can grow exponentially with the length of the input
+/// for some Regex, so every search is bounded by a budget of steps, after which it gives up with
+/// BudgetExhausted.
+/// The search keeps its own stack instead of recursing, so long inputs can not overflow the stack.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::backtrack::Backtracker, components::regex::Regex};
+///
+/// let backtracker = Backtracker::new(Regex::from_str("(a|ab) c").unwrap(), 1000).unwrap();
+/// assert_eq!(backtracker.is_match("abc"), Ok(true));
+/// assert_eq!(backtracker.match_at("acx", 0), Ok(Some(2)));
+///
+/// let possessive = Backtracker::new(Regex::from_str("a*+ a").unwrap(), 1000).unwrap();
+/// assert_eq!(possessive.is_match("aaa"), Ok(false));
+///
+/// let catastrophic = Backtracker::new(Regex::from_str("(a*)* b").unwrap(), 1000).unwrap();
+/// assert!(catastrophic.is_match("aaaaaaaaaaaaaaaaaaaaaaaaa").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backtracker {
+    regex: Regex,
+    budget: usize,
+}
+
+/// Implemented methods:
+/// - new: Creates a Backtracker of the Regex with the given budget of steps per search, or returns a
+///   CompileError for a Lookbehind of unbounded length (see RegexComponent::unbounded_lookbehind)
+/// - get_regex: Returns the Regex
+/// - get_budget: Returns the budget of steps of every search
+/// - is_match: Returns true if the Regex matches the whole input
+/// - match_at: Returns the end of the first match, in order of priority, starting at the given
+///   offset, None when the offset is not at a character boundary of the input
+/// - tokenize: Returns the tokens of the first match of the whole input, as Nfa::tokenize does
+/// - captures: Returns what the capture groups matched in the first match of the whole input, as
+///   Nfa::captures does, the iteration that matched nothing and ended a loop included
+impl Backtracker {
+    /// Default budget of steps of a search
+    pub const DEFAULT_BUDGET: usize = 1_000_000;
+
+    pub fn new(regex: Regex, budget: usize) -> Result<Backtracker, CompileError> {
+        supported(&regex)?;
+        Ok(Backtracker { regex, budget })
+    }
+
+    pub fn get_regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    pub fn get_budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn is_match(&self, input: &str) -> Result<bool, BudgetExhausted> {
+        is_match(&self.regex, input, self.budget)
+    }
+
+    pub fn match_at(&self, input: &str, start: usize) -> Result<Option<usize>, BudgetExhausted> {
+        match_at(&self.regex, input, start, self.budget)
+    }
+
+    pub fn tokenize<'a>(&'a self, input: &'a str) -> Result<Tokenized<'a>, BudgetExhausted> {
+        tokenize(&self.regex, input, self.budget)
+    }
+
+    pub fn captures<'a>(&self, input: &'a str) -> Result<Option<Captures<'a>>, BudgetExhausted> {
+        captures(&self.regex, input, self.budget)
+    }
+}
+
+/// Result of a tokenization: the tokens of the match, or the furthest offset where the match failed
+/// together with the components that could have been matched there
+pub type Tokenized<'a> = Result<Vec<TokenSlice<'a>>, TokenizeError>;
+
+/// Error for the first Lookbehind of the Regex whose length is unbounded, if any, which the searches
+/// would have to look behind for from every offset before the position
+pub(crate) fn supported(regex: &Regex) -> Result<(), CompileError> {
+    match regex.components.iter().find_map(RegexComponent::unbounded_lookbehind) {
+        Some(component) => Err(CompileError { component: component.clone() }),
+        None => Ok(()),
+    }
+}
+
+/// Returns true if the Regex matches the whole input, searching with the given budget of steps
+pub(crate) fn is_match(regex: &Regex, input: &str, budget: usize) -> Result<bool, BudgetExhausted> {
+    let mut search = Search::new(input, budget);
+    let state = search.start(regex, 0, false);
+    Ok(search.run(state, Some(input.len()))?.is_some())
+}
+
+/// Returns the end of the first match, in order of priority, starting at the given offset, None when
+/// the offset is not at a character boundary of the input
+pub(crate) fn match_at(
+    regex: &Regex,
+    input: &str,
+    start: usize,
+    budget: usize,
+) -> Result<Option<usize>, BudgetExhausted> {
+    if !input.is_char_boundary(start) {
+        return Ok(None);
+    }
+    let mut search = Search::new(input, budget);
+    let state = search.start(regex, start, false);
+    Ok(search.run(state, None)?.map(|state| state.offset))
+}
+
+/// Returns the tokens of the first match of the whole input, in order of priority, skipping the
+/// tokens that matched no text
+pub(crate) fn tokenize<'a>(regex: &'a Regex, input: &'a str, budget: usize) -> Result<Tokenized<'a>, BudgetExhausted> {
+    let mut search = Search::new(input, budget);
+    let state = search.start(regex, 0, true);
+    let Some(state) = search.run(state, Some(input.len()))? else {
+        return Ok(Err(TokenizeError::new(search.failure, search.furthest)));
+    };
+
+    let mut tokens = vec![];
+    let mut mark = state.marks;
+    while let Some(index) = mark {
+        let (previous, kind, start, end) = search.marks[index];
+        if end > start {
+            tokens.push(TokenSlice::new(kind, &input[start..end], start..end));
+        }
+        mark = previous;
+    }
+    tokens.reverse();
+    Ok(Ok(tokens))
+}
+
+/// Returns what the capture groups matched in the first match of the whole input, in order of
+/// priority, None if the input does not match
+pub(crate) fn captures<'a>(
+    regex: &Regex,
+    input: &'a str,
+    budget: usize,
+) -> Result<Option<Captures<'a>>, BudgetExhausted> {
+    let mut search = Search::new(input, budget);
+    let state = search.start(regex, 0, false);
+    let Some(state) = search.run(state, Some(input.len()))? else {
+        return Ok(None);
+    };
+
+    let names = regex.group_names();
+    let mut spans = vec![vec![]; names.len()];
+    let mut capture = state.captures;
+    while let Some(index) = capture {
+        let (previous, group, start, end) = search.captures[index];
+        spans[group].push(start..end);
+        capture = previous;
+    }
+    spans[0].push(0..input.len());
+    for spans in spans.iter_mut() {
+        spans.reverse();
+    }
+    let names = names.into_iter().map(|name| name.map(str::to_string)).collect();
+    Ok(Some(Captures::new(names, input, spans)))
+}
+
+/// What is left to match after a point of the Regex: a sequence of components, another iteration
+/// of a loop that started at offset `start`, or of a bounded repetition after `count` iterations,
+/// or the end of a token or of a capture group that started at offset `start`, each followed by the
+/// continuation `next`.
+/// `tokens` tells whether the components produce tokens, with the same rule as the Nfa, and `flags`
+/// are the flags in effect around them.
+#[derive(Debug, Clone, Copy)]
+enum Continuation<'r> {
+    Sequence {
+        components: &'r [RegexComponent],
+        tokens: bool,
+        flags: Flags,
+    },
+    Repeat {
+        regex: &'r Regex,
+        tokens: bool,
+        flags: Flags,
+        start: usize,
+    },
+    Counted {
+        regex: &'r Regex,
+        tokens: bool,
+        flags: Flags,
+        min: usize,
+        max: Option<usize>,
+        greediness: Greediness,
+        count: usize,
+        start: usize,
+    },
+    Token {
+        kind: &'r RegexComponent,
+        start: usize,
+    },
+    Group {
+        group: usize,
+        start: usize,
+    },
+}
+
+/// Point of the search: the continuation matched from the offset, None being the end of the Regex,
+/// and the last token mark and capture recorded on the way there
+#[derive(Debug, Clone, Copy)]
+struct State {
+    continuation: Option<usize>,
+    offset: usize,
+    marks: Option<usize>,
+    captures: Option<usize>,
+}
+
+/// State of a search: the continuations and the token marks are shared between the states of the
+/// search, which only refer to them by index
+struct Search<'r, 'i> {
+    input: &'i str,
+    continuations: Vec<(Continuation<'r>, Option<usize>)>,
+    /// States to go back to when the current one fails, the last one first
+    choices: Vec<State>,
+    /// Token marks: previous mark, component of the token, start and end offsets
+    marks: Vec<(Option<usize>, &'r RegexComponent, usize, usize)>,
+    /// Captures: previous capture, number of the group, start and end offsets
+    captures: Vec<(Option<usize>, usize, usize, usize)>,
+    /// Capture groups of the Regex, group 1 first
+    groups: Vec<&'r RegexComponent>,
+    /// Furthest offset where a component failed, with what was expected there
+    failure: Option<(usize, Vec<String>)>,
+    /// Furthest offset reached by a state, lookarounds left out
+    furthest: usize,
+    steps: usize,
+    budget: usize,
+}
+
+impl<'r, 'i> Search<'r, 'i> {
+    fn new(input: &'i str, budget: usize) -> Search<'r, 'i> {
+        Search {
+            input,
+            continuations: vec![],
+            choices: vec![],
+            marks: vec![],
+            captures: vec![],
+            groups: vec![],
+            failure: None,
+            furthest: 0,
+            steps: 0,
+            budget,
+        }
+    }
+
+    /// State matching the whole Regex from the offset
+    fn start(&mut self, regex: &'r Regex, offset: usize, tokens: bool) -> State {
+        self.groups = regex.groups();
+        let sequence = Continuation::Sequence { components: &regex.components, tokens, flags: Flags::new() };
+        let first = self.push(sequence, None);
+        State { continuation: Some(first), offset, marks: None, captures: None }
+    }
+
+    /// Runs the search from the state until the end of the Regex, only accepted at the offset `end`
+    /// if given, and returns the state reached there. The choices left when it succeeds are
+    /// dropped, so that a nested run is atomic: what it matched is never given back.
+    fn run(&mut self, state: State, end: Option<usize>) -> Result<Option<State>, BudgetExhausted> {
+        let base = self.choices.len();
+        let mut state = Some(state);
+        loop {
+            let current = match state.take() {
+                Some(current) => current,
+                None if self.choices.len() > base => self.choices.pop().unwrap(),
+                None => return Ok(None),
+            };
+            self.step(current.offset)?;
+            self.furthest = self.furthest.max(current.offset);
+            let Some(id) = current.continuation else {
+                if end.is_none_or(|end| current.offset == end) {
+                    self.choices.truncate(base);
+                    return Ok(Some(current));
+                }
+                self.fail(current.offset, "end of input".to_string());
+                continue;
+            };
+
+            let (kind, next) = self.continuations[id];
+            let State { offset, marks, .. } = current;
+            state = match kind {
+                Continuation::Sequence { components: [], .. } => Some(State { continuation: next, ..current }),
+                Continuation::Sequence { components: [first, rest @ ..], tokens, flags } => {
+                    let rest = if rest.is_empty() {
+                        next
+                    } else {
+                        Some(self.push(Continuation::Sequence { components: rest, tokens, flags }, next))
+                    };
+                    if tokens && !Nfa::has_subregex(first) {
+                        let end = self.push(Continuation::Token { kind: first, start: offset }, rest);
+                        self.component(first, current, Some(end), false, flags)?
+                    } else {
+                        self.component(first, current, rest, tokens, flags)?
+                    }
+                }
+                Continuation::Token { kind, start } => {
+                    self.marks.push((marks, kind, start, offset));
+                    Some(State { continuation: next, marks: Some(self.marks.len() - 1), ..current })
+                }
+                Continuation::Group { group, start } => {
+                    self.captures.push((current.captures, group, start, offset));
+                    Some(State { continuation: next, captures: Some(self.captures.len() - 1), ..current })
+                }
+                // An iteration that matched nothing would loop forever: the loop stops there
+                Continuation::Repeat { start, .. } if start == offset => Some(State { continuation: next, ..current }),
+                Continuation::Repeat { regex, tokens, flags, .. } => {
+                    Some(self.repeat(regex, tokens, flags, current, next))
+                }
+                // Past the minimum, an iteration that matched nothing stops the repetition in the same way
+                Continuation::Counted { min, count, start, .. } if count > min && start == offset => {
+                    Some(State { continuation: next, ..current })
+                }
+                Continuation::Counted { regex, tokens, flags, min, max, greediness, count, .. } => {
+                    Some(self.counted(regex, tokens, flags, (min, max, greediness, count), current, next))
+                }
+            };
+        }
+    }
+
+    fn push(&mut self, continuation: Continuation<'r>, next: Option<usize>) -> usize {
+        self.continuations.push((continuation, next));
+        self.continuations.len() - 1
+    }
+
+    fn step(&mut self, offset: usize) -> Result<(), BudgetExhausted> {
+        self.steps += 1;
+        if self.steps > self.budget {
+            return Err(BudgetExhausted {
+                budget: self.budget,
+                offset,
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of the capture group of a SubRegex or NamedSubRegex of the Regex
+    fn group(&self, component: &RegexComponent) -> usize {
+        let index = self.groups.iter().position(|group| std::ptr::eq(*group, component));
+        index.map_or(0, |index| index + 1)
+    }
+
+    /// Whether the assertion holds at the offset
+    fn holds(&self, assertion: Assertion, offset: usize) -> bool {
+        assertion.holds(self.input[..offset].chars().next_back(), self.input[offset..].chars().next())
+    }
+
+    /// Records that the component expected at the offset was not found there, keeping the furthest
+    /// failures only
+    fn fail(&mut self, offset: usize, expected: String) {
+        match &mut self.failure {
+            Some((furthest, _)) if *furthest > offset => (),
+            Some((furthest, list)) if *furthest == offset => {
+                if !list.contains(&expected) {
+                    list.push(expected);
+                }
+            }
+            _ => self.failure = Some((offset, vec![expected])),
+        }
+    }
+
+    /// State matching the component from the given state and then the continuation, None if it fails
+    fn component(
+        &mut self,
+        component: &'r RegexComponent,
+        state: State,
+        next: Option<usize>,
+        tokens: bool,
+        flags: Flags,
+    ) -> Result<Option<State>, BudgetExhausted> {
+        let offset = state.offset;
+        let sequence = |search: &mut Self, regex: &'r Regex, flags, next| {
+            let continuation = Continuation::Sequence { components: &regex.components, tokens, flags };
+            let continuation = search.push(continuation, next);
+            State { continuation: Some(continuation), ..state }
+        };
+        Ok(match component {
+            RegexComponent::Literal(value)
+            | RegexComponent::Keyword(value)
+            | RegexComponent::Operator(value)
+            | RegexComponent::Identifier(value) => {
+                // The characters of a text are read one after the other, as the Nfa does
+                let matched = match_text_prefix(value, &self.input[offset..], flags.is_case_insensitive());
+                self.furthest = self.furthest.max(offset + matched.unwrap_or_else(|len| len));
+                let Ok(len) = matched else {
+                    self.fail(offset, Symbol::Terminal(component.clone()).to_string());
+                    return Ok(None);
+                };
+                let end = offset + len;
+                let holds = component.trailing_assertion().is_none_or(|assertion| self.holds(assertion, end));
+                holds.then_some(State { continuation: next, offset: end, ..state })
+            }
+            RegexComponent::Assertion(assertion) => {
+                self.holds(assertion.with_flags(flags), offset).then_some(State { continuation: next, ..state })
+            }
+            RegexComponent::Lookahead { regex, negated } | RegexComponent::Lookbehind { regex, negated } => {
+                let found = match component {
+                    RegexComponent::Lookahead { .. } => self.look(regex, flags, state, None)?,
+                    _ => self.look_behind(regex, flags, state)?,
+                };
+                match (found, negated) {
+                    // The groups of a positive lookaround keep what they captured
+                    (Some(found), false) => Some(State { continuation: next, captures: found.captures, ..state }),
+                    (None, true) => Some(State { continuation: next, ..state }),
+                    _ => None,
+                }
+            }
+            RegexComponent::Class(_) | RegexComponent::Dot => {
+                match component.match_terminal_with(&self.input[offset..], flags) {
+                    Some(len) => Some(State { continuation: next, offset: offset + len, ..state }),
+                    None => {
+                        self.fail(offset, Symbol::Terminal(component.clone()).to_string());
+                        None
+                    }
+                }
+            }
+            RegexComponent::ZeroOrMore(regex) => Some(self.repeat(regex, tokens, flags, state, next)),
+            RegexComponent::OneOrMore(regex) => {
+                let again = self.push(Continuation::Repeat { regex, tokens, flags, start: offset }, next);
+                Some(sequence(self, regex, flags, Some(again)))
+            }
+            RegexComponent::Repeat { regex, min, max, greediness: Greediness::Possessive } => {
+                // The iterations are matched greedily on their own, and the first match found is kept
+                let bounds = (*min, *max, Greediness::Greedy, 0);
+                let iterations = self.counted(regex, tokens, flags, bounds, state, None);
+                self.run(iterations, None)?.map(|end| State { continuation: next, ..end })
+            }
+            RegexComponent::Repeat { regex, min, max, greediness } => {
+                Some(self.counted(regex, tokens, flags, (*min, *max, *greediness, 0), state, next))
+            }
+            RegexComponent::ZeroOrOne(regex) => {
+                self.choices.push(State { continuation: next, ..state });
+                Some(sequence(self, regex, flags, next))
+            }
+            RegexComponent::Or(regex1, regex2) => {
+                let second = sequence(self, regex2, flags, next);
+                self.choices.push(second);
+                Some(sequence(self, regex1, flags, next))
+            }
+            RegexComponent::SubRegex(regex) | RegexComponent::NamedSubRegex(_, regex) => {
+                let end = self.push(Continuation::Group { group: self.group(component), start: offset }, next);
+                Some(sequence(self, regex, flags, Some(end)))
+            }
+            RegexComponent::Flagged(inner, regex) => Some(sequence(self, regex, inner.within(flags), next)),
+        })
+    }
+
+    /// Runs the Regex of a lookaround on its own from the state, only accepted at the offset `end` if
+    /// given. Like assertions, lookarounds report no failure: the failures recorded so far are kept.
+    fn look(
+        &mut self,
+        regex: &'r Regex,
+        flags: Flags,
+        state: State,
+        end: Option<usize>,
+    ) -> Result<Option<State>, BudgetExhausted> {
+        let (failure, furthest) = (self.failure.take(), self.furthest);
+        let sequence = Continuation::Sequence { components: &regex.components, tokens: false, flags };
+        let first = self.push(sequence, None);
+        let found = self.run(State { continuation: Some(first), ..state }, end);
+        self.failure = failure;
+        self.furthest = furthest;
+        found
+    }
+
+    /// Runs the Regex of a lookbehind on its own from every offset before the state, the closest
+    /// first, until it matches a text ending at the offset of the state. As the text has a bounded
+    /// length, only the offsets up to that many characters before are tried: the unbounded ones are
+    /// rejected before searching (see supported).
+    fn look_behind(&mut self, regex: &'r Regex, flags: Flags, state: State) -> Result<Option<State>, BudgetExhausted> {
+        let input = self.input;
+        let len = regex.components.iter().map(RegexComponent::max_len).sum::<Option<usize>>();
+        let starts = input[..state.offset].char_indices().rev().map(|(start, _)| start);
+        for start in std::iter::once(state.offset).chain(starts).take(len.map_or(usize::MAX, |len| len + 1)) {
+            if let Some(found) = self.look(regex, flags, State { offset: start, ..state }, Some(state.offset))? {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Bounded repetition after `count` iterations: the iterations up to the minimum are required,
+    /// the next ones are tried until the maximum, before the continuation when greedy and after it
+    /// when lazy
+    fn counted(
+        &mut self,
+        regex: &'r Regex,
+        tokens: bool,
+        flags: Flags,
+        (min, max, greediness, count): (usize, Option<usize>, Greediness, usize),
+        state: State,
+        next: Option<usize>,
+    ) -> State {
+        let after = State { continuation: next, ..state };
+        if max == Some(count) {
+            return after;
+        }
+        let again = Continuation::Counted {
+            regex,
+            tokens,
+            flags,
+            min,
+            max,
+            greediness,
+            count: count + 1,
+            start: state.offset,
+        };
+        let again = self.push(again, next);
+        let body = self.push(Continuation::Sequence { components: &regex.components, tokens, flags }, Some(again));
+        let iteration = State { continuation: Some(body), ..state };
+        match greediness {
+            _ if count < min => iteration,
+            Greediness::Lazy => {
+                self.choices.push(iteration);
+                after
+            }
+            _ => {
+                self.choices.push(after);
+                iteration
+            }
+        }
+    }
+
+    /// Greedy loop: one more iteration of the Regex first, and the continuation if it fails
+    fn repeat(&mut self, regex: &'r Regex, tokens: bool, flags: Flags, state: State, next: Option<usize>) -> State {
+        self.choices.push(State { continuation: next, ..state });
+        let again = self.push(Continuation::Repeat { regex, tokens, flags, start: state.offset }, next);
+        let body = self.push(Continuation::Sequence { components: &regex.components, tokens, flags }, Some(again));
+        State { continuation: Some(body), ..state }
+    }
+}
+
+/// BudgetExhausted:
+/// Error of a Backtracker search that used all its budget of steps before finding whether the
+/// Regex matches. `offset` is the position in the input where the last step was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExhausted {
+    pub budget: usize,
+    pub offset: usize,
+}
+
+impl fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "backtracking budget of {} steps exhausted at offset {}", self.budget, self.offset)
+    }
+}
+
+impl std::error::Error for BudgetExhausted {}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::automata::derivative;
+    use crate::automata::nfa::Nfa;
+    use std::str::FromStr;
+
+    fn backtracker(pattern: &str) -> Backtracker {
+        Backtracker::new(Regex::from_str(pattern).unwrap(), Backtracker::DEFAULT_BUDGET).unwrap()
+    }
+
+    #[test]
+    fn test_backtracker_backtracks_into_loops() {
+        assert_eq!(backtracker("a* a").is_match("aaaa"), Ok(true));
+        assert_eq!(backtracker("(ab)+ ab c").is_match("abababc"), Ok(true));
+        assert_eq!(backtracker("(ab)+ ab c").is_match("abc"), Ok(false));
+        assert_eq!(backtracker("x (a|b)* b b").is_match("xabbabb"), Ok(true));
+    }
+
+    #[test]
+    fn test_backtracker_ordered_choice() {
+        // The first arm that lets the rest match wins, not the longest one
+        assert_eq!(backtracker("(a|ab)").match_at("ab", 0), Ok(Some(1)));
+        assert_eq!(backtracker("(ab|a)").match_at("ab", 0), Ok(Some(2)));
+        assert_eq!(backtracker("a*").match_at("aab", 0), Ok(Some(2)));
+        assert_eq!(backtracker("a?").match_at("b", 0), Ok(Some(0)));
+        assert_eq!(backtracker("ab").match_at("xab", 1), Ok(Some(3)));
+        assert_eq!(backtracker("ab").match_at("xab", 0), Ok(None));
+    }
+
+    #[test]
+    fn test_backtracker_nullable_loops() {
+        let nested = Backtracker::new(
+            Regex::new(vec![RegexComponent::ZeroOrMore(Regex::new(vec![RegexComponent::ZeroOrOne(
+                Regex::new(vec![RegexComponent::Literal("a".to_string())]),
+            )]))]),
+            1000,
+        )
+        .unwrap();
+        assert_eq!(nested.is_match(""), Ok(true));
+        assert_eq!(nested.is_match("aaa"), Ok(true));
+        assert_eq!(nested.is_match("ab"), Ok(false));
+    }
+
+    #[test]
+    fn test_backtracker_budget() {
+        let catastrophic = Backtracker::new(Regex::from_str("(a+)+ b").unwrap(), 10_000).unwrap();
+        let input = "a".repeat(30);
+        let error = catastrophic.is_match(&input).unwrap_err();
+        assert_eq!(error.budget, 10_000);
+        assert!(error.to_string().starts_with("backtracking budget of 10000 steps exhausted at offset"));
+        // The same Regex is fine when the input matches early
+        assert_eq!(catastrophic.is_match("aaab"), Ok(true));
+        // Long inputs do not overflow the stack
+        assert_eq!(backtracker("a* b").is_match(&("a".repeat(100_000) + "b")), Ok(true));
+    }
+
+    fn texts(backtracker: &Backtracker, input: &str) -> Vec<String> {
+        let tokens = backtracker.tokenize(input).unwrap().unwrap();
+        tokens.iter().map(|token| token.text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_backtracker_lazy_and_possessive() {
+        assert_eq!(texts(&backtracker("a{1,3}? a*"), "aaaa"), vec!["a", "aaa"]);
+        assert_eq!(texts(&backtracker("a{1,3} a*"), "aaaa"), vec!["aaa", "a"]);
+        assert_eq!(backtracker("a*? b").match_at("aab", 0), Ok(Some(3)));
+        assert_eq!(backtracker("(a|b)+?").match_at("abb", 0), Ok(Some(1)));
+
+        // A possessive repetition never gives back what it matched
+        assert_eq!(backtracker("a*+ a").is_match("aaa"), Ok(false));
+        assert_eq!(backtracker("a*+ b").is_match("aab"), Ok(true));
+        assert_eq!(backtracker("(a|ab)++ c").is_match("abc"), Ok(false));
+        assert_eq!(texts(&backtracker("[a-z]{1,2}+ [a-z]*"), "abcd"), vec!["ab", "cd"]);
+        let error = backtracker("x a*+ a").tokenize("xaa").unwrap().unwrap_err();
+        assert_eq!((error.offset, error.expected, error.furthest), (3, vec!["\"a\"".to_string()], 3));
+    }
+
+    #[test]
+    fn test_backtracker_same_tokens_as_nfa() {
+        let patterns = [
+            "a* a",
+            "(a|ab) (c|bcd) d?",
+            "(ab)* a?",
+            "x? (a+|b)+ c",
+            "(a b)* (a|b){2,3}",
+            "(a+ \\b \\ ?)+",
+            "^ a (\\B b)* $",
+            "(?i) a (b|c)* (?-i:d)?",
+            "(?m) (^ . $ \\n?)+",
+            "(?s:.)* b",
+            "ab (cd|ce)",
+            "(?i) abc+ (?-i:abd)",
+            // An iteration that matches nothing ends its loop in both engines
+            "(?:a??)* b?",
+            "(?:a*?)*",
+            "(?:a|b??)*",
+            "(?:(?:a|b?)*? \\b)+ a?",
+            "(?:^|a)+ b{0,2}",
+            "(?:\\b|a?){1,3} b*",
+            "(?:a* b??){2,}?",
+        ];
+        let inputs = [
+            "a", "aa", "abcd", "abbcdd", "aba", "ababa", "xaabbc", "abab", "x", "", "a a", "aa ab", "AbAB", "Abc",
+            "a\nb", "ab\n", "abcx", "ABcab", "abca", "aab", "ab b",
+        ];
+        for pattern in patterns {
+            let regex = Regex::from_str(pattern).unwrap();
+            let nfa = Nfa::new(&regex).unwrap();
+            let backtracker = Backtracker::new(regex, Backtracker::DEFAULT_BUDGET).unwrap();
+            for input in inputs {
+                assert_eq!(
+                    backtracker.tokenize(input),
+                    Ok(nfa.tokenize(input)),
+                    "pattern `{}`, input {:?}",
+                    pattern,
+                    input
+                );
+                for start in 0..=input.len() {
+                    assert_eq!(
+                        backtracker.match_at(input, start),
+                        Ok(nfa.match_at(input, start)),
+                        "pattern `{}`, input {:?} at {}",
+                        pattern,
+                        input,
+                        start
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_backtracker_same_captures_as_nfa() {
+        let patterns = [
+            "((a) b) (?<c>c|d)? (x|(y))*",
+            "(?<item>[a-z]+ ,?)+",
+            "(?<a>[a-z]*?) (?<b>[a-z]*)",
+            "(?<d>(?<e>a|b)){1,3} c?",
+            "(a b)* (c)?",
+            // Loops whose iterations can match nothing keep their last, empty, iteration
+            "(a*)*",
+            "(a?)*",
+            "(a*?)*?",
+            "(a*)+ (b?){2,} (c??){1,3}",
+            "((a?) (\\b)?)* b*",
+        ];
+        let inputs = ["abdx", "abxyy", "ab", "ab,c", "abc", "aabc", "", ",", "bbbc", "aa", "a b", "abbcc"];
+        for pattern in patterns {
+            let regex = Regex::from_str(pattern).unwrap();
+            let nfa = Nfa::new(&regex).unwrap();
+            let backtracker = Backtracker::new(regex, Backtracker::DEFAULT_BUDGET).unwrap();
+            for input in inputs {
+                assert_eq!(
+                    backtracker.captures(input),
+                    Ok(nfa.captures(input)),
+                    "pattern `{}`, input {:?}",
+                    pattern,
+                    input
+                );
+            }
+        }
+
+        let spans = |pattern: &str, input: &str| {
+            let captures = backtracker(pattern).captures(input).unwrap().unwrap();
+            captures.iterations(1).iter().map(|capture| capture.span.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(spans("(a*)*", "aa"), vec![0..2, 2..2]);
+        assert_eq!(spans("(a?)*", ""), vec![0..0]);
+        assert_eq!(spans("(a*?)*?", "aa"), vec![0..1, 1..2]);
+
+        // The iterations of a possessive repetition are captured as well
+        let possessive = backtracker("(?<a>a|b)++ c").captures("abc").unwrap().unwrap();
+        assert_eq!(possessive.iterations(1).len(), 2);
+        assert_eq!(possessive.get(1).unwrap().span, 1..2);
+    }
+
+    #[test]
+    fn test_backtracker_lookarounds() {
+        // A `-` is a unary minus when not followed by a digit
+        let minus = backtracker("[a-z] (- (?![0-9]) [a-z] | - [0-9])");
+        assert_eq!(minus.is_match("a-b"), Ok(true));
+        assert_eq!(minus.is_match("a-1"), Ok(true));
+        let unary = backtracker("- (?![0-9]) [0-9a-z]");
+        assert_eq!(unary.is_match("-b"), Ok(true));
+        assert_eq!(unary.is_match("-1"), Ok(false));
+
+        let price = backtracker("[a-z]* (?<=\\$|EUR) [0-9]+");
+        assert_eq!(price.is_match("$12"), Ok(false));
+        assert_eq!(backtracker("\\$? (?<=\\$) [0-9]+").is_match("$12"), Ok(true));
+        assert_eq!(backtracker("[A-Z]+ (?<=EUR) [0-9]+").is_match("EUR12"), Ok(true));
+        assert_eq!(backtracker("[A-Z]+ (?<=EUR) [0-9]+").is_match("USD12"), Ok(false));
+        assert_eq!(backtracker("(?<!a) b").match_at("ab", 1), Ok(None));
+        assert_eq!(backtracker("(?<!a) b").match_at("cb", 1), Ok(Some(2)));
+        // The text behind can start before the start of the search, and is followed by the input
+        assert_eq!(backtracker("(?<=a \\b) x?").match_at("a x", 1), Ok(Some(1)));
+        assert_eq!(backtracker("(?<=a \\b)").match_at("ab", 1), Ok(None));
+
+        // Lookarounds are atomic and keep what their groups captured when positive
+        assert_eq!(backtracker("(?=(a|ab)) ab").is_match("ab"), Ok(true));
+        assert_eq!(backtracker("(?=(a|ab) b) ab").is_match("ab"), Ok(true));
+        let first = backtracker("(?=(?<x>a|ab)) a b? (?<y>b?)").captures("ab").unwrap().unwrap();
+        assert_eq!((first.name("x").unwrap().text, first.name("y").unwrap().text), ("a", ""));
+        let inner = backtracker("(?=(?<x>a|ab) c) ab c").captures("abc").unwrap().unwrap();
+        assert_eq!(inner.name("x").unwrap().text, "ab");
+        let captures = backtracker("(?=(?<word>[a-z]+)) [a-z]+ 1").captures("ab1").unwrap().unwrap();
+        assert_eq!(captures.name("word").unwrap().span, 0..2);
+        assert_eq!(backtracker("(?!(?<x>a)) b").captures("b").unwrap().unwrap().name("x"), None);
+        // and report no failure
+        let error = backtracker("a (?=b) b c").tokenize("abd").unwrap().unwrap_err();
+        assert_eq!((error.offset, error.expected, error.furthest), (2, vec!["\"c\"".to_string()], 2));
+
+        // A Lookbehind built without the textual syntax must have a bounded length as well
+        let unbounded = RegexComponent::Lookbehind { regex: Regex::from_str("a+").unwrap(), negated: false };
+        let regex = Regex::new(vec![RegexComponent::SubRegex(Regex::new(vec![unbounded.clone()]))]);
+        let error = Backtracker::new(regex, 1000).unwrap_err();
+        assert_eq!(error.component, unbounded);
+        assert_eq!(error.to_string(), "`(?<=a+)` looks behind for a text of unbounded length");
+    }
+
+    #[test]
+    fn test_backtracker_same_as_derivatives() {
+        let patterns = [
+            "a* a",
+            "(a|ab) (c|bcd) d?",
+            "(ab)* a?",
+            "x? (a+|b)+ c",
+            "((a|b)(a|b))*",
+            "(a|b){2,3} a?",
+            "(a?){2} b{1,}",
+            "(a+ \\b \\ ?)+",
+            "(\\b|x)+ a \\B b*",
+            "(?i:x? A) (b|ab)*",
+            "(?ms) (a|b)* . ^ b",
+        ];
+        let inputs = [
+            "", "a", "aa", "abcd", "abbcdd", "aba", "ababa", "xaabbc", "bc", "abab", "a a", "xab", "XaB", "a\nb",
+            "ab\nb",
+        ];
+        for pattern in patterns {
+            let regex = Regex::from_str(pattern).unwrap();
+            let backtracker = Backtracker::new(regex.clone(), Backtracker::DEFAULT_BUDGET).unwrap();
+            for input in inputs {
+                assert_eq!(
+                    backtracker.is_match(input),
+                    Ok(derivative::matches(&regex, input).unwrap()),
+                    "pattern `{}`, input {:?}",
+                    pattern,
+                    input
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use crate::automata::nfa::CompileError;
+use crate::components::class::fold_case;
+use crate::components::items::{Flags, RegexComponent};
+use crate::components::regex::Regex;
+use crate::constants::NEWLINE;
+
+/// Brzozowski derivatives:
+/// the derivative of a Regex with respect to a character is a Regex matching the rest of every
+/// input that the Regex matches and that starts with that character. An input is then matched by
+/// taking the derivative with respect to each of its characters, and checking that the final Regex
+/// matches the empty string.
+/// Derivatives are computed directly on the Regex: None stands for the Regex matching nothing, and
+/// a Regex without components for the one matching only the empty string. The smart constructors
+/// (`sequence` and `alternation`) inline SubRegex, drop empty text and remove duplicated
+/// alternatives, which keeps the derivatives small. The code follows the definition closely, which
+/// makes it a reference to test the other engines against rather than a fast engine.
+/// Derivatives describe the language only: lazy repetitions are matched as greedy ones, while
+/// possessive repetitions, which can reject inputs of their language, and lookarounds are not
+/// supported. `matches` and `longest_match` return a CompileError for them, as Nfa::new does, while
+/// `derivative` itself does not check them.
+/// Assertions match no character: the derivative also takes the character before the one it is
+/// taken with, and an assertion at the front of the Regex is checked between the two of them.
+/// The derivative of a Flagged component is the derivative of its components with its flags,
+/// wrapped in a Flagged component with the same flags, so that the rest is matched with them too.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::derivative, components::regex::Regex};
+///
+/// let regex = Regex::from_str("a* ab").unwrap();
+/// assert_eq!(derivative::derivative(&regex, None, 'a').unwrap().to_string(), "(?:a* ab|b)");
+/// assert_eq!(derivative::matches(&regex, "aab"), Ok(true));
+/// assert_eq!(derivative::matches(&regex, "aa"), Ok(false));
+/// assert_eq!(derivative::longest_match(&regex, "aabab", 0), Ok(Some(3)));
+/// assert!(derivative::matches(&Regex::from_str("a*+ a").unwrap(), "aa").is_err());
+/// ```
+pub fn derivative(regex: &Regex, before: Option<char>, ch: char) -> Option<Regex> {
+    derive_sequence(&regex.components, Flags::new(), before, ch)
+}
+
+/// Returns true if the Regex matches the whole input, or a CompileError for the components that only
+/// the backtracking engine can match
+pub fn matches(regex: &Regex, input: &str) -> Result<bool, CompileError> {
+    supported(regex)?;
+    let mut current = sequence(regex.components.iter().cloned());
+    let mut before = None;
+    for ch in input.chars() {
+        match derivative(&current, before, ch) {
+            Some(next) => current = next,
+            None => return Ok(false),
+        }
+        before = Some(ch);
+    }
+    Ok(nullable(&current.components, Flags::new(), before, None))
+}
+
+/// Returns the end of the longest match of the Regex starting at the given offset, if any, None
+/// when the offset is not at a character boundary of the input, or a CompileError as matches does
+pub fn longest_match(regex: &Regex, input: &str, start: usize) -> Result<Option<usize>, CompileError> {
+    supported(regex)?;
+    if !input.is_char_boundary(start) {
+        return Ok(None);
+    }
+    let mut current = sequence(regex.components.iter().cloned());
+    let mut before = input[..start].chars().next_back();
+    let mut chars = input[start..].char_indices().peekable();
+    let after = chars.peek().map(|&(_, ch)| ch);
+    let mut longest = nullable(&current.components, Flags::new(), before, after).then_some(start);
+    while let Some((offset, ch)) = chars.next() {
+        match derivative(&current, before, ch) {
+            Some(next) => current = next,
+            None => break,
+        }
+        before = Some(ch);
+        if nullable(&current.components, Flags::new(), before, chars.peek().map(|&(_, ch)| ch)) {
+            longest = Some(start + offset + ch.len_utf8());
+        }
+    }
+    Ok(longest)
+}
+
+/// Error for the first component of the Regex that derivatives can't match, if any
+fn supported(regex: &Regex) -> Result<(), CompileError> {
+    match regex.components.iter().find_map(RegexComponent::backtracking_only) {
+        Some(component) => Err(CompileError { component: component.clone() }),
+        None => Ok(()),
+    }
+}
+
+/// Whether the components can match nothing between the two characters, None standing for the
+/// start or the end of the input, with the given flags in effect around them
+fn nullable(components: &[RegexComponent], flags: Flags, before: Option<char>, after: Option<char>) -> bool {
+    components
+        .iter()
+        .all(|component| nullable_component(component, flags, before, after))
+}
+
+fn nullable_component(component: &RegexComponent, flags: Flags, before: Option<char>, after: Option<char>) -> bool {
+    match component {
+        RegexComponent::Assertion(assertion) => assertion.with_flags(flags).holds(before, after),
+        RegexComponent::OneOrMore(regex)
+        | RegexComponent::SubRegex(regex)
+        | RegexComponent::NamedSubRegex(_, regex) => nullable(&regex.components, flags, before, after),
+        RegexComponent::Flagged(inner, regex) => nullable(&regex.components, inner.within(flags), before, after),
+        RegexComponent::Repeat { regex, min, .. } => *min == 0 || nullable(&regex.components, flags, before, after),
+        RegexComponent::Or(regex1, regex2) => {
+            nullable(&regex1.components, flags, before, after) || nullable(&regex2.components, flags, before, after)
+        }
+        component => component.is_nullable(),
+    }
+}
+
+/// Derivative of a sequence of components: the derivative of the first component followed by the
+/// others, or also the derivative of the others when the first component can match nothing
+fn derive_sequence(components: &[RegexComponent], flags: Flags, before: Option<char>, ch: char) -> Option<Regex> {
+    let (first, rest) = components.split_first()?;
+    let head = derive_component(first, flags, before, ch).map(|head| {
+        sequence(head.components.into_iter().chain(rest.iter().cloned()))
+    });
+    if nullable_component(first, flags, before, Some(ch)) {
+        alternation(head, derive_sequence(rest, flags, before, ch))
+    } else {
+        head
+    }
+}
+
+fn derive_component(component: &RegexComponent, flags: Flags, before: Option<char>, ch: char) -> Option<Regex> {
+    if let (RegexComponent::Keyword(value), Some(assertion)) = (component, component.trailing_assertion()) {
+        // The text is followed by its assertion, which the smart constructor would drop with the text
+        let expanded = [RegexComponent::Literal(value.clone()), RegexComponent::Assertion(assertion)];
+        return derive_sequence(&expanded, flags, before, ch);
+    }
+    match component {
+        RegexComponent::Literal(value)
+        | RegexComponent::Keyword(value)
+        | RegexComponent::Operator(value)
+        | RegexComponent::Identifier(value) => {
+            let mut chars = value.chars();
+            let first = chars.next()?;
+            let matched = first == ch || (flags.is_case_insensitive() && fold_case(first) == fold_case(ch));
+            let rest = matched.then(|| chars.as_str().to_string())?;
+            let rest = match component {
+                RegexComponent::Keyword(_) => RegexComponent::Keyword(rest),
+                RegexComponent::Operator(_) => RegexComponent::Operator(rest),
+                RegexComponent::Identifier(_) => RegexComponent::Identifier(rest),
+                _ => RegexComponent::Literal(rest),
+            };
+            Some(sequence([rest]))
+        }
+        RegexComponent::ZeroOrMore(regex) | RegexComponent::OneOrMore(regex) => {
+            let body = derive_sequence(&regex.components, flags, before, ch)?;
+            let again = RegexComponent::ZeroOrMore(regex.clone());
+            Some(sequence(body.components.into_iter().chain([again])))
+        }
+        RegexComponent::Repeat { regex, min, max, greediness } => {
+            // x{n,m} is x x{n-1,m-1}
+            if *max == Some(0) {
+                return None;
+            }
+            let again = RegexComponent::Repeat {
+                regex: regex.clone(),
+                min: min.saturating_sub(1),
+                max: max.map(|max| max - 1),
+                greediness: *greediness,
+            };
+            let body = derive_sequence(&regex.components, flags, before, ch)
+                .map(|body| sequence(body.components.into_iter().chain([again.clone()])));
+            // An iteration matching nothing here, such as an assertion, may not match nothing at the
+            // next position, so the required iterations are not skipped: they are left to x{n-1,m-1}
+            if *min > 0 && nullable(&regex.components, flags, before, Some(ch)) {
+                alternation(body, derive_component(&again, flags, before, ch))
+            } else {
+                body
+            }
+        }
+        RegexComponent::ZeroOrOne(regex)
+        | RegexComponent::SubRegex(regex)
+        | RegexComponent::NamedSubRegex(_, regex) => derive_sequence(&regex.components, flags, before, ch),
+        RegexComponent::Flagged(inner, regex) => {
+            let derived = derive_sequence(&regex.components, inner.within(flags), before, ch)?;
+            Some(sequence([RegexComponent::Flagged(*inner, derived)]))
+        }
+        RegexComponent::Or(regex1, regex2) => alternation(
+            derive_sequence(&regex1.components, flags, before, ch),
+            derive_sequence(&regex2.components, flags, before, ch),
+        ),
+        RegexComponent::Class(class) if flags.is_case_insensitive() => {
+            class.contains_ignoring_case(ch).then(|| Regex::new(vec![]))
+        }
+        RegexComponent::Class(class) => class.contains(ch).then(|| Regex::new(vec![])),
+        RegexComponent::Dot => (ch != NEWLINE || flags.is_dot_matches_newline()).then(|| Regex::new(vec![])),
+        RegexComponent::Assertion(_) | RegexComponent::Lookahead { .. } | RegexComponent::Lookbehind { .. } => None,
+    }
+}
+
+/// Smart constructor of a sequence: SubRegex and NamedSubRegex are inlined, and empty text, loops over nothing,
+/// repetitions of at most zero times or Flagged components without components, which only match the empty string,
+/// are removed
+fn sequence(components: impl IntoIterator<Item = RegexComponent>) -> Regex {
+    let mut result = vec![];
+    for component in components {
+        match component {
+            RegexComponent::SubRegex(regex) | RegexComponent::NamedSubRegex(_, regex) => {
+                result.extend(sequence(regex.components).components)
+            }
+            RegexComponent::ZeroOrMore(regex)
+            | RegexComponent::OneOrMore(regex)
+            | RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::Flagged(_, regex)
+                if regex.components.is_empty() => {}
+            RegexComponent::Repeat { regex, max, .. } if regex.components.is_empty() || max == Some(0) => {}
+            component if component.as_text() == Some("") => {}
+            component => result.push(component),
+        }
+    }
+    Regex::new(result)
+}
+
+/// Smart constructor of an alternation: the Regex matching nothing is dropped, and nested Or are
+/// flattened so that the same alternative only appears once
+fn alternation(regex1: Option<Regex>, regex2: Option<Regex>) -> Option<Regex> {
+    let (regex1, regex2) = match (regex1, regex2) {
+        (None, regex) | (regex, None) => return regex,
+        (Some(regex1), Some(regex2)) => (regex1, regex2),
+    };
+    let mut alternatives = vec![];
+    flatten(regex1, &mut alternatives);
+    flatten(regex2, &mut alternatives);
+    alternatives.into_iter().rev().reduce(|rest, alternative| {
+        Regex::new(vec![RegexComponent::Or(alternative, rest)])
+    })
+}
+
+fn flatten(regex: Regex, alternatives: &mut Vec<Regex>) {
+    if let [RegexComponent::Or(regex1, regex2)] = regex.components.as_slice() {
+        flatten(regex1.clone(), alternatives);
+        flatten(regex2.clone(), alternatives);
+    } else if !alternatives.contains(&regex) {
+        alternatives.push(regex);
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::automata::backtrack::Backtracker;
+    use crate::automata::dfa::Dfa;
+    use crate::automata::lazy_dfa::LazyDfa;
+    use crate::automata::nfa::Nfa;
+    use crate::components::class::CharClass;
+    use crate::components::items::{Assertion, Flags, Greediness};
+    use crate::parser::naive_parser::{Engine, NaiveParser};
+    use crate::parser::Parser;
+    use std::str::FromStr;
+
+    fn regex(pattern: &str) -> Regex {
+        Regex::from_str(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_derivative_smart_constructors() {
+        assert_eq!(derivative(&regex("ab"), None, 'a'), Some(regex("b")));
+        assert_eq!(derivative(&regex("ab"), None, 'b'), None);
+        assert_eq!(derivative(&regex("a"), None, 'a'), Some(Regex::new(vec![])));
+        // Both arms give the same derivative, which appears once
+        assert_eq!(derivative(&regex("(ab|ab)"), None, 'a'), Some(regex("b")));
+        assert_eq!(derivative(&regex("(a|b) c"), None, 'b'), Some(regex("c")));
+        assert_eq!(derivative(&regex("a+ b"), None, 'a'), Some(regex("a* b")));
+        // The derivatives of a* stay the same
+        let star = regex("(ab)*");
+        let after_ab = derivative(&derivative(&star, None, 'a').unwrap(), Some('a'), 'b');
+        assert_eq!(after_ab, Some(star));
+    }
+
+    #[test]
+    fn test_derivative_matches() {
+        assert!(matches(&regex("a* a"), "aaa").unwrap());
+        assert!(!matches(&regex("(a|b) c"), "c").unwrap());
+        assert!(matches(&regex("x? (ab)+"), "abab").unwrap());
+        assert!(matches(&regex("é+"), "éé").unwrap());
+        assert!(!matches(&regex("(ab)+"), "").unwrap());
+        assert!(matches(&regex("[^a]+ \\d"), "bé7").unwrap());
+        assert_eq!(derivative(&regex("[a-c]"), None, 'b'), Some(Regex::new(vec![])));
+        assert_eq!(derivative(&regex("[^a-c]"), None, 'b'), None);
+        assert_eq!(derivative(&regex("(ab){2,3}"), None, 'a'), Some(regex("b (ab){1,2}")));
+        assert_eq!(derivative(&regex("a{0,1} b"), None, 'b'), Some(Regex::new(vec![])));
+        assert!(matches(&regex("a{2,} b{0,2}"), "aaab").unwrap());
+        assert!(!matches(&regex("a{2,} b{0,2}"), "abbb").unwrap());
+        assert_eq!(longest_match(&regex("a b*"), "xabbc", 1), Ok(Some(4)));
+        assert_eq!(longest_match(&regex("a b*"), "xabbc", 0), Ok(None));
+        // Possessive repetitions and lookarounds are refused rather than matched as something else
+        assert_eq!(matches(&regex("x a*+ a"), "xaa").unwrap_err().component.to_string(), "a*+");
+        assert_eq!(matches(&regex("(- (?![0-9]))"), "-1").unwrap_err().component.to_string(), "(?![0-9])");
+        assert!(longest_match(&regex("(?<=a) b"), "ab", 1).is_err());
+    }
+
+    #[test]
+    fn test_derivative_assertions() {
+        // The assertion at the front is checked against the character before
+        let boundary = regex("\\b a");
+        assert_eq!(derivative(&boundary, None, 'a'), Some(Regex::new(vec![])));
+        assert_eq!(derivative(&boundary, Some('b'), 'a'), None);
+        assert!(matches(&regex("a \\b\\ b"), "a b").unwrap());
+        assert!(!matches(&regex("a \\B\\ b"), "a b").unwrap());
+        assert!(matches(&regex("^ a* $"), "aa").unwrap());
+        assert!(!matches(&regex("a ^ a"), "aa").unwrap());
+        assert_eq!(longest_match(&regex("\\b [a-z]+ \\b"), "x ab cd", 2), Ok(Some(4)));
+        assert_eq!(longest_match(&regex("\\b [a-z]+"), "xab", 1), Ok(None));
+
+        let keyword = Regex::new(vec![RegexComponent::Keyword("if".to_string())]);
+        assert!(matches(&keyword, "if").unwrap());
+        assert_eq!(longest_match(&keyword, "if x", 0), Ok(Some(2)));
+        assert_eq!(longest_match(&keyword, "iffy", 0), Ok(None));
+    }
+
+    #[test]
+    fn test_derivative_nullable_iterations() {
+        // An iteration can match nothing at one position and something at the next one
+        let cases = [
+            ("(?:^|a){2}", "a", true),
+            ("(^|.){2}", " ", true),
+            ("(?:\\b|a){2,3} b", "ab", true),
+            ("(?:$|a){3}", "aa", true),
+            ("(?:^|a){2}", "aaa", false),
+        ];
+        for (pattern, input, expected) in cases {
+            let regex = regex(pattern);
+            let nfa = Nfa::new(&regex).unwrap();
+            let backtracker = Backtracker::new(regex.clone(), Backtracker::DEFAULT_BUDGET).unwrap();
+            assert_eq!(matches(&regex, input), Ok(expected), "pattern `{}`, input {:?}", pattern, input);
+            assert_eq!(nfa.is_match(input), expected, "pattern `{}`, input {:?}", pattern, input);
+            assert_eq!(Dfa::new(std::slice::from_ref(&nfa)).matches(input).is_some(), expected);
+            assert_eq!(LazyDfa::new(vec![nfa], 256).matches(input).is_some(), expected);
+            assert_eq!(backtracker.is_match(input), Ok(expected), "pattern `{}`, input {:?}", pattern, input);
+        }
+    }
+
+    /// Small deterministic generator, to build many regexes and inputs without dependencies
+    struct Generator(u64);
+
+    impl Generator {
+        fn next(&mut self, bound: u64) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+
+        fn sequence(&mut self, depth: u32) -> Regex {
+            let length = 1 + self.next(3);
+            Regex::new((0..length).map(|_| self.component(depth)).collect())
+        }
+
+        fn component(&mut self, depth: u32) -> RegexComponent {
+            let choice = if depth == 0 { 0 } else { self.next(8) };
+            match choice {
+                0 if self.next(3) == 0 => RegexComponent::Class(match self.next(3) {
+                    0 => CharClass::set(&['a']),
+                    1 => CharClass::set(&['a']).negated(),
+                    _ => CharClass::range('a', 'b'),
+                }),
+                0 if self.next(4) == 0 => RegexComponent::Assertion(
+                    [
+                        Assertion::Start,
+                        Assertion::End,
+                        Assertion::StartOfInput,
+                        Assertion::EndOfInput,
+                        Assertion::StartOfLine,
+                        Assertion::EndOfLine,
+                        Assertion::WordBoundary,
+                        Assertion::NotWordBoundary,
+                    ][self.next(8) as usize],
+                ),
+                0 if self.next(6) == 0 => RegexComponent::Dot,
+                0 | 1 => {
+                    let length = 1 + self.next(2);
+                    let text = (0..length).map(|_| ['a', 'b'][self.next(2) as usize]).collect();
+                    if self.next(4) == 0 {
+                        RegexComponent::Keyword(text)
+                    } else {
+                        RegexComponent::Literal(text)
+                    }
+                }
+                2 => RegexComponent::ZeroOrMore(self.sequence(depth - 1)),
+                3 => RegexComponent::OneOrMore(self.sequence(depth - 1)),
+                4 => RegexComponent::ZeroOrOne(self.sequence(depth - 1)),
+                5 => RegexComponent::Or(self.sequence(depth - 1), self.sequence(depth - 1)),
+                6 => {
+                    let min = self.next(3) as usize;
+                    let max = [None, Some(min), Some(min + 1)][self.next(3) as usize];
+                    // Lazy repetitions match the same language, so they must agree with the others
+                    let greediness = [Greediness::Greedy, Greediness::Lazy][self.next(2) as usize];
+                    RegexComponent::Repeat { regex: self.sequence(depth - 1), min, max, greediness }
+                }
+                7 if self.next(2) == 0 => {
+                    let mut flag = || [None, Some(true), Some(false)][self.next(3) as usize];
+                    let flags = Flags { case_insensitive: flag(), dot_matches_newline: flag(), multiline: flag() };
+                    RegexComponent::Flagged(flags, self.sequence(depth - 1))
+                }
+                _ => RegexComponent::SubRegex(self.sequence(depth - 1)),
+            }
+        }
+
+        fn input(&mut self) -> String {
+            let length = self.next(7);
+            (0..length).map(|_| ['a', 'b', 'A', 'b', ' ', '\n'][self.next(6) as usize]).collect()
+        }
+    }
+
+    #[test]
+    fn test_derivative_differential() {
+        let mut generator = Generator(42);
+        for _ in 0..200 {
+            let regexes = vec![generator.sequence(2), generator.sequence(2)];
+            let nfas: Vec<Nfa> = regexes.iter().map(|regex| Nfa::new(regex).unwrap()).collect();
+            let dfa = Dfa::new(&nfas);
+            let mut lazy_dfa = LazyDfa::new(nfas.clone(), 256);
+            let parser = NaiveParser::with_regexes(regexes.clone());
+            for _ in 0..10 {
+                let input = generator.input();
+                let expected = regexes.iter().position(|regex| matches(regex, &input).unwrap());
+                let context = format!("regexes `{}` and `{}`, input {:?}", regexes[0], regexes[1], input);
+                for (nfa, regex) in nfas.iter().zip(regexes.iter()) {
+                    assert_eq!(Ok(nfa.is_match(&input)), matches(regex, &input), "{}", context);
+                    let single = Dfa::new(std::slice::from_ref(nfa));
+                    for (start, _) in input.char_indices().chain([(input.len(), ' ')]) {
+                        let end = longest_match(regex, &input, start).unwrap();
+                        assert_eq!(nfa.longest_match(&input, start), end, "{}, start {}", context, start);
+                        assert_eq!(
+                            single.longest_match(&input, start).map(|(_, end)| end),
+                            end,
+                            "{}, start {}",
+                            context,
+                            start
+                        );
+                    }
+                }
+                assert_eq!(dfa.matches(&input), expected, "{}", context);
+                assert_eq!(lazy_dfa.matches(&input), expected, "{}", context);
+                // The parser trims the input first
+                let expected = regexes.iter().position(|regex| matches(regex, input.trim()).unwrap());
+                for engine in [
+                    Engine::Dfa,
+                    Engine::Nfa,
+                    Engine::Derivative,
+                    Engine::Backtrack { budget: 1_000_000 },
+                ] {
+                    assert_eq!(
+                        parser.parse(&input, Some(engine)).is_ok(),
+                        expected.is_some(),
+                        "{}",
+                        context
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,686 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::automata::nfa::{Nfa, State};
+use crate::components::class::CharClass;
+use crate::constants::NEWLINE;
+
+/// DfaState:
+/// State of a Dfa: its transitions, as sorted and disjoint ranges of characters with the target
+/// state, and the index of the Nfa it accepts at the end of the input, if any.
+/// Because of assertions, what a state accepts before another character may depend on that
+/// character: `accept_before` then gives the ranges of characters before which the state accepts
+/// an Nfa, and is None when the state accepts the same Nfa whatever follows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DfaState {
+    pub transitions: Vec<(char, char, usize)>,
+    pub accept: Option<usize>,
+    pub accept_before: Option<Vec<(char, char, usize)>>,
+}
+
+/// Dfa:
+/// Deterministic automaton recognising the languages of a list of Nfa at once, built with the subset
+/// construction and minimized with Hopcroft's algorithm. A state accepts the first Nfa of the list
+/// matching the input read so far, so that earlier Nfa have priority over later ones. State 0 is
+/// the initial state, and characters without a transition lead to a dead state, which is not stored.
+/// Assertions depend on the characters around a position: every state also stands for a kind of
+/// character read last (see `context`), and a search starting after the start of the input
+/// starts from the initial state of the character before it.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::{dfa::Dfa, nfa::Nfa}, components::regex::Regex};
+///
+/// let nfas: Vec<Nfa> = ["a b*", "a+", "c"]
+///     .iter()
+///     .map(|pattern| Nfa::new(&Regex::from_str(pattern).unwrap()).unwrap())
+///     .collect();
+/// let dfa = Dfa::new(&nfas);
+/// assert_eq!(dfa.matches("abb"), Some(0));
+/// assert_eq!(dfa.matches("a"), Some(0));
+/// assert_eq!(dfa.matches("aa"), Some(1));
+/// assert_eq!(dfa.matches("b"), None);
+/// assert_eq!(dfa.longest_match("aac", 0), Some((1, 2)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa {
+    states: Vec<DfaState>,
+    /// Initial state of every context of CONTEXTS
+    starts: [usize; 4],
+}
+
+/// Implemented methods:
+/// - new: Builds the minimal Dfa of the list of Nfa
+/// - get_states: Returns the states of the Dfa
+/// - state_count: Returns the number of states, without the dead state
+/// - transition_count: Returns the number of ranges of characters with a transition, over all states
+/// - start: Returns the initial state of a search starting after the given character, None
+///   standing for the start of the input
+/// - next: Returns the state reached from a state with a character, None for the dead state
+/// - accepts: Returns the Nfa accepted by a state before the given character, None standing for
+///   the end of the input
+/// - matches: Returns the index of the Nfa matching the whole input, if any
+/// - longest_match: Returns the Nfa and the end of the longest match starting at the given offset,
+///   None when the offset is not at a character boundary of the input
+impl Dfa {
+    pub fn new(nfas: &[Nfa]) -> Dfa {
+        let alphabet = Alphabet::new(nfas);
+        let subsets = Subsets::build(nfas, &alphabet);
+        subsets.minimize(&alphabet)
+    }
+
+    pub fn get_states(&self) -> &[DfaState] {
+        &self.states
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.states.iter().map(|state| state.transitions.len()).sum()
+    }
+
+    pub fn start(&self, before: Option<char>) -> usize {
+        let context = context(before);
+        self.starts[CONTEXTS.iter().position(|&other| other == context).unwrap_or(0)]
+    }
+
+    pub fn next(&self, state: usize, ch: char) -> Option<usize> {
+        lookup(&self.states[state].transitions, ch)
+    }
+
+    pub fn accepts(&self, state: usize, after: Option<char>) -> Option<usize> {
+        let state = &self.states[state];
+        match (&state.accept_before, after) {
+            (Some(ranges), Some(ch)) => lookup(ranges, ch),
+            _ => state.accept,
+        }
+    }
+
+    pub fn matches(&self, input: &str) -> Option<usize> {
+        let mut state = self.start(None);
+        for ch in input.chars() {
+            state = self.next(state, ch)?;
+        }
+        self.states[state].accept
+    }
+
+    pub fn longest_match(&self, input: &str, start: usize) -> Option<(usize, usize)> {
+        if !input.is_char_boundary(start) {
+            return None;
+        }
+        let mut state = self.start(input[..start].chars().next_back());
+        let mut longest = None;
+        let mut chars = input[start..].char_indices();
+        loop {
+            let (offset, ch) = match chars.next() {
+                Some((offset, ch)) => (start + offset, Some(ch)),
+                None => (input.len(), None),
+            };
+            if let Some(accept) = self.accepts(state, ch) {
+                longest = Some((accept, offset));
+            }
+            match ch.and_then(|ch| self.next(state, ch)) {
+                Some(next) => state = next,
+                None => return longest,
+            }
+        }
+    }
+}
+
+/// Value of the sorted and disjoint ranges at the character, if any
+fn lookup(ranges: &[(char, char, usize)], ch: char) -> Option<usize> {
+    let index = ranges.partition_point(|&(_, hi, _)| hi < ch);
+    match ranges.get(index) {
+        Some(&(lo, _, value)) if lo <= ch => Some(value),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Dfa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, state) in self.states.iter().enumerate() {
+            write!(f, "{}", index)?;
+            if let Some(accept) = state.accept {
+                write!(f, " (accepts {})", accept)?;
+            }
+            for &(lo, hi, accept) in state.accept_before.iter().flatten() {
+                write!(f, " (accepts {} before {:?}-{:?})", accept, lo, hi)?;
+            }
+            write!(f, ":")?;
+            for &(lo, hi, target) in state.transitions.iter() {
+                if lo == hi {
+                    write!(f, " {:?} -> {}", lo, target)?;
+                } else {
+                    write!(f, " {:?}-{:?} -> {}", lo, hi, target)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ranges of characters that every state of the Nfa either fully consumes or fully rejects, so
+/// that the Dfa only needs one transition per range. Ranges that no state and no assertion tell
+/// apart, such as the many ranges of the word characters, share a symbol, and the Dfa is built with
+/// one transition per symbol, computed from a representative character.
+#[derive(Debug, Clone)]
+pub(crate) struct Alphabet {
+    /// Sorted and disjoint ranges covering every character, with their symbol
+    ranges: Vec<(char, char, usize)>,
+    /// Representative character of every symbol
+    symbols: Vec<char>,
+}
+
+impl Alphabet {
+    pub(crate) fn new(nfas: &[Nfa]) -> Alphabet {
+        let mut sets: BTreeSet<Vec<(char, char)>> = BTreeSet::new();
+        let mut asserts = false;
+        for nfa in nfas.iter() {
+            for state in nfa.get_states().iter() {
+                match *state {
+                    State::Char { ch, .. } => {
+                        sets.insert(vec![(ch, ch)]);
+                    }
+                    State::Class { class, .. } => {
+                        sets.insert(nfa.class(class).matched_ranges());
+                    }
+                    State::Assert { .. } => asserts = true,
+                    _ => (),
+                }
+            }
+        }
+        let mut bounds: BTreeSet<u32> = BTreeSet::from([0, char::MAX as u32 + 1]);
+        for &(lo, hi) in sets.iter().flatten() {
+            bounds.insert(lo as u32);
+            bounds.insert(hi as u32 + 1);
+        }
+        // Assertions tell the characters apart by their context
+        let contexts: &[(u32, u32, usize)] = if asserts { context_ranges() } else { &[(0, char::MAX as u32, 0)] };
+
+        // Windows are told apart by the sets containing them, then by their context
+        let mut signatures: HashMap<Vec<bool>, usize> = HashMap::new();
+        let mut ids: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut alphabet = Alphabet { ranges: vec![], symbols: vec![] };
+        let mut contexts = contexts.iter().peekable();
+        let bounds: Vec<u32> = bounds.into_iter().collect();
+        for pair in bounds.windows(2) {
+            let Some(first) = (pair[0]..pair[1]).find_map(char::from_u32) else {
+                continue;
+            };
+            let inside: Vec<bool> = sets.iter().map(|set| contains(set, first)).collect();
+            let count = signatures.len();
+            let signature = *signatures.entry(inside).or_insert(count);
+            while let Some(&&(lo, hi, context)) = contexts.peek() {
+                let (lo, hi) = (lo.max(pair[0]), hi.min(pair[1] - 1));
+                if lo <= hi {
+                    alphabet.push(lo, hi, (signature, context), &mut ids);
+                }
+                if hi + 1 < pair[1] {
+                    contexts.next();
+                } else {
+                    break;
+                }
+            }
+        }
+        alphabet
+    }
+
+    /// Adds the characters from lo to hi, skipping the surrogates, with the symbol of the key
+    fn push(&mut self, lo: u32, hi: u32, key: (usize, usize), ids: &mut HashMap<(usize, usize), usize>) {
+        // Ranges starting inside the surrogates start at the first character after them
+        let Some(lo) = (lo..=hi).find_map(char::from_u32) else {
+            return;
+        };
+        let hi = char::from_u32(hi).unwrap_or('\u{d7ff}');
+        let symbol = *ids.entry(key).or_insert_with(|| {
+            self.symbols.push(lo);
+            self.symbols.len() - 1
+        });
+        self.ranges.push((lo, hi, symbol));
+    }
+
+    fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Symbol of the character
+    pub(crate) fn symbol(&self, ch: char) -> usize {
+        lookup(&self.ranges, ch).expect("the ranges cover every character")
+    }
+}
+
+/// Ranges of characters covering every character, as code points, with the index in CONTEXTS of
+/// their context, computed the first time they are needed
+fn context_ranges() -> &'static [(u32, u32, usize)] {
+    static CONTEXT_RANGES: OnceLock<Vec<(u32, u32, usize)>> = OnceLock::new();
+    CONTEXT_RANGES.get_or_init(|| {
+        let mut bounds: BTreeSet<u32> = BTreeSet::from([0, char::MAX as u32 + 1]);
+        for (lo, hi) in CharClass::unicode_word().matched_ranges().into_iter().chain([(NEWLINE, NEWLINE)]) {
+            bounds.insert(lo as u32);
+            bounds.insert(hi as u32 + 1);
+        }
+        let bounds: Vec<u32> = bounds.into_iter().collect();
+        bounds
+            .windows(2)
+            .map(|pair| {
+                let before = (pair[0]..pair[1]).find_map(char::from_u32);
+                let context = CONTEXTS.iter().position(|&other| other == context(before)).unwrap_or(0);
+                (pair[0], pair[1] - 1, context)
+            })
+            .collect()
+    })
+}
+
+/// Whether the character is in the sorted and disjoint ranges
+fn contains(ranges: &[(char, char)], ch: char) -> bool {
+    let index = ranges.partition_point(|&(_, hi)| hi < ch);
+    ranges.get(index).is_some_and(|&(lo, _)| lo <= ch)
+}
+
+/// Dfa built by the subset construction, before minimization. Every state is the set of Char,
+/// Class, Assert and Match states, tagged with the index of their Nfa, that the input read so far
+/// can reach, with the context of the last character read.
+struct Subsets {
+    /// Transitions of every state, one per symbol of the alphabet
+    transitions: Vec<Vec<Option<usize>>>,
+    accept: Vec<Option<usize>>,
+    /// Nfa accepted before every symbol of the alphabet, when it depends on the symbol
+    accept_before: Vec<Option<Vec<Option<usize>>>>,
+    /// Initial state of every context of CONTEXTS
+    starts: [usize; 4],
+}
+
+impl Subsets {
+    fn build(nfas: &[Nfa], alphabet: &Alphabet) -> Subsets {
+        let start: Vec<(usize, usize)> = nfas
+            .iter()
+            .enumerate()
+            .map(|(index, nfa)| (index, nfa.get_start()))
+            .collect();
+        let start = closure(nfas, start);
+
+        let mut ids: HashMap<Subset, usize> = HashMap::new();
+        let mut sets: Vec<Subset> = vec![];
+        let mut id = |subset: Subset, sets: &mut Vec<Subset>| {
+            *ids.entry(subset.clone()).or_insert_with(|| {
+                sets.push(subset);
+                sets.len() - 1
+            })
+        };
+        let starts = CONTEXTS.map(|before| id(subset(nfas, start.clone(), before), &mut sets));
+        let mut subsets = Subsets {
+            transitions: vec![],
+            accept: vec![],
+            accept_before: vec![],
+            starts,
+        };
+        let mut index = 0;
+        while index < sets.len() {
+            let (set, before) = sets[index].clone();
+            let accept = accepting(nfas, &resolve(nfas, &set, before, None));
+            let accept_before: Vec<Option<usize>> = (alphabet.symbols.iter())
+                .map(|&lo| accepting(nfas, &resolve(nfas, &set, before, Some(lo))))
+                .collect();
+            subsets.accept.push(accept);
+            subsets.accept_before.push(accept_before.iter().any(|&other| other != accept).then_some(accept_before));
+
+            let mut row = vec![None; alphabet.len()];
+            for (symbol, &lo) in alphabet.symbols.iter().enumerate() {
+                let target = step(nfas, &set, before, lo);
+                if target.is_empty() {
+                    continue;
+                }
+                row[symbol] = Some(id(subset(nfas, target, Some(lo)), &mut sets));
+            }
+            subsets.transitions.push(row);
+            index += 1;
+        }
+        subsets
+    }
+
+    /// Hopcroft's algorithm: starting from the states grouped by what they accept, splits the groups
+    /// until all the states of a group go to the same group with every symbol of the alphabet
+    fn minimize(&self, alphabet: &Alphabet) -> Dfa {
+        // The dead state is made explicit, so that the transitions are total
+        let dead = self.transitions.len();
+        let count = dead + 1;
+        let target = |state: usize, symbol: usize| -> usize {
+            match self.transitions.get(state) {
+                Some(row) => row[symbol].unwrap_or(dead),
+                None => dead,
+            }
+        };
+        let mut inverse: Vec<Vec<Vec<usize>>> = vec![vec![vec![]; count]; alphabet.len()];
+        for state in 0..count {
+            for (symbol, sources) in inverse.iter_mut().enumerate() {
+                sources[target(state, symbol)].push(state);
+            }
+        }
+
+        let mut blocks: Vec<Vec<usize>> = vec![];
+        let mut block_of = vec![0; count];
+        let mut labels = HashMap::new();
+        for (state, block_of_state) in block_of.iter_mut().enumerate() {
+            let accept = self.accept.get(state).copied().flatten();
+            let accept_before = self.accept_before.get(state).and_then(Option::as_ref);
+            let block = *labels.entry((accept, accept_before)).or_insert_with(|| {
+                blocks.push(vec![]);
+                blocks.len() - 1
+            });
+            blocks[block].push(state);
+            *block_of_state = block;
+        }
+
+        let mut waiting: BTreeSet<usize> = (0..blocks.len()).collect();
+        while let Some(splitter) = waiting.pop_first() {
+            let splitter = blocks[splitter].clone();
+            for sources in inverse.iter() {
+                let mut marked: HashMap<usize, Vec<usize>> = HashMap::new();
+                for &state in splitter.iter() {
+                    for &source in sources[state].iter() {
+                        marked.entry(block_of[source]).or_default().push(source);
+                    }
+                }
+                for (block, inside) in marked {
+                    if inside.len() == blocks[block].len() {
+                        continue;
+                    }
+                    let inside_set: BTreeSet<usize> = inside.into_iter().collect();
+                    let (inside, outside): (Vec<usize>, Vec<usize>) =
+                        blocks[block].iter().partition(|state| inside_set.contains(state));
+                    let new = blocks.len();
+                    for &state in inside.iter() {
+                        block_of[state] = new;
+                    }
+                    let smaller = if inside.len() <= outside.len() { new } else { block };
+                    blocks[block] = outside;
+                    blocks.push(inside);
+                    if waiting.contains(&block) {
+                        waiting.insert(new);
+                    } else {
+                        waiting.insert(smaller);
+                    }
+                }
+            }
+        }
+
+        // Number the blocks in the order they are reached from the initial states
+        let dead_block = block_of[dead];
+        let mut numbers: HashMap<usize, usize> = HashMap::new();
+        let mut order = vec![];
+        let starts = self.starts.map(|start| {
+            *numbers.entry(block_of[start]).or_insert_with(|| {
+                order.push(block_of[start]);
+                order.len() - 1
+            })
+        });
+        let mut states = vec![];
+        let mut index = 0;
+        while index < order.len() {
+            let representative = blocks[order[index]][0];
+            let mut transitions: Vec<(char, char, usize)> = vec![];
+            for &(lo, hi, symbol) in alphabet.ranges.iter() {
+                let block = block_of[target(representative, symbol)];
+                if block == dead_block {
+                    continue;
+                }
+                let number = *numbers.entry(block).or_insert_with(|| {
+                    order.push(block);
+                    order.len() - 1
+                });
+                match transitions.last_mut() {
+                    Some((_, last, previous))
+                        if *previous == number && (*last as u32) + 1 == lo as u32 =>
+                    {
+                        *last = hi
+                    }
+                    _ => transitions.push((lo, hi, number)),
+                }
+            }
+            let accept_before = self.accept_before[representative].as_ref().map(|accept_before| {
+                let mut ranges: Vec<(char, char, usize)> = vec![];
+                for &(lo, hi, symbol) in alphabet.ranges.iter() {
+                    match (ranges.last_mut(), accept_before[symbol]) {
+                        (Some((_, last, previous)), Some(accept))
+                            if *previous == accept && (*last as u32) + 1 == lo as u32 =>
+                        {
+                            *last = hi
+                        }
+                        (_, Some(accept)) => ranges.push((lo, hi, accept)),
+                        (_, None) => (),
+                    }
+                }
+                ranges
+            });
+            states.push(DfaState {
+                transitions,
+                accept: self.accept.get(representative).copied().flatten(),
+                accept_before,
+            });
+            index += 1;
+        }
+        Dfa { states, starts }
+    }
+}
+
+/// Characters standing for every context of a position, that is the kind of character before it as
+/// far as assertions can tell: the start of the input, a newline, a word character, or any other
+/// character
+pub(crate) const CONTEXTS: [Option<char>; 4] = [None, Some(NEWLINE), Some('a'), Some(' ')];
+
+/// Character of CONTEXTS standing for the given character before a position
+pub(crate) fn context(before: Option<char>) -> Option<char> {
+    match before {
+        Some(NEWLINE) | None => before,
+        Some(ch) if CharClass::is_word(ch) => Some('a'),
+        Some(_) => Some(' '),
+    }
+}
+
+/// Set of states with the context of the character read last, which only matters when the set
+/// contains assertions
+pub(crate) type Subset = (Vec<(usize, usize)>, Option<char>);
+
+pub(crate) fn subset(nfas: &[Nfa], set: Vec<(usize, usize)>, before: Option<char>) -> Subset {
+    let asserts = set
+        .iter()
+        .any(|&(nfa, state)| matches!(nfas[nfa].get_states()[state], State::Assert { .. }));
+    let before = if asserts { context(before) } else { None };
+    (set, before)
+}
+
+/// Char, Class, Assert and Match states, tagged with the index of their Nfa, reachable from the
+/// given states without consuming input nor checking assertions
+pub(crate) fn closure(nfas: &[Nfa], states: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut closure = BTreeSet::new();
+    let mut seen = BTreeSet::new();
+    let mut stack = states;
+    while let Some((nfa, state)) = stack.pop() {
+        if !seen.insert((nfa, state)) {
+            continue;
+        }
+        match nfas[nfa].get_states()[state] {
+            State::Split { first, second } => {
+                stack.push((nfa, first));
+                stack.push((nfa, second));
+            }
+            State::Token { next, .. } | State::Group { next, .. } | State::Enter { next, .. } => {
+                stack.push((nfa, next))
+            }
+            // Ending the loop after an iteration that matched nothing leaves the language unchanged
+            State::Iterate { next, exit, .. } => {
+                stack.push((nfa, next));
+                stack.push((nfa, exit));
+            }
+            State::Char { .. } | State::Class { .. } | State::Assert { .. } | State::Match => {
+                closure.insert((nfa, state));
+            }
+        }
+    }
+    closure.into_iter().collect()
+}
+
+/// Char, Class and Match states reachable from the set of states by following the assertions that
+/// hold between the two characters, None standing for the start or the end of the input
+pub(crate) fn resolve(
+    nfas: &[Nfa],
+    set: &[(usize, usize)],
+    before: Option<char>,
+    after: Option<char>,
+) -> Vec<(usize, usize)> {
+    let mut resolved = BTreeSet::new();
+    let mut seen = BTreeSet::new();
+    let mut stack = set.to_vec();
+    while let Some((nfa, state)) = stack.pop() {
+        if !seen.insert((nfa, state)) {
+            continue;
+        }
+        match nfas[nfa].get_states()[state] {
+            State::Assert { assertion, next } => {
+                if assertion.holds(before, after) {
+                    stack.extend(closure(nfas, vec![(nfa, next)]));
+                }
+            }
+            _ => {
+                resolved.insert((nfa, state));
+            }
+        }
+    }
+    resolved.into_iter().collect()
+}
+
+/// States reached from the set of states, after the given character, by consuming the character,
+/// empty for the dead state
+pub(crate) fn step(nfas: &[Nfa], set: &[(usize, usize)], before: Option<char>, ch: char) -> Vec<(usize, usize)> {
+    let targets: Vec<(usize, usize)> = resolve(nfas, set, before, Some(ch))
+        .iter()
+        .filter_map(|&(nfa, state)| match nfas[nfa].get_states()[state] {
+            State::Char { next, .. } | State::Class { next, .. } if nfas[nfa].consumes(state, ch) => {
+                Some((nfa, next))
+            }
+            _ => None,
+        })
+        .collect();
+    if targets.is_empty() {
+        return targets;
+    }
+    closure(nfas, targets)
+}
+
+/// First Nfa having its Match state in the set of states
+pub(crate) fn accepting(nfas: &[Nfa], set: &[(usize, usize)]) -> Option<usize> {
+    set.iter()
+        .filter(|&&(nfa, state)| nfas[nfa].get_states()[state] == State::Match)
+        .map(|&(nfa, _)| nfa)
+        .min()
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::regex::Regex;
+    use std::str::FromStr;
+
+    fn dfa(patterns: &[&str]) -> Dfa {
+        let nfas: Vec<Nfa> = patterns
+            .iter()
+            .map(|pattern| Nfa::new(&Regex::from_str(pattern).unwrap()).unwrap())
+            .collect();
+        Dfa::new(&nfas)
+    }
+
+    #[test]
+    fn test_dfa_priority() {
+        let dfa = dfa(&["if", "[a-z]+", "[0-9]+"]);
+        assert_eq!(dfa.matches("if"), Some(0));
+        assert_eq!(dfa.matches("iff"), Some(1));
+        assert_eq!(dfa.matches("x"), Some(1));
+        assert_eq!(dfa.matches("42"), Some(2));
+        assert_eq!(dfa.matches("4a"), None);
+        assert_eq!(dfa.matches(""), None);
+        assert_eq!(dfa.longest_match("if42", 0), Some((0, 2)));
+        assert_eq!(dfa.longest_match("if42", 2), Some((2, 4)));
+        assert_eq!(dfa.longest_match("if42", 4), None);
+    }
+
+    #[test]
+    fn test_dfa_minimization() {
+        // (a|b)* a b b has a minimal Dfa of 4 states
+        let dfa = dfa(&["(a|b)* abb"]);
+        assert_eq!(dfa.state_count(), 4);
+        assert_eq!(dfa.transition_count(), 8);
+        assert_eq!(dfa.matches("babb"), Some(0));
+        assert_eq!(dfa.matches("abab"), None);
+
+        // Equivalent regexes give the same automaton
+        assert_eq!(dfa.get_states().len(), self::dfa(&["(a|b)* a (b|b) b"]).state_count());
+        // Ranges of characters going to the same state are merged
+        let letters = self::dfa(&["[a-e]"]);
+        assert_eq!(letters.get_states()[0].transitions, vec![('a', 'e', 1)]);
+        assert_eq!(letters.to_string(), "0: 'a'-'e' -> 1\n1 (accepts 0):\n");
+    }
+
+    #[test]
+    fn test_dfa_unicode() {
+        let dfa = dfa(&["é+ ü"]);
+        assert_eq!(dfa.matches("ééü"), Some(0));
+        assert_eq!(dfa.longest_match("xéü", 1), Some((0, 5)));
+        assert_eq!(dfa.next(0, 'u'), None);
+    }
+
+    #[test]
+    fn test_dfa_classes() {
+        let dfa = dfa(&["\"[^\"]*\"", "[a-z]+", "\\S+"]);
+        assert_eq!(dfa.matches("\"a b\""), Some(0));
+        assert_eq!(dfa.matches("abc"), Some(1));
+        assert_eq!(dfa.matches("a\"€"), Some(2));
+        assert_eq!(dfa.matches("a b"), None);
+        // The negated classes cover every character outside their ranges, up to char::MAX
+        assert_eq!(dfa.matches("\u{10ffff}"), Some(2));
+        assert_eq!(dfa.next(0, ' '), None);
+    }
+
+    #[test]
+    fn test_dfa_assertions() {
+        let dfa = dfa(&["\\b if \\b", "[a-z]+", "\\B [0-9]+"]);
+        assert_eq!(dfa.matches("if"), Some(0));
+        assert_eq!(dfa.matches("iffy"), Some(1));
+        assert_eq!(dfa.matches("42"), None);
+        assert_eq!(dfa.longest_match("if(", 0), Some((0, 2)));
+        assert_eq!(dfa.longest_match("iffy", 0), Some((1, 4)));
+        assert_eq!(dfa.longest_match("x42", 1), Some((2, 3)));
+        assert_eq!(dfa.longest_match(" 42", 1), None);
+        // The state reached after `if` accepts the first Nfa only before a non word character
+        let state = dfa.next(dfa.next(dfa.start(None), 'i').unwrap(), 'f').unwrap();
+        assert_eq!(dfa.accepts(state, None), Some(0));
+        assert_eq!(dfa.accepts(state, Some('(')), Some(0));
+        assert_eq!(dfa.accepts(state, Some('f')), Some(1));
+        assert!(dfa.get_states()[state].accept_before.is_some());
+        assert_ne!(dfa.start(None), dfa.start(Some('x')));
+
+        // Regexes without assertions keep a single initial state
+        let plain = self::dfa(&["(a|b)* abb"]);
+        assert_eq!(plain.start(Some('a')), plain.start(None));
+        assert_eq!(plain.state_count(), 4);
+    }
+
+    #[test]
+    fn test_dfa_flags() {
+        let dfa = dfa(&["(?i) select \\b", "[a-z]+", "(?m) ^ . $"]);
+        assert_eq!(dfa.matches("SELECT"), Some(0));
+        assert_eq!(dfa.matches("Select"), Some(0));
+        assert_eq!(dfa.matches("selects"), Some(1));
+        assert_eq!(dfa.matches("SELECTS"), None);
+        assert_eq!(dfa.longest_match("X\nY", 2), Some((2, 3)));
+        assert_eq!(dfa.longest_match("XY", 1), None);
+        // The case variants of a character do not multiply the states
+        let insensitive = self::dfa(&["(?i) (a|b)* abb"]);
+        assert_eq!(insensitive.state_count(), 4);
+        assert_eq!(insensitive.matches("bAbABb"), Some(0));
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::automata::dfa::{accepting, closure, resolve, step, subset, Alphabet, Subset};
+use crate::automata::nfa::Nfa;
+
+/// Number of times the cache can be flushed during a single search before it falls back to the
+/// simulation of the Nfa
+const MAX_FLUSHES: usize = 3;
+
+/// Approximate memory used by a transition stored in the cache
+const TRANSITION_SIZE: usize = size_of::<(usize, Option<usize>)>();
+
+/// State of the LazyDfa built so far: the set of Nfa states it stands for with the context of the
+/// character read last, the Nfa it accepts at the end of the input and the transitions already
+/// computed for the symbols of the alphabet, None being the dead state
+#[derive(Debug, Clone)]
+struct LazyState {
+    set: Vec<(usize, usize)>,
+    before: Option<char>,
+    accept: Option<usize>,
+    transitions: HashMap<usize, Option<usize>>,
+}
+
+/// LazyDfa:
+/// Dfa of a list of Nfa, like automata::dfa::Dfa, whose states are only built when a search reaches
+/// them, so that the states never visited by the input cost nothing. Transitions are computed for
+/// the symbols of the alphabet of the Dfa, the characters that no Nfa tells apart sharing one.
+/// Built states and transitions are cached across searches as long as they fit in the memory budget
+/// (in bytes) of the cache, which is flushed when they do not.
+/// When the cache has to be flushed more than a few times during the same search, the search falls
+/// back to simulating the Nfa one after the other.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::{lazy_dfa::LazyDfa, nfa::Nfa}, components::regex::Regex};
+///
+/// let nfas: Vec<Nfa> = ["a b*", "a+"]
+///     .iter()
+///     .map(|pattern| Nfa::new(&Regex::from_str(pattern).unwrap()).unwrap())
+///     .collect();
+/// let mut dfa = LazyDfa::new(nfas, LazyDfa::DEFAULT_BUDGET);
+/// assert_eq!(dfa.matches("abb"), Some(0));
+/// assert_eq!(dfa.matches("aa"), Some(1));
+/// assert_eq!(dfa.matches("ba"), None);
+/// assert!(dfa.state_count() > 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazyDfa {
+    nfas: Vec<Nfa>,
+    alphabet: Alphabet,
+    budget: usize,
+    states: Vec<LazyState>,
+    ids: HashMap<Subset, usize>,
+    memory: usize,
+    flushes: usize,
+    fallbacks: usize,
+}
+
+/// Implemented methods:
+/// - new: Creates a LazyDfa of the list of Nfa with an empty cache and the given memory budget
+/// - get_budget: Returns the memory budget of the cache, in bytes
+/// - set_budget: Changes the memory budget, flushing the cache if it does not fit anymore
+/// - state_count: Returns the number of states in the cache
+/// - memory: Returns the approximate memory used by the cache, in bytes
+/// - flush_count: Returns the number of times the cache was flushed
+/// - fallback_count: Returns the number of searches that fell back to the simulation of the Nfa
+/// - matches: Returns the index of the first Nfa matching the whole input, if any
+impl LazyDfa {
+    /// Default memory budget of the cache: 1 MiB
+    pub const DEFAULT_BUDGET: usize = 1 << 20;
+
+    pub fn new(nfas: Vec<Nfa>, budget: usize) -> LazyDfa {
+        LazyDfa {
+            alphabet: Alphabet::new(&nfas),
+            nfas,
+            budget,
+            states: vec![],
+            ids: HashMap::new(),
+            memory: 0,
+            flushes: 0,
+            fallbacks: 0,
+        }
+    }
+
+    pub fn get_budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        if self.memory > budget {
+            self.flush();
+        }
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn memory(&self) -> usize {
+        self.memory
+    }
+
+    pub fn flush_count(&self) -> usize {
+        self.flushes
+    }
+
+    pub fn fallback_count(&self) -> usize {
+        self.fallbacks
+    }
+
+    pub fn matches(&mut self, input: &str) -> Option<usize> {
+        let start: Vec<(usize, usize)> = self
+            .nfas
+            .iter()
+            .enumerate()
+            .map(|(index, nfa)| (index, nfa.get_start()))
+            .collect();
+        let start = subset(&self.nfas, closure(&self.nfas, start), None);
+        let mut flushes = 0;
+        let mut state = match self.insert(start.clone(), 0) {
+            Some(state) => state,
+            None => {
+                self.flush();
+                flushes += 1;
+                self.force_insert(start)
+            }
+        };
+
+        for ch in input.chars() {
+            let symbol = self.alphabet.symbol(ch);
+            if let Some(&next) = self.states[state].transitions.get(&symbol) {
+                state = next?;
+                continue;
+            }
+
+            let target = step(&self.nfas, &self.states[state].set, self.states[state].before, ch);
+            let target = (!target.is_empty()).then(|| subset(&self.nfas, target, Some(ch)));
+            // The transition is cached only if it fits in the budget, together with its target
+            let next = match &target {
+                _ if self.memory + TRANSITION_SIZE > self.budget => None,
+                None => Some(None),
+                Some(target) => self.insert(target.clone(), TRANSITION_SIZE).map(Some),
+            };
+            if let Some(next) = next {
+                self.states[state].transitions.insert(symbol, next);
+                self.memory += TRANSITION_SIZE;
+                state = next?;
+                continue;
+            }
+
+            let target = target?;
+            flushes += 1;
+            if flushes > MAX_FLUSHES {
+                self.fallbacks += 1;
+                return self.nfas.iter().position(|nfa| nfa.is_match(input));
+            }
+            // The search goes on from the target, the only state it still needs
+            self.flush();
+            state = self.force_insert(target);
+        }
+        self.states[state].accept
+    }
+
+    /// Approximate memory used by a state of the given set, stored both in the states and the ids
+    fn state_size(set: &[(usize, usize)]) -> usize {
+        size_of::<LazyState>() + 2 * size_of::<(usize, usize)>() * set.len() + size_of::<usize>()
+    }
+
+    /// Returns the state of the set, building it if it fits in the budget with the given number of
+    /// bytes left over
+    fn insert(&mut self, subset: Subset, reserved: usize) -> Option<usize> {
+        if let Some(&id) = self.ids.get(&subset) {
+            return Some(id);
+        }
+        if self.memory + Self::state_size(&subset.0) + reserved > self.budget {
+            return None;
+        }
+        Some(self.force_insert(subset))
+    }
+
+    /// Builds the state of the set, even if it does not fit in the budget
+    fn force_insert(&mut self, subset: Subset) -> usize {
+        let (set, before) = subset.clone();
+        self.memory += Self::state_size(&set);
+        self.states.push(LazyState {
+            accept: accepting(&self.nfas, &resolve(&self.nfas, &set, before, None)),
+            set,
+            before,
+            transitions: HashMap::new(),
+        });
+        self.ids.insert(subset, self.states.len() - 1);
+        self.states.len() - 1
+    }
+
+    fn flush(&mut self) {
+        self.states.clear();
+        self.ids.clear();
+        self.memory = 0;
+        self.flushes += 1;
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::automata::dfa::Dfa;
+    use crate::components::regex::Regex;
+    use std::str::FromStr;
+
+    fn nfas(patterns: &[&str]) -> Vec<Nfa> {
+        patterns
+            .iter()
+            .map(|pattern| Nfa::new(&Regex::from_str(pattern).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_lazy_dfa_same_as_dfa() {
+        let nfas = nfas(&["if", "[a-z]+", "[0-9]+ (\\.[0-9]+)?"]);
+        let dfa = Dfa::new(&nfas);
+        let mut lazy = LazyDfa::new(nfas, LazyDfa::DEFAULT_BUDGET);
+        for input in ["if", "iff", "x", "42", "4.2", "4.", "", "a1"] {
+            assert_eq!(lazy.matches(input), dfa.matches(input), "input {:?}", input);
+        }
+        assert_eq!(lazy.flush_count(), 0);
+        assert_eq!(lazy.fallback_count(), 0);
+    }
+
+    #[test]
+    fn test_lazy_dfa_builds_visited_states_only() {
+        let mut lazy = LazyDfa::new(nfas(&["[a-z]+"]), LazyDfa::DEFAULT_BUDGET);
+        assert_eq!(lazy.state_count(), 0);
+        lazy.matches("abc");
+        let states = lazy.state_count();
+        let memory = lazy.memory();
+        assert!(memory > 0);
+        // The cached states and transitions are reused
+        lazy.matches("abc");
+        assert_eq!(lazy.state_count(), states);
+        assert_eq!(lazy.memory(), memory);
+    }
+
+    #[test]
+    fn test_lazy_dfa_budget() {
+        // (a|b)* a (a|b) (a|b) (a|b) needs many states, which do not fit in a tiny budget
+        let nfas = nfas(&["(a|b)* a (a|b) (a|b) (a|b)"]);
+        let dfa = Dfa::new(&nfas);
+        let mut lazy = LazyDfa::new(nfas, 4 * LazyDfa::state_size(&[(0, 0); 4]));
+        for input in ["abbb", "babababbbab", "bbbbbbbbbbbbbbbbba", "aaaa"] {
+            assert_eq!(lazy.matches(input), dfa.matches(input), "input {:?}", input);
+            assert!(lazy.memory() <= lazy.get_budget());
+        }
+        assert!(lazy.flush_count() > 0);
+        assert!(lazy.fallback_count() > 0);
+
+        lazy.set_budget(0);
+        assert_eq!(lazy.state_count(), 0);
+        assert_eq!(lazy.matches("abab"), Some(0));
+    }
+
+    #[test]
+    fn test_lazy_dfa_transitions_by_symbol() {
+        // Characters that the Nfa do not tell apart share their transitions
+        let mut lazy = LazyDfa::new(nfas(&[".*"]), 4096);
+        let input: String = (0x4e00..0x4e00 + 20_000).filter_map(char::from_u32).collect();
+        assert_eq!(lazy.matches(&input), Some(0));
+        assert_eq!(lazy.state_count(), 1);
+        assert_eq!(lazy.flush_count(), 0);
+        assert!(lazy.memory() <= lazy.get_budget());
+
+        // Transitions count in the budget: a state whose transitions do not fit flushes the cache
+        let patterns = ["(a|b|c|d|e|f|g|h)*"];
+        let mut lazy = LazyDfa::new(nfas(&patterns), LazyDfa::DEFAULT_BUDGET);
+        lazy.matches("abcdefgh");
+        let budget = lazy.memory() - TRANSITION_SIZE;
+        let mut lazy = LazyDfa::new(nfas(&patterns), budget);
+        assert_eq!(lazy.matches("abcdefgh"), Some(0));
+        assert!(lazy.memory() <= budget);
+        assert_eq!(lazy.flush_count(), 1);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod nfa;
+pub mod dfa;
+pub mod lazy_dfa;
+pub mod derivative;
+pub mod backtrack;
@@ -0,0 +1,910 @@
+use std::fmt;
+
+use crate::components::captures::Captures;
+use crate::components::class::CharClass;
+use crate::components::grammar::Symbol;
+use crate::components::items::{Assertion, Flags, Greediness, RegexComponent};
+use crate::components::regex::Regex;
+use crate::components::token::TokenSlice;
+use crate::constants::NEWLINE;
+
+/// Placeholder target of a state that is patched once the state it points to is compiled
+const UNPATCHED: usize = usize::MAX;
+
+/// enum State
+/// State of a Nfa. Char and Class states consume one character of the input, every other state is
+/// followed without consuming any input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    /// Consumes the given character
+    Char { ch: char, next: usize },
+    /// Consumes a character of the class `class` of the Nfa
+    Class { class: usize, next: usize },
+    /// Continues with both states, `first` having priority over `second`
+    Split { first: usize, second: usize },
+    /// Marks the start (or the end) of a token whose kind is the component `kind` of the Nfa
+    Token { kind: usize, start: bool, next: usize },
+    /// Marks the start (or the end) of an iteration of the capture group `group`
+    Group { group: usize, start: bool, next: usize },
+    /// Continues with `next` only if the assertion holds at the current position
+    Assert { assertion: Assertion, next: usize },
+    /// Starts an optional iteration of a loop nested in `depth` loops whose iterations can match
+    /// nothing, the loop itself included
+    Enter { depth: usize, next: usize },
+    /// Ends an iteration of a loop nested in `depth` such loops: continues with `next`, or with `exit`
+    /// when the iteration matched nothing, which ends the loop
+    Iterate { depth: usize, next: usize, exit: usize },
+    /// The whole Regex has been matched
+    Match,
+}
+
+/// Nfa:
+/// Thompson NFA compiled from a Regex, simulated on the input with a set of states, so that
+/// matching takes linear time in the length of the input and never backtracks. Threads are kept in
+/// priority order, so quantifiers are greedy and the first arm of an Or is preferred when both arms
+/// match. As in the backtracking engine, an iteration of a loop that matches nothing ends the loop,
+/// so that both engines find the same highest priority match.
+/// Every component of the Regex produces a token, except SubRegex and Flagged components, and
+/// quantifiers or Or containing one, whose inner components produce the tokens instead. SubRegex
+/// components are capture groups as well, whose iterations are recorded like the tokens (see
+/// captures). The flags of Flagged components are applied while compiling: a case-insensitive
+/// character becomes the class of its case variants.
+/// A Regex with possessive repetitions or lookarounds can't be compiled (see
+/// RegexComponent::backtracking_only).
+/// Assertions, and the word boundary after a Keyword, are checked against the characters around
+/// the current position, so a match starting after the start of the input sees what precedes it.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::nfa::Nfa, components::regex::Regex};
+///
+/// let nfa = Nfa::new(&Regex::from_str("a* a").unwrap()).unwrap();
+/// assert!(nfa.is_match("aa"));
+/// assert!(!nfa.is_match(""));
+/// let tokens = nfa.tokenize("aaa").unwrap();
+/// assert_eq!(tokens.iter().map(|t| t.text).collect::<Vec<_>>(), vec!["aa", "a"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nfa {
+    states: Vec<State>,
+    start: usize,
+    /// Components the tokens and the characters come from
+    components: Vec<RegexComponent>,
+    /// Classes of the Class states
+    classes: Vec<CharClass>,
+    /// For every Char and Class state, the component it comes from and the index of the character
+    /// in its text
+    origins: Vec<Option<(usize, usize)>>,
+    /// Names of the capture groups, by number (see Regex::group_names)
+    groups: Vec<Option<String>>,
+    /// Largest number of loops nested in each other
+    depth: usize,
+    /// Number of loops around the components being compiled
+    nesting: usize,
+}
+
+/// Implemented methods:
+/// - new: Compiles the Regex into a Nfa, or returns a CompileError for the components that only the
+///   backtracking engine can match
+/// - get_states: Returns the states of the Nfa
+/// - get_start: Returns the index of the initial state
+/// - component: Returns the component referenced by a Token state
+/// - class: Returns the class referenced by a Class state
+/// - is_match: Returns true if the Nfa matches the whole input
+/// - longest_match: Returns the end of the longest match starting at the given offset, if any, None
+///   when the offset is not at a character boundary of the input
+/// - match_at: Returns the end of the highest priority match starting at the given offset, as the
+///   backtracking engine finds it, so that a lazy quantifier stops as early as it can
+/// - tokenize: Matches the whole input and returns its tokens, or the furthest failure
+/// - captures: Matches the whole input and returns what its capture groups matched
+impl Nfa {
+    pub fn new(regex: &Regex) -> Result<Nfa, CompileError> {
+        if let Some(component) = regex.components.iter().find_map(RegexComponent::backtracking_only) {
+            return Err(CompileError { component: component.clone() });
+        }
+        let mut nfa = Nfa {
+            states: vec![State::Match],
+            start: 0,
+            components: vec![],
+            classes: vec![],
+            origins: vec![None],
+            groups: regex.group_names().into_iter().map(|name| name.map(str::to_string)).collect(),
+            depth: 0,
+            nesting: 0,
+        };
+        nfa.start = nfa.sequence(&regex.components, 0, true, 1, Flags::new());
+        Ok(nfa)
+    }
+
+    pub fn get_states(&self) -> &[State] {
+        &self.states
+    }
+
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn component(&self, kind: usize) -> &RegexComponent {
+        &self.components[kind]
+    }
+
+    pub fn class(&self, class: usize) -> &CharClass {
+        &self.classes[class]
+    }
+
+    pub fn is_match(&self, input: &str) -> bool {
+        self.run(input, 0, Goal::Whole).matched.is_some()
+    }
+
+    pub fn longest_match(&self, input: &str, start: usize) -> Option<usize> {
+        if !input.is_char_boundary(start) {
+            return None;
+        }
+        self.run(input, start, Goal::Longest).end
+    }
+
+    pub fn match_at(&self, input: &str, start: usize) -> Option<usize> {
+        if !input.is_char_boundary(start) {
+            return None;
+        }
+        self.run(input, start, Goal::First).end
+    }
+
+    /// tokenize
+    /// Returns the tokens of the highest priority match of the whole input, skipping the tokens that
+    /// matched no text. On failure, returns the furthest offset where the match failed together with
+    /// the components that could have been matched there, and the furthest offset the threads reached.
+    pub fn tokenize<'a>(&'a self, input: &'a str) -> Result<Vec<TokenSlice<'a>>, TokenizeError> {
+        let run = self.run(input, 0, Goal::Whole);
+        let Some(marks) = run.matched else {
+            return Err(TokenizeError::new(run.failure, run.furthest));
+        };
+
+        let mut tokens = vec![];
+        let mut opened = 0;
+        for (state, offset) in run.events(marks) {
+            if let State::Token { kind, start, .. } = self.states[state] {
+                if start {
+                    opened = offset;
+                } else if offset > opened {
+                    tokens.push(TokenSlice::new(&self.components[kind], &input[opened..offset], opened..offset));
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// captures
+    /// Returns what every capture group matched in the highest priority match of the whole input,
+    /// None if the input does not match. The iteration of a loop that matched nothing, and so ended
+    /// the loop, is recorded as well: `(a*)*` captures `aa` then the empty text at the end of `aa`.
+    pub fn captures<'a>(&self, input: &'a str) -> Option<Captures<'a>> {
+        let run = self.run(input, 0, Goal::Whole);
+        let mut spans = vec![vec![]; self.groups.len()];
+        spans[0].push(0..input.len());
+        let mut opened = vec![0; self.groups.len()];
+        for (state, offset) in run.events(run.matched?) {
+            if let State::Group { group, start, .. } = self.states[state] {
+                if start {
+                    opened[group] = offset;
+                } else {
+                    spans[group].push(opened[group]..offset);
+                }
+            }
+        }
+        Some(Captures::new(self.groups.clone(), input, spans))
+    }
+
+    fn push(&mut self, state: State, origin: Option<(usize, usize)>) -> usize {
+        self.states.push(state);
+        self.origins.push(origin);
+        self.states.len() - 1
+    }
+
+    /// Points the unpatched side of a Split state to the target
+    fn patch(&mut self, state: usize, target: usize) {
+        if let State::Split { first, second } = &mut self.states[state] {
+            if *first == UNPATCHED {
+                *first = target;
+            } else {
+                *second = target;
+            }
+        }
+    }
+
+    /// Split state choosing between another iteration and the state after the loop, in the order
+    /// given by the greediness
+    fn split(&mut self, iteration: usize, after: usize, greediness: Greediness) -> usize {
+        let state = match greediness {
+            Greediness::Lazy => State::Split { first: after, second: iteration },
+            _ => State::Split { first: iteration, second: after },
+        };
+        self.push(state, None)
+    }
+
+    /// Compiles the components so that they continue with `next`, returning the first state.
+    /// `group` is the number of the first capture group of the components, and `flags` the flags in
+    /// effect around them.
+    fn sequence(
+        &mut self,
+        components: &[RegexComponent],
+        next: usize,
+        tokens: bool,
+        group: usize,
+        flags: Flags,
+    ) -> usize {
+        let groups: Vec<usize> = components
+            .iter()
+            .scan(group, |group, component| {
+                let first = *group;
+                *group += Self::group_count(std::slice::from_ref(component));
+                Some(first)
+            })
+            .collect();
+        components
+            .iter()
+            .zip(groups)
+            .rev()
+            .fold(next, |next, (component, group)| self.component_state(component, next, tokens, group, flags))
+    }
+
+    /// Number of capture groups in the components
+    fn group_count(components: &[RegexComponent]) -> usize {
+        let mut groups = vec![];
+        for component in components {
+            component.groups(&mut groups);
+        }
+        groups.len()
+    }
+
+    fn component_state(
+        &mut self,
+        component: &RegexComponent,
+        next: usize,
+        tokens: bool,
+        group: usize,
+        flags: Flags,
+    ) -> usize {
+        if tokens && !Self::has_subregex(component) {
+            self.components.push(component.clone());
+            let kind = self.components.len() - 1;
+            let end = self.push(State::Token { kind, start: false, next }, None);
+            let inner = self.component_state(component, end, false, group, flags);
+            return self.push(State::Token { kind, start: true, next: inner }, None);
+        }
+
+        match component {
+            RegexComponent::Literal(value)
+            | RegexComponent::Keyword(value)
+            | RegexComponent::Identifier(value)
+            | RegexComponent::Operator(value) => {
+                self.components.push(component.clone());
+                let origin = self.components.len() - 1;
+                let next = match component.trailing_assertion() {
+                    Some(assertion) => self.push(State::Assert { assertion, next }, None),
+                    None => next,
+                };
+                let chars: Vec<char> = value.chars().collect();
+                chars.into_iter().enumerate().rev().fold(next, |next, (index, ch)| {
+                    let variants = flags.is_case_insensitive().then(|| CharClass::set(&[ch]).case_insensitive());
+                    match variants.filter(|variants| variants.get_ranges() != [(ch, ch)]) {
+                        Some(variants) => {
+                            self.classes.push(variants);
+                            self.push(State::Class { class: self.classes.len() - 1, next }, Some((origin, index)))
+                        }
+                        None => self.push(State::Char { ch, next }, Some((origin, index))),
+                    }
+                })
+            }
+            RegexComponent::ZeroOrMore(regex) => {
+                let split = self.push(State::Split { first: UNPATCHED, second: next }, None);
+                let body = self.iteration(regex, split, next, tokens, group, flags);
+                self.patch(split, body);
+                split
+            }
+            RegexComponent::OneOrMore(regex) => {
+                let split = self.push(State::Split { first: UNPATCHED, second: next }, None);
+                let body = self.iteration(regex, split, next, tokens, group, flags);
+                self.patch(split, body);
+                body
+            }
+            RegexComponent::ZeroOrOne(regex) => {
+                let body = self.sequence(&regex.components, next, tokens, group, flags);
+                self.push(State::Split { first: body, second: next }, None)
+            }
+            RegexComponent::Repeat { regex, min, max, greediness } => {
+                // The optional iterations are nested, each one able to skip straight to `next`, so
+                // that the Nfa grows linearly with the bounds: x{2,4} is x x (x (x)?)?
+                let mut rest = match max {
+                    None => {
+                        let split = self.split(UNPATCHED, next, *greediness);
+                        let body = self.iteration(regex, split, next, tokens, group, flags);
+                        self.patch(split, body);
+                        split
+                    }
+                    Some(max) => (*min..*max).fold(next, |rest, _| {
+                        let body = self.iteration(regex, rest, next, tokens, group, flags);
+                        self.split(body, next, *greediness)
+                    }),
+                };
+                for _ in 0..*min {
+                    rest = self.sequence(&regex.components, rest, tokens, group, flags);
+                }
+                rest
+            }
+            RegexComponent::Or(regex1, regex2) => {
+                let first = self.sequence(&regex1.components, next, tokens, group, flags);
+                let second_group = group + Self::group_count(&regex1.components);
+                let second = self.sequence(&regex2.components, next, tokens, second_group, flags);
+                self.push(State::Split { first, second }, None)
+            }
+            RegexComponent::SubRegex(regex) | RegexComponent::NamedSubRegex(_, regex) => {
+                let end = self.push(State::Group { group, start: false, next }, None);
+                let inner = self.sequence(&regex.components, end, tokens, group + 1, flags);
+                self.push(State::Group { group, start: true, next: inner }, None)
+            }
+            RegexComponent::Flagged(inner, regex) => {
+                self.sequence(&regex.components, next, tokens, group, inner.within(flags))
+            }
+            RegexComponent::Class(_) | RegexComponent::Dot => {
+                let class = match component {
+                    RegexComponent::Class(class) if flags.is_case_insensitive() => class.case_insensitive(),
+                    RegexComponent::Class(class) => class.clone(),
+                    _ if flags.is_dot_matches_newline() => CharClass::range('\0', char::MAX),
+                    _ => CharClass::set(&[NEWLINE]).negated(),
+                };
+                self.components.push(component.clone());
+                self.classes.push(class);
+                let origin = self.components.len() - 1;
+                self.push(State::Class { class: self.classes.len() - 1, next }, Some((origin, 0)))
+            }
+            RegexComponent::Assertion(assertion) => {
+                self.push(State::Assert { assertion: assertion.with_flags(flags), next }, None)
+            }
+            RegexComponent::Lookahead { .. } | RegexComponent::Lookbehind { .. } => {
+                unreachable!("lookarounds are rejected by Nfa::new")
+            }
+        }
+    }
+
+    /// Compiles an optional iteration of a loop, continuing with `next` after it, or with `exit` when
+    /// it matched nothing: as in the backtracking engine, an iteration that matches nothing ends the
+    /// loop instead of being tried again from the same position. Only an iteration that can match
+    /// nothing needs the Enter and Iterate states telling it.
+    fn iteration(
+        &mut self,
+        regex: &Regex,
+        next: usize,
+        exit: usize,
+        tokens: bool,
+        group: usize,
+        flags: Flags,
+    ) -> usize {
+        if !regex.components.iter().all(RegexComponent::is_nullable) {
+            return self.sequence(&regex.components, next, tokens, group, flags);
+        }
+        self.nesting += 1;
+        self.depth = self.depth.max(self.nesting);
+        let depth = self.nesting;
+        let end = self.push(State::Iterate { depth, next, exit }, None);
+        let body = self.sequence(&regex.components, end, tokens, group, flags);
+        self.nesting -= 1;
+        self.push(State::Enter { depth, next: body }, None)
+    }
+
+    /// Whether the inner components of the component produce the tokens, instead of the component itself
+    pub(crate) fn has_subregex(component: &RegexComponent) -> bool {
+        match component {
+            RegexComponent::SubRegex(_) | RegexComponent::NamedSubRegex(_, _) | RegexComponent::Flagged(_, _) => true,
+            RegexComponent::ZeroOrMore(regex)
+            | RegexComponent::OneOrMore(regex)
+            | RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::Repeat { regex, .. } => regex.components.iter().any(Self::has_subregex),
+            RegexComponent::Or(regex1, regex2) => regex1
+                .components
+                .iter()
+                .chain(regex2.components.iter())
+                .any(Self::has_subregex),
+            _ => false,
+        }
+    }
+
+    /// Adds the thread to the list, following the states that consume no input in priority order
+    fn add_thread(&self, run: &mut Run, list: &mut Vec<Thread>, thread: Thread, input: &str, offset: usize) {
+        let before = input[..offset].chars().next_back();
+        let after = input[offset..].chars().next();
+        let mut stack = vec![thread];
+        while let Some(thread) = stack.pop() {
+            // Threads in the same state go on in the same way, unless different loops started here
+            let seen = thread.state * (self.depth + 1) + thread.fresh;
+            if run.seen[seen] == run.generation {
+                continue;
+            }
+            run.seen[seen] = run.generation;
+            match self.states[thread.state] {
+                State::Split { first, second } => {
+                    stack.push(Thread { state: second, ..thread });
+                    stack.push(Thread { state: first, ..thread });
+                }
+                State::Token { next, .. } | State::Group { next, .. } => {
+                    run.marks.push((thread.marks, thread.state, offset));
+                    stack.push(Thread { state: next, marks: Some(run.marks.len() - 1), ..thread });
+                }
+                State::Assert { assertion, next } => {
+                    if assertion.holds(before, after) {
+                        stack.push(Thread { state: next, ..thread });
+                    }
+                }
+                State::Enter { depth, next } => {
+                    // The loops inside a loop that started here start here as well
+                    let fresh = if thread.fresh == 0 { depth } else { thread.fresh };
+                    stack.push(Thread { state: next, fresh, ..thread });
+                }
+                State::Iterate { depth, next, exit } => {
+                    if thread.fresh != 0 && thread.fresh <= depth {
+                        let fresh = if thread.fresh == depth { 0 } else { thread.fresh };
+                        stack.push(Thread { state: exit, fresh, ..thread });
+                    } else {
+                        stack.push(Thread { state: next, ..thread });
+                    }
+                }
+                State::Char { .. } | State::Class { .. } | State::Match => list.push(thread),
+            }
+        }
+    }
+
+    /// Whether the state consumes the character
+    pub(crate) fn consumes(&self, state: usize, ch: char) -> bool {
+        match self.states[state] {
+            State::Char { ch: expected, .. } => expected == ch,
+            State::Class { class, .. } => self.classes[class].contains(ch),
+            _ => false,
+        }
+    }
+
+    /// Simulates the Nfa from the given offset, looking for the match of the Goal. Unless only a
+    /// match of the whole input is accepted, the simulation stops once no thread is left.
+    fn run(&self, input: &str, start: usize, goal: Goal) -> Run {
+        let mut run = Run {
+            seen: vec![0; self.states.len() * (self.depth + 1)],
+            generation: 1,
+            marks: vec![],
+            matched: None,
+            end: None,
+            failure: None,
+            furthest: start,
+        };
+        let mut current = vec![];
+        self.add_thread(&mut run, &mut current, Thread { state: self.start, marks: None, fresh: 0 }, input, start);
+
+        let mut chars = input[start..].char_indices();
+        loop {
+            let (offset, ch) = match chars.next() {
+                Some((offset, ch)) => (start + offset, Some(ch)),
+                None => (input.len(), None),
+            };
+            run.generation += 1;
+            let mut next = vec![];
+            for thread in current.iter() {
+                match self.states[thread.state] {
+                    State::Match => {
+                        if ch.is_none() {
+                            run.matched = Some(thread.marks);
+                            run.end = Some(offset);
+                            return run;
+                        }
+                        match goal {
+                            Goal::Whole => run.fail(offset, "end of input".to_string()),
+                            Goal::Longest => run.end = run.end.max(Some(offset)),
+                            Goal::First => {
+                                // The threads after this one have a lower priority: only the ones
+                                // before it, already stepped, can still find a better match
+                                run.end = Some(offset);
+                                break;
+                            }
+                        }
+                    }
+                    State::Char { next: target, .. } | State::Class { next: target, .. } => {
+                        if let Some(ch) = ch.filter(|&ch| self.consumes(thread.state, ch)) {
+                            let thread = Thread { state: target, marks: thread.marks, fresh: 0 };
+                            run.furthest = offset + ch.len_utf8();
+                            self.add_thread(&mut run, &mut next, thread, input, offset + ch.len_utf8());
+                        } else if let Some((origin, index)) = self.origins[thread.state] {
+                            // The component started `index` characters before
+                            let before = input[..offset].char_indices().rev().take(index).last();
+                            let expected = Symbol::Terminal(self.components[origin].clone()).to_string();
+                            run.fail(before.map_or(offset, |(start, _)| start), expected);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            if next.is_empty() || ch.is_none() {
+                return run;
+            }
+            current = next;
+        }
+    }
+}
+
+/// CompileError:
+/// Error of Nfa::new for a Regex containing a component that only the backtracking engine can
+/// match, such as a possessive repetition or a lookaround, and of Backtracker::new for a Regex
+/// containing a Lookbehind of unbounded length, which no engine can match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub component: RegexComponent,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.component.unbounded_lookbehind() == Some(&self.component) {
+            return write!(f, "`{}` looks behind for a text of unbounded length", self.component);
+        }
+        write!(f, "`{}` can only be matched by the backtracking engine", self.component)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// TokenizeError:
+/// Failure of Nfa::tokenize, and of the backtracking engine's tokenize, to match the whole input:
+/// the furthest offset where a component failed, which is the start of the component, with the
+/// components that could have been matched there, and the furthest offset the match reached, past
+/// the failure when the component was partly matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenizeError {
+    pub offset: usize,
+    pub expected: Vec<String>,
+    pub furthest: usize,
+}
+
+impl TokenizeError {
+    /// Error of the furthest failure recorded by a matcher, if any, and the furthest offset it reached
+    pub(crate) fn new(failure: Option<(usize, Vec<String>)>, furthest: usize) -> TokenizeError {
+        let (offset, expected) = failure.unwrap_or((0, vec![]));
+        TokenizeError {
+            offset,
+            expected,
+            furthest: furthest.max(offset),
+        }
+    }
+}
+
+/// Thread of the simulation: its current state, the last token mark recorded on its path, and the
+/// depth of the outermost loop whose iteration started at the current offset, 0 if none
+#[derive(Debug, Clone, Copy)]
+struct Thread {
+    state: usize,
+    marks: Option<usize>,
+    fresh: usize,
+}
+
+/// Match a simulation of the Nfa looks for:
+/// - Whole: the highest priority match of the whole input
+/// - Longest: the longest match, whatever its priority
+/// - First: the highest priority match, wherever it ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    Whole,
+    Longest,
+    First,
+}
+
+/// State of a simulation of the Nfa
+struct Run {
+    /// Generation in which every state was last added, so that a state is added once per step
+    seen: Vec<usize>,
+    generation: usize,
+    /// Marks shared by the threads: previous mark, Token or Group state and offset
+    marks: Vec<(Option<usize>, usize, usize)>,
+    /// Marks of the thread that matched the whole input
+    matched: Option<Option<usize>>,
+    /// End of the match found so far, the longest one or the highest priority one
+    end: Option<usize>,
+    /// Furthest offset where a thread failed, with what it expected
+    failure: Option<(usize, Vec<String>)>,
+    /// Furthest offset reached by a thread
+    furthest: usize,
+}
+
+impl Run {
+    /// Token and Group states crossed by the thread whose last mark is given, with their offsets,
+    /// in the order they were crossed
+    fn events(&self, marks: Option<usize>) -> Vec<(usize, usize)> {
+        let mut events = vec![];
+        let mut mark = marks;
+        while let Some(index) = mark {
+            let (previous, state, offset) = self.marks[index];
+            events.push((state, offset));
+            mark = previous;
+        }
+        events.reverse();
+        events
+    }
+
+    fn fail(&mut self, offset: usize, expected: String) {
+        match &mut self.failure {
+            Some((furthest, _)) if *furthest > offset => (),
+            Some((furthest, list)) if *furthest == offset => {
+                if !list.contains(&expected) {
+                    list.push(expected);
+                }
+            }
+            _ => self.failure = Some((offset, vec![expected])),
+        }
+    }
+}
+
+impl fmt::Display for Nfa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, state) in self.states.iter().enumerate() {
+            let marker = if index == self.start { ">" } else { " " };
+            match state {
+                State::Char { ch, next } => writeln!(f, "{}{}: {:?} -> {}", marker, index, ch, next)?,
+                State::Class { class, next } => {
+                    writeln!(f, "{}{}: {} -> {}", marker, index, self.classes[*class], next)?
+                }
+                State::Split { first, second } => writeln!(f, "{}{}: split {}, {}", marker, index, first, second)?,
+                State::Token { kind, start, next } => {
+                    let side = if *start { "start" } else { "end" };
+                    writeln!(f, "{}{}: token {} {} -> {}", marker, index, side, kind, next)?
+                }
+                State::Group { group, start, next } => {
+                    let side = if *start { "start" } else { "end" };
+                    writeln!(f, "{}{}: group {} {} -> {}", marker, index, side, group, next)?
+                }
+                State::Assert { assertion, next } => writeln!(f, "{}{}: {} -> {}", marker, index, assertion, next)?,
+                State::Enter { depth, next } => writeln!(f, "{}{}: enter {} -> {}", marker, index, depth, next)?,
+                State::Iterate { depth, next, exit } => {
+                    writeln!(f, "{}{}: iterate {} -> {}, {}", marker, index, depth, next, exit)?
+                }
+                State::Match => writeln!(f, "{}{}: match", marker, index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn nfa(pattern: &str) -> Nfa {
+        Nfa::new(&Regex::from_str(pattern).unwrap()).unwrap()
+    }
+
+    fn texts(nfa: &Nfa, input: &str) -> Vec<String> {
+        nfa.tokenize(input)
+            .unwrap()
+            .iter()
+            .map(|token| token.text.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_nfa_backtracking_semantics() {
+        // A greedy loop followed by the same text must give some of it back
+        let star = nfa("a* a");
+        assert!(star.is_match("a"));
+        assert!(star.is_match("aaaa"));
+        assert_eq!(texts(&star, "aaaa"), vec!["aaa", "a"]);
+
+        let plus = nfa("(ab)+ ab c");
+        assert!(plus.is_match("ababc"));
+        assert!(!plus.is_match("abc"));
+    }
+
+    #[test]
+    fn test_nfa_or() {
+        let or = nfa("x (a|bc) y");
+        assert!(or.is_match("xay"));
+        assert!(or.is_match("xbcy"));
+        // An Or is not optional: one of its arms has to match
+        assert!(!or.is_match("xy"));
+        assert!(!or.is_match("xby"));
+        assert_eq!(texts(&or, "xbcy"), vec!["x", "bc", "y"]);
+    }
+
+    #[test]
+    fn test_nfa_nullable_loops() {
+        // Loops whose body can match nothing terminate
+        let nested = Nfa::new(&Regex::new(vec![RegexComponent::ZeroOrMore(Regex::new(vec![
+            RegexComponent::ZeroOrOne(Regex::new(vec![RegexComponent::Literal("a".to_string())])),
+        ]))]))
+        .unwrap();
+        assert!(nested.is_match(""));
+        assert!(nested.is_match("aaa"));
+        assert!(!nested.is_match("ab"));
+        assert_eq!(texts(&nested, ""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_nfa_repeat() {
+        let nfa = nfa("\\\\u\\{ [0-9a-f]{4} \\}");
+        assert!(nfa.is_match("\\u{00e9}"));
+        assert!(!nfa.is_match("\\u{e9}"));
+        assert!(!nfa.is_match("\\u{000e9}"));
+
+        let bounded = self::nfa("a{2,4} b{2,}");
+        assert!(!bounded.is_match("abb"));
+        assert!(bounded.is_match("aabb"));
+        assert!(bounded.is_match("aaaabbbbb"));
+        assert!(!bounded.is_match("aaaaabb"));
+        assert!(!bounded.is_match("aab"));
+        // The optional iterations are greedy
+        assert_eq!(texts(&self::nfa("a{1,3} a*"), "aaaa"), vec!["aaa", "a"]);
+        // The states grow linearly with the bounds
+        assert!(self::nfa("(?:ab){100,200}").get_states().len() < 1000);
+    }
+
+    #[test]
+    fn test_nfa_lazy_and_possessive() {
+        // A lazy repetition takes as little as the rest of the Regex lets it
+        let comment = nfa("/\\* [a-z */]*? \\*/");
+        assert_eq!(texts(&comment, "/* a */"), vec!["/*", " a ", "*/"]);
+        assert_eq!(texts(&nfa("a{1,3}? a*"), "aaaa"), vec!["a", "aaa"]);
+        assert!(nfa("a+? b").is_match("aaab"));
+
+        let error = Nfa::new(&Regex::from_str("x (a|b*+)").unwrap()).unwrap_err();
+        assert_eq!(error.component.to_string(), "b*+");
+        assert_eq!(error.to_string(), "`b*+` can only be matched by the backtracking engine");
+    }
+
+    #[test]
+    fn test_nfa_longest_match() {
+        let nfa = nfa("ab* c?");
+        assert_eq!(nfa.longest_match("abbbd", 0), Some(4));
+        assert_eq!(nfa.longest_match("xabc", 1), Some(4));
+        assert_eq!(nfa.longest_match("xabc", 0), None);
+
+        // The highest priority match is the one the backtracking engine finds first
+        let comment = self::nfa("/\\* .*? \\*/");
+        assert_eq!(comment.longest_match("/* a */ x /* b */", 0), Some(17));
+        assert_eq!(comment.match_at("/* a */ x /* b */", 0), Some(7));
+        assert_eq!(self::nfa("(a|ab) c?").match_at("abc", 0), Some(1));
+        assert_eq!(self::nfa("a*").match_at("xaa", 1), Some(3));
+        assert_eq!(self::nfa("a b").match_at("ac", 0), None);
+        // An iteration that matches nothing ends the loop, as in the backtracking engine
+        assert_eq!(self::nfa("(?:a??)*").match_at("aa", 0), Some(0));
+        assert_eq!(self::nfa("(?:a*?)*").match_at("aa", 0), Some(0));
+        assert_eq!(self::nfa("(?:a|b??)*").match_at("ab", 0), Some(1));
+        assert_eq!(self::nfa("(?:a??)*").longest_match("aa", 0), Some(2));
+    }
+
+    #[test]
+    fn test_nfa_unicode() {
+        let nfa = Nfa::new(&Regex::new(vec![
+            RegexComponent::Literal("é".to_string()),
+            RegexComponent::OneOrMore(Regex::new(vec![RegexComponent::Literal("ü".to_string())])),
+        ]))
+        .unwrap();
+        let tokens = nfa.tokenize("éüü").unwrap();
+        assert_eq!(tokens[1].span, 2..6);
+        assert!(!nfa.is_match("é"));
+        // Offsets inside a character start no match
+        assert_eq!(nfa.longest_match("éü", 1), None);
+    }
+
+    #[test]
+    fn test_nfa_classes() {
+        let nfa = nfa("[a-z_] \\w* = [^;]+ ;");
+        assert!(nfa.is_match("x_1=été;"));
+        assert!(!nfa.is_match("1x=a;"));
+        assert!(!nfa.is_match("x=;"));
+        assert_eq!(texts(&nfa, "ab=12;"), vec!["a", "b", "=", "12", ";"]);
+        let error = TokenizeError { offset: 3, expected: vec!["/[^;]/".to_string()], furthest: 3 };
+        assert_eq!(nfa.tokenize("ab=;"), Err(error));
+    }
+
+    #[test]
+    fn test_nfa_assertions() {
+        // A Keyword ending with a word character can't be followed by another one
+        let keyword = Nfa::new(&Regex::new(vec![RegexComponent::Keyword("if".to_string())])).unwrap();
+        assert!(keyword.is_match("if"));
+        assert_eq!(keyword.longest_match("iffy", 0), None);
+        assert_eq!(keyword.longest_match("if(", 0), Some(2));
+
+        let word = nfa("\\b [a-z]+ \\b");
+        assert_eq!(word.longest_match("ab cd", 0), Some(2));
+        // The character before the start of the search is taken into account
+        assert_eq!(word.longest_match("ab cd", 1), None);
+        assert_eq!(word.longest_match("ab cd", 3), Some(5));
+        assert_eq!(nfa("^ a").longest_match("aa", 1), None);
+        assert!(!nfa("a $ b").is_match("ab"));
+
+        let lines = Nfa::new(&Regex::new(vec![
+            RegexComponent::Literal("a".to_string()),
+            RegexComponent::Assertion(Assertion::EndOfLine),
+            RegexComponent::Literal("\n".to_string()),
+            RegexComponent::Assertion(Assertion::StartOfLine),
+            RegexComponent::Literal("b".to_string()),
+        ]))
+        .unwrap();
+        assert!(lines.is_match("a\nb"));
+        assert_eq!(lines.tokenize("a\nb").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_nfa_flags() {
+        let select = Regex::new(vec![RegexComponent::Keyword("select".to_string())]);
+        let insensitive = Nfa::new(&select.clone().with_flags(Flags::new().with_case_insensitive(true))).unwrap();
+        assert!(insensitive.is_match("SELECT") && insensitive.is_match("sElEcT"));
+        assert_eq!(insensitive.longest_match("Selection", 0), None);
+        assert_eq!(insensitive.tokenize("SELECT").unwrap()[0].kind, &select.components[0]);
+
+        // Case variants can be longer or shorter than the text: `ſ` is two bytes, the Kelvin sign three
+        let kelvin = nfa("(?i) sk [a-z]+");
+        assert!(kelvin.is_match("ſ\u{212a}IP") && !kelvin.is_match("sk1"));
+        let error = TokenizeError { offset: 5, expected: vec!["/[a-z]/".to_string()], furthest: 5 };
+        assert_eq!(kelvin.tokenize("ſ\u{212a}"), Err(error));
+        let error = TokenizeError { offset: 0, expected: vec!["\"sk\"".to_string()], furthest: 2 };
+        assert_eq!(kelvin.tokenize("ſx"), Err(error));
+        assert!(nfa("a (?i:b) c").is_match("aBc"));
+        assert!(!nfa("a (?i:b) c").is_match("ABc"));
+        assert!(!nfa("(?i) a (?-i:b)").is_match("AB"));
+        assert!(nfa("(?i)[^a-z]").is_match("1") && !nfa("(?i)[^a-z]").is_match("Q"));
+
+        assert!(nfa("a . b").is_match("a-b") && !nfa("a . b").is_match("a\nb"));
+        assert!(nfa("(?s) a . b").is_match("a\nb"));
+
+        let lines = nfa("(?m) ^ [a-z]+ $ \\n? ^ [a-z]+ $");
+        assert!(lines.is_match("ab\ncd"));
+        assert!(!nfa("^ [a-z]+ $ \\n? ^ [a-z]+ $").is_match("ab\ncd"));
+        // `\A` and `\z` ignore the multiline flag
+        assert!(!nfa("(?m) a \\n \\A b").is_match("a\nb"));
+        assert!(nfa("(?m) a $ \\n b \\z").is_match("a\nb"));
+    }
+
+    #[test]
+    fn test_nfa_captures() {
+        // Groups are numbered by their opening parenthesis, the groups inside a group after it
+        let nfa = nfa("((a) b) (?<c>c|d) (x|(y))");
+        let captures = nfa.captures("abdx").unwrap();
+        assert_eq!(captures.len(), 6);
+        assert_eq!(captures.get(0).unwrap().text, "abdx");
+        assert_eq!(captures.get(1).unwrap().span, 0..2);
+        assert_eq!(captures.get(2).unwrap().span, 0..1);
+        assert_eq!(captures.name("c").unwrap().span, 2..3);
+        // A group of alternatives captures like any other group, and the arm that was not taken
+        // captures nothing
+        assert_eq!(captures.get(4).unwrap().span, 3..4);
+        assert_eq!(captures.get(5), None);
+        assert_eq!(nfa.captures("abdy").unwrap().get(5).unwrap().span, 3..4);
+        assert_eq!(nfa.captures("abd"), None);
+        let alternatives = self::nfa("(a|ab)(c|bcd)(d*)").captures("abcd").unwrap();
+        assert_eq!(alternatives.len(), 4);
+        assert_eq!(alternatives.get(1).unwrap().span, 0..1);
+        assert_eq!(alternatives.get(2).unwrap().span, 1..4);
+        assert_eq!(alternatives.get(3).unwrap().span, 4..4);
+
+        // Every iteration of a group inside a repetition is kept
+        let items = self::nfa("(?<item>[a-z]+ ,?)+");
+        let iterations: Vec<&str> = items.captures("ab,c").unwrap().iterations(1).iter().map(|c| c.text).collect();
+        assert_eq!(iterations, vec!["ab,", "c"]);
+        assert_eq!(self::nfa("(?<d>[0-9]){2,3}").captures("123").unwrap().iterations(1).len(), 3);
+        let repeated = self::nfa("(x y)*").captures("xyxy").unwrap();
+        assert_eq!(repeated.iterations(1).iter().map(|c| c.span.clone()).collect::<Vec<_>>(), vec![0..2, 2..4]);
+        assert_eq!(self::nfa("(?:x y)*").captures("xyxy").unwrap().len(), 1);
+        let empty = self::nfa("(a*)*").captures("aa").unwrap();
+        assert_eq!(empty.iterations(1).iter().map(|c| c.span.clone()).collect::<Vec<_>>(), vec![0..2, 2..2]);
+        // and the groups that matched nothing as well
+        let lazy = self::nfa("(?<a>[a-z]*?) (?<b>[a-z]*)").captures("abc").unwrap();
+        assert_eq!((lazy.name("a").unwrap().span.clone(), lazy.name("b").unwrap().text), (0..0, "abc"));
+        // Capture groups do not change the tokens
+        assert_eq!(texts(&self::nfa("(?<x>a b) c"), "abc"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_nfa_failure() {
+        let nfa = nfa("ab (cd|ce)");
+        // The failure is at the start of the literals, which were partly matched up to the `x`
+        let error = nfa.tokenize("abcx").unwrap_err();
+        assert_eq!((error.offset, error.furthest), (2, 3));
+        assert_eq!(error.expected, vec!["\"cd\"".to_string(), "\"ce\"".to_string()]);
+        let error = TokenizeError { offset: 4, expected: vec!["end of input".to_string()], furthest: 4 };
+        assert_eq!(nfa.tokenize("abcdd"), Err(error));
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+use std::ops::Range;
+
+/// Capture:
+/// Text matched by one iteration of a capture group, borrowed from the input, with its byte range
+/// in the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Capture<'a> {
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Capture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}..{}", self.text, self.span.start, self.span.end)
+    }
+}
+
+/// Captures:
+/// What every capture group of a Regex matched (see regex::Regex::group_names for their numbers),
+/// group 0 being the whole match. A group has one Capture per time it was matched: none if it did
+/// not take part in the match, such as the arm of an Or that was not taken, and one per iteration
+/// when it is inside a repetition.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{automata::nfa::Nfa, components::regex::Regex};
+///
+/// let nfa = Nfa::new(&Regex::from_str("(?<key>[a-z]+) = (?<value>[0-9] ,?)*").unwrap()).unwrap();
+/// let captures = nfa.captures("x=1,2").unwrap();
+/// assert_eq!(captures.name("key").unwrap().text, "x");
+/// assert_eq!(captures.get(2).unwrap().span, 4..5);
+/// let values: Vec<&str> = captures.iterations(2).iter().map(|capture| capture.text).collect();
+/// assert_eq!(values, vec!["1,", "2"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'a> {
+    names: Vec<Option<String>>,
+    groups: Vec<Vec<Capture<'a>>>,
+}
+
+/// Implemented methods:
+/// - new: Creates the Captures of the groups with the given names from their spans in the input
+/// - len: Returns the number of groups, group 0 included
+/// - is_empty: Returns true if there is no group, not even group 0
+/// - get: Returns the last iteration of a group, if it matched
+/// - name: Returns the last iteration of the group with the given name, if it matched
+/// - iterations: Returns all the iterations of a group, in the order of the input
+/// - index_of: Returns the number of the group with the given name
+/// - shifted: Returns the Captures with every span moved by the given offset in a larger input
+impl<'a> Captures<'a> {
+    pub fn new(names: Vec<Option<String>>, input: &'a str, spans: Vec<Vec<Range<usize>>>) -> Captures<'a> {
+        let groups = spans
+            .into_iter()
+            .map(|spans| {
+                spans
+                    .into_iter()
+                    .map(|span| Capture { text: &input[span.clone()], span })
+                    .collect()
+            })
+            .collect();
+        Captures { names, groups }
+    }
+
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Capture<'a>> {
+        self.groups.get(index)?.last()
+    }
+
+    pub fn name(&self, name: &str) -> Option<&Capture<'a>> {
+        self.get(self.index_of(name)?)
+    }
+
+    pub fn iterations(&self, index: usize) -> &[Capture<'a>] {
+        self.groups.get(index).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|other| other.as_deref() == Some(name))
+    }
+
+    pub fn shifted(mut self, offset: usize) -> Captures<'a> {
+        for capture in self.groups.iter_mut().flatten() {
+            capture.span = capture.span.start + offset..capture.span.end + offset;
+        }
+        self
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_captures() {
+        let names = vec![None, Some("key".to_string()), None];
+        let captures = Captures::new(names, "ab=1,2", vec![vec![0..6], vec![0..2], vec![3..4, 5..6]]);
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures.get(0).unwrap().text, "ab=1,2");
+        assert_eq!(captures.name("key"), Some(&Capture { text: "ab", span: 0..2 }));
+        assert_eq!(captures.get(2).unwrap().to_string(), "\"2\" at 5..6");
+        assert_eq!(captures.iterations(2).len(), 2);
+        assert_eq!(captures.iterations(3), &[]);
+        assert_eq!(captures.get(3), None);
+        assert_eq!(captures.name("value"), None);
+        assert_eq!(captures.shifted(2).get(1).unwrap().span, 2..4);
+    }
+}
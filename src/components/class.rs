@@ -0,0 +1,548 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::components::unicode_tables::{GeneralCategory, Script, GENERAL_CATEGORIES, SCRIPTS};
+use crate::constants::{
+    ALPHANUMS, CARRIAGE_RETURN, DIGITS, LOWECASE_LETTERS, NEWLINE, TAB, UNDERSCORE,
+    UPPERCASE_LETTERS, WHITE,
+};
+
+/// CharClass:
+/// Set of characters matched by a Class component, stored as sorted and disjoint inclusive ranges.
+/// A negated class matches every character that is not in its ranges. Classes built from a
+/// UnicodeClass keep its name, to be displayed as `\p{Name}` rather than as their many ranges.
+///
+/// ```rust
+/// use pilator::components::class::{CharClass, UnicodeClass};
+/// use pilator::components::unicode_tables::Script;
+///
+/// let hex = CharClass::digits().union(&CharClass::range('a', 'f'));
+/// assert!(hex.contains('7') && hex.contains('c') && !hex.contains('g'));
+/// assert_eq!(hex.to_string(), "[0-9a-f]");
+/// let greek = CharClass::unicode(UnicodeClass::Script(Script::Greek));
+/// assert!(greek.contains('λ') && !greek.contains('l'));
+/// assert_eq!(greek.to_string(), "\\p{Greek}");
+/// let not_quote = CharClass::set(&['"']).negated();
+/// assert!(not_quote.contains('x') && !not_quote.contains('"'));
+/// assert_eq!(not_quote.to_string(), "[^\"]");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharClass {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+    name: Option<&'static str>,
+}
+
+/// Implemented methods:
+/// - new: Creates the class of the given inclusive ranges, which are sorted and merged
+/// - set: Creates the class of the given characters
+/// - range: Creates the class of the characters from start to end, both included
+/// - digits, lowercase, uppercase, letters, alphanumerics, word, whitespace: Predefined classes,
+///   built from the constants module
+/// - unicode_word: Creates the class of the characters of words for the word boundary assertions
+/// - is_word: Returns true if the character is in CharClass::unicode_word
+/// - unicode: Creates the class of a UnicodeClass
+/// - negated: Returns the class matching every character this class does not match
+/// - union: Returns the class matching the characters of both classes
+/// - get_ranges: Returns the ranges of the class, before negation
+/// - is_negated: Returns true if the class is negated
+/// - contains: Returns true if the class matches the character
+/// - contains_ignoring_case: Returns true if the class matches the character or one with the same
+///   case folding (see fold_case)
+/// - case_insensitive: Returns the class matching case-insensitively what this class matches
+/// - matched_ranges: Returns the sorted ranges of the characters the class matches, after negation
+impl CharClass {
+    pub fn new(ranges: Vec<(char, char)>) -> CharClass {
+        CharClass {
+            ranges: normalize(ranges),
+            negated: false,
+            name: None,
+        }
+    }
+
+    pub fn set(chars: &[char]) -> CharClass {
+        CharClass::new(chars.iter().map(|&ch| (ch, ch)).collect())
+    }
+
+    pub fn range(start: char, end: char) -> CharClass {
+        CharClass::new(vec![(start, end)])
+    }
+
+    pub fn digits() -> CharClass {
+        let digits: Vec<char> = DIGITS
+            .iter()
+            .filter_map(|&digit| char::from_digit(digit as u32, 10))
+            .collect();
+        CharClass::set(&digits)
+    }
+
+    pub fn lowercase() -> CharClass {
+        CharClass::set(&LOWECASE_LETTERS)
+    }
+
+    pub fn uppercase() -> CharClass {
+        CharClass::set(&UPPERCASE_LETTERS)
+    }
+
+    pub fn letters() -> CharClass {
+        CharClass::lowercase().union(&CharClass::uppercase())
+    }
+
+    pub fn alphanumerics() -> CharClass {
+        CharClass::set(&ALPHANUMS)
+    }
+
+    /// Characters of identifiers: alphanumerics and `_`
+    pub fn word() -> CharClass {
+        CharClass::alphanumerics().union(&CharClass::set(&[UNDERSCORE]))
+    }
+
+    /// Characters of words for the word boundary assertions: Unicode alphanumerics and `_`
+    pub fn unicode_word() -> CharClass {
+        let ranges = WORD_RANGES.get_or_init(|| ranges_where(CharClass::is_word));
+        CharClass::new(ranges.clone())
+    }
+
+    /// Whether the character is in CharClass::unicode_word, without building the class
+    pub fn is_word(ch: char) -> bool {
+        ch.is_alphanumeric() || ch == UNDERSCORE
+    }
+
+    pub fn whitespace() -> CharClass {
+        CharClass::set(&[WHITE, TAB, NEWLINE, CARRIAGE_RETURN])
+    }
+
+    pub fn unicode(class: UnicodeClass) -> CharClass {
+        CharClass {
+            ranges: class.ranges().to_vec(),
+            negated: false,
+            name: Some(class.name()),
+        }
+    }
+
+    pub fn negated(self) -> CharClass {
+        CharClass {
+            negated: !self.negated,
+            ..self
+        }
+    }
+
+    pub fn union(&self, other: &CharClass) -> CharClass {
+        let mut ranges = self.matched_ranges();
+        ranges.extend(other.matched_ranges());
+        CharClass::new(ranges)
+    }
+
+    pub fn get_ranges(&self) -> &[(char, char)] {
+        &self.ranges
+    }
+
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    pub fn contains(&self, ch: char) -> bool {
+        self.in_ranges(ch) != self.negated
+    }
+
+    pub fn contains_ignoring_case(&self, ch: char) -> bool {
+        let inside = match case_classes().variants.get(&fold_case(ch)) {
+            Some(variants) => variants.iter().any(|&variant| self.in_ranges(variant)),
+            None => self.in_ranges(ch),
+        };
+        inside != self.negated
+    }
+
+    /// case_insensitive
+    /// The ranges of the returned class also contain every character with the same case folding as
+    /// one of theirs: its contains is the contains_ignoring_case of this class. A negated class
+    /// stays negated, so that `[^k]` rejects `K` as well.
+    pub fn case_insensitive(&self) -> CharClass {
+        let classes = case_classes();
+        let mut ranges = self.ranges.clone();
+        for &(start, end) in self.ranges.iter() {
+            for folded in classes.folded.range(start..=end).map(|(_, folded)| folded) {
+                ranges.extend(classes.variants[folded].iter().map(|&variant| (variant, variant)));
+            }
+        }
+        let ranges = normalize(ranges);
+        // A UnicodeClass keeps its name only if no character was added
+        let name = self.name.filter(|_| ranges == self.ranges);
+        CharClass { ranges, negated: self.negated, name }
+    }
+
+    /// Whether the character is in the ranges of the class, before negation
+    fn in_ranges(&self, ch: char) -> bool {
+        let index = self.ranges.partition_point(|&(_, end)| end < ch);
+        self.ranges.get(index).is_some_and(|&(start, _)| start <= ch)
+    }
+
+    pub fn matched_ranges(&self) -> Vec<(char, char)> {
+        if !self.negated {
+            return self.ranges.clone();
+        }
+        let mut complement = vec![];
+        let mut next = Some('\0');
+        for &(start, end) in self.ranges.iter() {
+            if let Some(first) = next.filter(|&first| first < start) {
+                complement.push((first, previous_char(start)));
+            }
+            next = next_char(end);
+        }
+        if let Some(first) = next {
+            complement.push((first, char::MAX));
+        }
+        complement
+    }
+}
+
+/// Simple case folding of a character, which maps the characters that only differ by their case to
+/// the same one: `K`, `k` and the Kelvin sign `\u{212a}` all fold to `k`. It is computed from the
+/// case mappings of the standard library, as the lowercase of the uppercase of the character when
+/// both are single characters, and its lowercase otherwise. The dotless `ı` is left alone, as Unicode
+/// only folds it in Turkish.
+pub fn fold_case(ch: char) -> char {
+    fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+        let first = chars.next()?;
+        chars.next().is_none().then_some(first)
+    }
+    if ch == DOTLESS_I {
+        return ch;
+    }
+    single(ch.to_uppercase())
+        .and_then(|upper| single(upper.to_lowercase()))
+        .or_else(|| single(ch.to_lowercase()))
+        .unwrap_or(ch)
+}
+
+const DOTLESS_I: char = '\u{131}';
+
+/// Characters that share their case folding with other characters: the folding of each of them,
+/// and the characters with each folding
+struct CaseClasses {
+    folded: BTreeMap<char, char>,
+    variants: HashMap<char, Vec<char>>,
+}
+
+/// CaseClasses of all the characters, computed the first time they are needed
+fn case_classes() -> &'static CaseClasses {
+    static CASE_CLASSES: OnceLock<CaseClasses> = OnceLock::new();
+    CASE_CLASSES.get_or_init(|| {
+        let mut variants: HashMap<char, Vec<char>> = HashMap::new();
+        for ch in ('\0'..=char::MAX).filter(|&ch| fold_case(ch) != ch) {
+            variants.entry(fold_case(ch)).or_insert_with(|| vec![fold_case(ch)]).push(ch);
+        }
+        let folded = variants
+            .iter()
+            .flat_map(|(&folded, chars)| chars.iter().map(move |&ch| (ch, folded)))
+            .collect();
+        CaseClasses { folded, variants }
+    })
+}
+
+/// Sorts the ranges and merges the ones that overlap or touch
+fn normalize(mut ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    ranges.retain(|(start, end)| start <= end);
+    ranges.sort();
+    let mut merged: Vec<(char, char)> = vec![];
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last)) if next_char(*last).is_none_or(|next| next >= start) => {
+                *last = (*last).max(end)
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Character following the given one, skipping the surrogates, None after char::MAX
+fn next_char(ch: char) -> Option<char> {
+    match ch {
+        '\u{d7ff}' => Some('\u{e000}'),
+        ch => char::from_u32(ch as u32 + 1),
+    }
+}
+
+/// Character preceding the given one, skipping the surrogates. The given one is never '\0'.
+fn previous_char(ch: char) -> char {
+    match ch {
+        '\u{e000}' => '\u{d7ff}',
+        ch => char::from_u32(ch as u32 - 1).unwrap_or('\0'),
+    }
+}
+
+/// enum UnicodeClass
+/// Unicode classes, written `\p{Name}` in the textual regex syntax:
+/// - the Alphabetic, Lowercase, Uppercase and White_Space properties, given by the standard library
+/// - a general category, such as `\p{L}` or `\p{Letter}` for the letters and `\p{Nd}` for the
+///   decimal digits (see GeneralCategory)
+/// - a script, such as `\p{Greek}` or `\p{Grek}` (see Script)
+///
+/// The categories and scripts come from the tables of components::unicode_tables, generated from
+/// the Unicode Character Database 14.0.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnicodeClass {
+    Alphabetic,
+    Lowercase,
+    Uppercase,
+    Whitespace,
+    Category(GeneralCategory),
+    Script(Script),
+}
+
+/// Every property of the standard library, in declaration order, with the names it can be
+/// written with in `\p{...}`, the first one being the name it is displayed with
+const PROPERTIES: [(UnicodeClass, &[&str]); 4] = [
+    (UnicodeClass::Alphabetic, &["Alphabetic", "Alpha"]),
+    (UnicodeClass::Lowercase, &["Lowercase", "Lower"]),
+    (UnicodeClass::Uppercase, &["Uppercase", "Upper"]),
+    (UnicodeClass::Whitespace, &["White_Space", "Whitespace", "Space"]),
+];
+
+/// Ranges of every property of the standard library, computed the first time they are needed
+static PROPERTY_RANGES: [OnceLock<Vec<(char, char)>>; 4] = [const { OnceLock::new() }; 4];
+
+/// Ranges of CharClass::unicode_word, computed the first time they are needed
+static WORD_RANGES: OnceLock<Vec<(char, char)>> = OnceLock::new();
+
+/// Implemented methods:
+/// - from_name: Returns the class written with the given name, if any
+/// - name: Returns the name the class is displayed with
+/// - ranges: Returns the sorted ranges of the characters of the class
+impl UnicodeClass {
+    pub fn from_name(name: &str) -> Option<UnicodeClass> {
+        let properties = PROPERTIES.iter().copied();
+        let categories = GENERAL_CATEGORIES
+            .iter()
+            .map(|&(category, names, _)| (UnicodeClass::Category(category), names));
+        let scripts = SCRIPTS.iter().map(|&(script, names, _)| (UnicodeClass::Script(script), names));
+        properties
+            .chain(categories)
+            .chain(scripts)
+            .find(|(_, names)| names.contains(&name))
+            .map(|(class, _)| class)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UnicodeClass::Category(category) => GENERAL_CATEGORIES[category as usize].1[0],
+            UnicodeClass::Script(script) => SCRIPTS[script as usize].1[0],
+            property => PROPERTIES[property.index()].1[0],
+        }
+    }
+
+    pub fn ranges(self) -> &'static [(char, char)] {
+        match self {
+            UnicodeClass::Category(category) => GENERAL_CATEGORIES[category as usize].2,
+            UnicodeClass::Script(script) => SCRIPTS[script as usize].2,
+            property => PROPERTY_RANGES[property.index()].get_or_init(|| match property {
+                UnicodeClass::Alphabetic => ranges_where(char::is_alphabetic),
+                UnicodeClass::Lowercase => ranges_where(char::is_lowercase),
+                UnicodeClass::Uppercase => ranges_where(char::is_uppercase),
+                _ => ranges_where(char::is_whitespace),
+            }),
+        }
+    }
+
+    /// Index of a property of the standard library in PROPERTIES
+    fn index(self) -> usize {
+        PROPERTIES
+            .iter()
+            .position(|&(class, _)| class == self)
+            .unwrap_or(0)
+    }
+}
+
+/// Sorted ranges of the characters satisfying the predicate
+fn ranges_where(predicate: fn(char) -> bool) -> Vec<(char, char)> {
+    let mut ranges: Vec<(char, char)> = vec![];
+    for ch in ('\0'..=char::MAX).filter(|&ch| predicate(ch)) {
+        match ranges.last_mut() {
+            Some((_, last)) if next_char(*last) == Some(ch) => *last = ch,
+            _ => ranges.push((ch, ch)),
+        }
+    }
+    ranges
+}
+
+fn write_class_char(f: &mut fmt::Formatter<'_>, ch: char) -> fmt::Result {
+    match ch {
+        '\t' => write!(f, "\\t"),
+        '\n' => write!(f, "\\n"),
+        '\r' => write!(f, "\\r"),
+        '\\' | ']' | '[' | '^' | '-' => write!(f, "\\{}", ch),
+        ch => write!(f, "{}", ch),
+    }
+}
+
+/// Display implementation: the class is written in the textual regex syntax, such as `[^a-z_]`
+/// or `\P{Greek}`
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name {
+            let escape = if self.negated { 'P' } else { 'p' };
+            return write!(f, "\\{}{{{}}}", escape, name);
+        }
+        write!(f, "[")?;
+        if self.negated {
+            write!(f, "^")?;
+        }
+        for &(start, end) in self.ranges.iter() {
+            write_class_char(f, start)?;
+            if start != end {
+                write!(f, "-")?;
+                write_class_char(f, end)?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_char_class_ranges() {
+        let class = CharClass::new(vec![('m', 'z'), ('a', 'c'), ('d', 'f'), ('b', 'b')]);
+        assert_eq!(class.get_ranges(), &[('a', 'f'), ('m', 'z')]);
+        assert!(class.contains('e') && class.contains('z'));
+        assert!(!class.contains('g') && !class.contains('A'));
+        assert_eq!(CharClass::digits().get_ranges(), &[('0', '9')]);
+        assert_eq!(CharClass::word().to_string(), "[0-9A-Z_a-z]");
+        let word = CharClass::unicode_word();
+        assert!(['é', 'λ', '中', '٣', '_', 'Z'].iter().all(|&ch| word.contains(ch) && CharClass::is_word(ch)));
+        assert!(['-', ' ', '€'].iter().all(|&ch| !word.contains(ch) && !CharClass::is_word(ch)));
+        assert_eq!(CharClass::whitespace().to_string(), "[\\t-\\n\\r ]");
+    }
+
+    #[test]
+    fn test_char_class_unicode() {
+        let letters = CharClass::unicode(UnicodeClass::Alphabetic);
+        assert!(['a', 'É', 'ß', 'λ', 'ж', 'م', '中', 'ㄱ'].iter().all(|&ch| letters.contains(ch)));
+        assert!(['1', '_', ' ', '€', '٣'].iter().all(|&ch| !letters.contains(ch)));
+        let space = CharClass::unicode(UnicodeClass::Whitespace);
+        assert!(space.contains('\u{a0}') && space.contains('\u{3000}') && !space.contains('x'));
+        assert!(CharClass::unicode(UnicodeClass::Uppercase).contains('Ж'));
+        assert!(!CharClass::unicode(UnicodeClass::Lowercase).contains('Ж'));
+
+        // General categories
+        let category = |category| CharClass::unicode(UnicodeClass::Category(category));
+        let letters = category(GeneralCategory::L);
+        assert!(['a', 'É', 'ß', 'λ', 'ж', 'م', '中', 'ㄱ', 'ǅ', 'ʰ'].iter().all(|&ch| letters.contains(ch)));
+        // Unlike the Alphabetic property, the letters have no letter numbers nor marks
+        assert!(['Ⅻ', '\u{5b0}', '1', '_'].iter().all(|&ch| !letters.contains(ch)));
+        assert!(category(GeneralCategory::Lu).contains('Ж') && !category(GeneralCategory::Lu).contains('ж'));
+        assert!(category(GeneralCategory::Ll).contains('ß') && category(GeneralCategory::Lt).contains('ǅ'));
+        assert!(['a', 'Z', 'ǅ'].iter().all(|&ch| category(GeneralCategory::LC).contains(ch)));
+        assert!(!category(GeneralCategory::LC).contains('中') && category(GeneralCategory::Lo).contains('中'));
+        let digits = category(GeneralCategory::Nd);
+        assert!(['7', '٣', '\u{1d7ce}'].iter().all(|&ch| digits.contains(ch)));
+        assert!(!digits.contains('Ⅻ') && !digits.contains('½') && !digits.contains('x'));
+        let numbers = category(GeneralCategory::N);
+        assert!(['7', '٣', 'Ⅻ', '½'].iter().all(|&ch| numbers.contains(ch)));
+        assert!(category(GeneralCategory::Nl).contains('Ⅻ') && category(GeneralCategory::No).contains('½'));
+        assert!(category(GeneralCategory::Pd).contains('-') && category(GeneralCategory::Ps).contains('('));
+        assert!(category(GeneralCategory::Sc).contains('€') && category(GeneralCategory::Sm).contains('+'));
+        assert!(category(GeneralCategory::Zs).contains(' ') && !category(GeneralCategory::Zs).contains('\n'));
+        assert!(category(GeneralCategory::Cc).contains('\n') && category(GeneralCategory::Co).contains('\u{e000}'));
+        assert!(category(GeneralCategory::Cn).contains('\u{378}') && !category(GeneralCategory::Cn).contains('a'));
+        assert_eq!(category(GeneralCategory::Cs).get_ranges(), &[]);
+        // The two letter categories split every character, and the one letter ones are their unions
+        let is_group = |name: &str| !name.chars().nth(1).is_some_and(char::is_lowercase);
+        let two_letters: Vec<_> = GENERAL_CATEGORIES.iter().filter(|(_, names, _)| !is_group(names[0])).collect();
+        let count: usize = two_letters.iter().flat_map(|(_, _, ranges)| ranges.iter()).map(range_len).sum();
+        assert_eq!(count, 0x110000 - 0x800);
+        for (group, names, ranges) in GENERAL_CATEGORIES.iter().filter(|(_, names, _)| names[0].len() == 1) {
+            let members = two_letters.iter().filter(|(_, member, _)| member[0].starts_with(names[0]));
+            let union = members.fold(CharClass::new(vec![]), |union, &&(category, _, _)| {
+                union.union(&CharClass::unicode(UnicodeClass::Category(category)))
+            });
+            assert_eq!(union.get_ranges(), *ranges, "{:?}", group);
+        }
+        let cased = category(GeneralCategory::Lu).union(&category(GeneralCategory::Ll));
+        let cased = cased.union(&category(GeneralCategory::Lt));
+        assert_eq!(cased.get_ranges(), category(GeneralCategory::LC).get_ranges());
+
+        // Scripts
+        let script = |script| CharClass::unicode(UnicodeClass::Script(script));
+        let greek = script(Script::Greek);
+        assert!(['λ', 'Ω', 'ά', '\u{1f00}', '\u{1d26}'].iter().all(|&ch| greek.contains(ch)));
+        // The Coptic letters of the Greek and Coptic block are Coptic
+        assert!(!greek.contains('\u{3e2}') && script(Script::Coptic).contains('\u{3e2}'));
+        assert!(!greek.contains('l') && !greek.contains(';'));
+        let cyrillic = script(Script::Cyrillic);
+        assert!(cyrillic.contains('ж') && !cyrillic.contains('x') && !cyrillic.contains('λ'));
+        let han = script(Script::Han);
+        assert!(han.contains('中') && han.contains('\u{20000}') && !han.contains('の'));
+        assert!(script(Script::Hiragana).contains('の') && script(Script::Katakana).contains('カ'));
+        assert!(script(Script::Latin).contains('é') && !script(Script::Latin).contains('1'));
+        // Digits, punctuation and the like are used by several scripts
+        assert!(['1', ' ', '.', '\u{30fc}'].iter().all(|&ch| script(Script::Common).contains(ch)));
+        assert!(script(Script::Inherited).contains('\u{301}') && script(Script::Unknown).contains('\u{378}'));
+        assert!(script(Script::OldItalic).contains('\u{10300}'));
+
+        assert_eq!(UnicodeClass::from_name("Upper"), Some(UnicodeClass::Uppercase));
+        assert_eq!(UnicodeClass::from_name("L"), Some(UnicodeClass::Category(GeneralCategory::L)));
+        assert_eq!(UnicodeClass::from_name("Letter"), Some(UnicodeClass::Category(GeneralCategory::L)));
+        assert_eq!(UnicodeClass::from_name("Decimal_Number"), Some(UnicodeClass::Category(GeneralCategory::Nd)));
+        assert_eq!(UnicodeClass::from_name("Greek"), Some(UnicodeClass::Script(Script::Greek)));
+        assert_eq!(UnicodeClass::from_name("Grek"), Some(UnicodeClass::Script(Script::Greek)));
+        assert_eq!(UnicodeClass::from_name("Old_Italic"), Some(UnicodeClass::Script(Script::OldItalic)));
+        for name in ["Klingon", "InGreek", "greek", "Lx"] {
+            assert_eq!(UnicodeClass::from_name(name), None);
+        }
+        assert_eq!(UnicodeClass::Category(GeneralCategory::Nd).name(), "Nd");
+        assert_eq!(UnicodeClass::Script(Script::OldItalic).name(), "Old_Italic");
+        assert_eq!(CharClass::unicode(UnicodeClass::Alphabetic).negated().to_string(), "\\P{Alphabetic}");
+        assert_eq!(category(GeneralCategory::Lu).to_string(), "\\p{Lu}");
+        // A union is displayed by its ranges
+        let union = script(Script::Thai).union(&CharClass::set(&['_']));
+        assert!(union.to_string().starts_with("[_\u{e01}-"));
+    }
+
+    fn range_len(&(start, end): &(char, char)) -> usize {
+        end as usize - start as usize + 1
+    }
+
+    #[test]
+    fn test_char_class_negated() {
+        let class = CharClass::range('b', 'y').negated();
+        assert!(class.contains('a') && class.contains('é') && !class.contains('m'));
+        assert_eq!(class.matched_ranges(), vec![('\0', 'a'), ('z', char::MAX)]);
+        assert_eq!(class.clone().negated(), CharClass::range('b', 'y'));
+        // The surrogates are not characters, and are never part of a range
+        let class = CharClass::new(vec![('\0', '\u{d7ff}')]).negated();
+        assert_eq!(class.matched_ranges(), vec![('\u{e000}', char::MAX)]);
+        assert_eq!(CharClass::range('\0', char::MAX).negated().matched_ranges(), vec![]);
+        assert_eq!(CharClass::set(&['a']).negated().union(&CharClass::set(&['a'])).get_ranges(), &[('\0', char::MAX)]);
+    }
+
+    #[test]
+    fn test_char_class_case_folding() {
+        assert_eq!(fold_case('K'), 'k');
+        assert_eq!(fold_case('\u{212a}'), 'k');
+        assert_eq!(fold_case('ſ'), 's');
+        assert_eq!(fold_case('Σ'), fold_case('ς'));
+        assert_eq!(fold_case('ẞ'), 'ß');
+        assert_eq!(fold_case('ı'), 'ı');
+        assert_eq!(fold_case('7'), '7');
+
+        let class = CharClass::range('a', 'k');
+        assert!(class.contains_ignoring_case('B') && class.contains_ignoring_case('\u{212a}'));
+        assert!(!class.contains('B') && !class.contains_ignoring_case('L'));
+        assert_eq!(class.case_insensitive().get_ranges(), &[('A', 'K'), ('a', 'k'), ('\u{212a}', '\u{212a}')]);
+        // A negated class rejects the case variants of its characters too
+        let not_k = CharClass::set(&['k']).negated();
+        assert!(!not_k.contains_ignoring_case('K') && not_k.contains_ignoring_case('x'));
+        assert!(!not_k.case_insensitive().contains('\u{212a}'));
+        // A UnicodeClass keeps its name unless case variants are added to it
+        let han = CharClass::unicode(UnicodeClass::Script(Script::Han));
+        assert_eq!(han.case_insensitive().to_string(), "\\p{Han}");
+        let greek = CharClass::unicode(UnicodeClass::Script(Script::Greek));
+        assert!(greek.case_insensitive().to_string().starts_with('['));
+    }
+}
@@ -0,0 +1,691 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::components::grammar::{Grammar, Symbol};
+use crate::components::items::RegexComponent;
+use crate::components::regex::Regex;
+use crate::components::syntax;
+
+/// load
+/// Builds a Grammar from BNF/EBNF text. Both the ISO-EBNF and the W3C notations are accepted:
+/// - rules are written `name = ... ;` (ISO) or `name ::= ...` (W3C), `<name>` is accepted as well,
+///   the terminating `;` or `.` is optional, and the first rule defines the start symbol
+/// - `|` separates alternatives, as do `/` and `!` in ISO files, `,` between items is optional
+/// - `{ ... }` and postfix `*` map onto ZeroOrMore, `[ ... ]` and postfix `?` onto ZeroOrOne,
+///   postfix `+` onto OneOrMore and `( ... )` onto SubRegex; `(: ... :)` and `(/ ... /)` are the
+///   ISO spellings of `{ ... }` and `[ ... ]`, and `3 * x` repeats x exactly three times
+/// - quoted strings map onto Literal, never onto Keyword: a Keyword must end at a word boundary,
+///   which a rule building words out of quoted letters, such as `letter = "a" | "b"`, can't allow
+/// - in files using `::=`, `[...]` is a W3C character class instead of an optional part
+/// - `(* ... *)` and `/* ... */` are comments
+/// - names are made of letters, digits, `_` and `-`, and do not start with a digit
+///
+/// Exceptions (`a - b`) and ISO special sequences (`? ... ?`) are not supported, and fail with
+/// EbnfErrorKind::Unsupported where they are found.
+///
+/// Groups made only of terminals become a single terminal with the corresponding RegexComponent,
+/// while groups referencing nonterminals are turned into generated nonterminals (`rule'1`, ...).
+///
+/// ```rust
+/// use pilator::{components::ebnf, parser::{llparsers::LLParser, Parser}};
+///
+/// let grammar = ebnf::load(r#"
+///     list = "(" , { item } , ")" ;
+///     item = "x" | list ;
+/// "#).unwrap();
+/// let parser = LLParser::new(&grammar).unwrap();
+/// assert_eq!(parser.parse("(x (x))", None).unwrap(), vec!["(", "x", "(", "x", ")", ")"]);
+/// ```
+pub fn load(text: &str) -> Result<Grammar, EbnfError> {
+    let tokens = tokenize(text)?;
+    let mut loader = Loader {
+        tokens,
+        position: 0,
+        generated: vec![],
+    };
+    loader.grammar()
+}
+
+/// FromStr implementation: loads a Grammar from BNF/EBNF text, see ebnf::load
+impl FromStr for Grammar {
+    type Err = EbnfError;
+
+    fn from_str(text: &str) -> Result<Grammar, EbnfError> {
+        load(text)
+    }
+}
+
+/// enum EbnfErrorKind
+/// Reasons why a grammar file can't be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EbnfErrorKind {
+    /// A character that does not start any token of the notation
+    UnexpectedChar(char),
+    /// A quoted string that is never closed
+    UnterminatedString,
+    /// A comment that is never closed
+    UnterminatedComment,
+    /// A token that can't appear at this position, with a description of what was expected
+    UnexpectedToken { found: String, expected: String },
+    /// A W3C character class that is not valid
+    InvalidClass(String),
+    /// A name starting with a digit, which is not a repetition count
+    InvalidName(String),
+    /// A construct of the notation that the loader does not support, such as exceptions
+    Unsupported(String),
+    /// The text does not contain any rule
+    EmptyGrammar,
+}
+
+/// EbnfError:
+/// Error returned when loading a grammar, with the 1-based line and column where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EbnfError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: EbnfErrorKind,
+}
+
+impl fmt::Display for EbnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            EbnfErrorKind::UnexpectedChar(c) => write!(f, "unexpected `{}`", c),
+            EbnfErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            EbnfErrorKind::UnterminatedComment => write!(f, "unterminated comment"),
+            EbnfErrorKind::UnexpectedToken { found, expected } => {
+                write!(f, "unexpected {}, expected {}", found, expected)
+            }
+            EbnfErrorKind::InvalidClass(error) => write!(f, "invalid character class: {}", error),
+            EbnfErrorKind::InvalidName(name) => write!(f, "invalid name `{}`, names can't start with a digit", name),
+            EbnfErrorKind::Unsupported(construct) => write!(f, "unsupported {}", construct),
+            EbnfErrorKind::EmptyGrammar => write!(f, "no rule found"),
+        }
+    }
+}
+
+impl std::error::Error for EbnfError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    /// Count of an ISO repetition `3 * x`
+    Count(usize),
+    Text(String),
+    Class(Regex),
+    Define,
+    Pipe,
+    Comma,
+    End,
+    Open(char),
+    Close(char),
+    Postfix(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Name(name) => write!(f, "name `{}`", name),
+            Token::Count(count) => write!(f, "count `{}`", count),
+            Token::Text(text) => write!(f, "string {:?}", text),
+            Token::Class(class) => write!(f, "class `{}`", class),
+            Token::Define => write!(f, "definition symbol"),
+            Token::Pipe => write!(f, "`|`"),
+            Token::Comma => write!(f, "`,`"),
+            Token::End => write!(f, "end of rule"),
+            Token::Open(c) | Token::Close(c) | Token::Postfix(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+/// Token with its line and column
+type Located = (Token, usize, usize);
+
+fn tokenize(text: &str) -> Result<Vec<Located>, EbnfError> {
+    let w3c = text.contains("::=");
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<Located> = vec![];
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+
+    // Moves past n characters, keeping track of the line and column
+    let advance = |i: &mut usize, line: &mut usize, column: &mut usize, n: usize| {
+        for _ in 0..n {
+            if chars.get(*i) == Some(&'\n') {
+                *line += 1;
+                *column = 1;
+            } else {
+                *column += 1;
+            }
+            *i += 1;
+        }
+    };
+    let error = |line, column, kind| Err(EbnfError { line, column, kind });
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (start_line, start_column) = (line, column);
+        let after_item = matches!(
+            tokens.last(),
+            Some((Token::Name(_) | Token::Text(_) | Token::Class(_) | Token::Close(_) | Token::Postfix(_), _, _))
+        );
+        let token = match c {
+            c if c.is_whitespace() => {
+                advance(&mut i, &mut line, &mut column, 1);
+                continue;
+            }
+            // An ISO special sequence starts where an item is expected, while `?` ends an optional item
+            '?' if !w3c && !after_item => {
+                let special = "special sequence `? ... ?`".to_string();
+                return error(line, column, EbnfErrorKind::Unsupported(special));
+            }
+            '-' => return error(line, column, EbnfErrorKind::Unsupported("exception `-`".to_string())),
+            '(' if matches!(next, Some(':' | '/')) => {
+                advance(&mut i, &mut line, &mut column, 2);
+                let open = if next == Some(':') { '{' } else { '[' };
+                tokens.push((Token::Open(open), start_line, start_column));
+                continue;
+            }
+            ':' | '/' if next == Some(')') => {
+                advance(&mut i, &mut line, &mut column, 2);
+                let close = if c == ':' { '}' } else { ']' };
+                tokens.push((Token::Close(close), start_line, start_column));
+                continue;
+            }
+            '(' | '/' if next == Some('*') => {
+                let close = if c == '(' { ")" } else { "/" };
+                let body: String = chars[i + 2..].iter().collect();
+                match body.find(&format!("*{}", close)) {
+                    Some(end) => {
+                        let length = body[..end].chars().count() + 4;
+                        advance(&mut i, &mut line, &mut column, length);
+                        continue;
+                    }
+                    None => return error(line, column, EbnfErrorKind::UnterminatedComment),
+                }
+            }
+            '"' | '\'' => {
+                let length = match chars[i + 1..].iter().position(|x| *x == c) {
+                    Some(length) => length,
+                    None => return error(line, column, EbnfErrorKind::UnterminatedString),
+                };
+                let text: String = chars[i + 1..i + 1 + length].iter().collect();
+                advance(&mut i, &mut line, &mut column, length + 2);
+                tokens.push((Token::Text(text), start_line, start_column));
+                continue;
+            }
+            '<' => {
+                let length = chars[i + 1..].iter().position(|x| *x == '>');
+                match length {
+                    Some(length) if length > 0 => {
+                        let name: String = chars[i + 1..i + 1 + length].iter().collect();
+                        advance(&mut i, &mut line, &mut column, length + 2);
+                        tokens.push((Token::Name(name.trim().to_string()), start_line, start_column));
+                        continue;
+                    }
+                    _ => return error(line, column, EbnfErrorKind::UnexpectedChar(c)),
+                }
+            }
+            '[' if w3c => {
+                let length = match chars[i + 1..].iter().position(|x| *x == ']') {
+                    Some(length) => length + 2,
+                    None => return error(line, column, EbnfErrorKind::UnexpectedChar(c)),
+                };
+                let pattern: String = chars[i..i + length].iter().collect();
+                let class = syntax::parse(&pattern).map_err(|e| EbnfError {
+                    line,
+                    column: column + pattern[..e.position].chars().count(),
+                    kind: EbnfErrorKind::InvalidClass(e.kind.to_string()),
+                })?;
+                advance(&mut i, &mut line, &mut column, length);
+                tokens.push((Token::Class(class), start_line, start_column));
+                continue;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|x| !(x.is_alphanumeric() || *x == '_' || *x == '-'))
+                    .unwrap_or(chars.len() - i);
+                let name: String = chars[i..i + length].iter().collect();
+                let token = if !c.is_numeric() {
+                    Token::Name(name)
+                } else if let Some(count) = name.parse().ok().filter(|_| name.chars().all(|c| c.is_ascii_digit())) {
+                    Token::Count(count)
+                } else {
+                    return error(line, column, EbnfErrorKind::InvalidName(name));
+                };
+                advance(&mut i, &mut line, &mut column, length);
+                tokens.push((token, start_line, start_column));
+                continue;
+            }
+            ':' if next == Some(':') && chars.get(i + 2) == Some(&'=') => {
+                advance(&mut i, &mut line, &mut column, 2);
+                Token::Define
+            }
+            '=' => Token::Define,
+            '|' => Token::Pipe,
+            '/' | '!' if !w3c => Token::Pipe,
+            ',' => Token::Comma,
+            ';' | '.' => Token::End,
+            '(' | '{' | '[' => Token::Open(c),
+            ')' | '}' | ']' => Token::Close(c),
+            '*' | '+' | '?' => Token::Postfix(c),
+            c => return error(line, column, EbnfErrorKind::UnexpectedChar(c)),
+        };
+        advance(&mut i, &mut line, &mut column, 1);
+        tokens.push((token, start_line, start_column));
+    }
+    Ok(tokens)
+}
+
+/// Expression of the right-hand side of a rule
+#[derive(Debug, Clone)]
+enum Expr {
+    Terminal(RegexComponent),
+    NonTerminal(String),
+    Sequence(Vec<Expr>),
+    Alternatives(Vec<Expr>),
+    ZeroOrMore(Box<Expr>),
+    OneOrMore(Box<Expr>),
+    ZeroOrOne(Box<Expr>),
+}
+
+impl Expr {
+    fn has_nonterminal(&self) -> bool {
+        match self {
+            Expr::Terminal(_) => false,
+            Expr::NonTerminal(_) => true,
+            Expr::Sequence(exprs) | Expr::Alternatives(exprs) => {
+                exprs.iter().any(|expr| expr.has_nonterminal())
+            }
+            Expr::ZeroOrMore(expr) | Expr::OneOrMore(expr) | Expr::ZeroOrOne(expr) => {
+                expr.has_nonterminal()
+            }
+        }
+    }
+
+    /// Regex matching an expression made only of terminals
+    fn regex(&self) -> Regex {
+        match self {
+            Expr::Terminal(component) => Regex::new(vec![component.clone()]),
+            Expr::Sequence(exprs) => exprs
+                .iter()
+                .fold(Regex::new(vec![]), |regex, expr| regex + expr.regex()),
+            _ => Regex::new(vec![self.component()]),
+        }
+    }
+
+    /// RegexComponent matching an expression made only of terminals
+    fn component(&self) -> RegexComponent {
+        match self {
+            Expr::Terminal(component) => component.clone(),
+            Expr::ZeroOrMore(expr) => RegexComponent::ZeroOrMore(expr.regex()),
+            Expr::OneOrMore(expr) => RegexComponent::OneOrMore(expr.regex()),
+            Expr::ZeroOrOne(expr) => RegexComponent::ZeroOrOne(expr.regex()),
+            Expr::Alternatives(exprs) => {
+                let mut alternatives = exprs.iter().rev().map(|expr| expr.regex());
+                let last = alternatives.next().unwrap_or(Regex::new(vec![]));
+                let regex = alternatives.fold(last, |rest, regex| {
+                    Regex::new(vec![RegexComponent::Or(regex, rest)])
+                });
+                match <[RegexComponent; 1]>::try_from(regex.components) {
+                    Ok([component]) => component,
+                    Err(components) => RegexComponent::SubRegex(Regex::new(components)),
+                }
+            }
+            Expr::Sequence(_) => RegexComponent::SubRegex(self.regex()),
+            Expr::NonTerminal(_) => unreachable!("groups referencing rules are turned into nonterminals"),
+        }
+    }
+}
+
+struct Loader {
+    tokens: Vec<Located>,
+    position: usize,
+    /// Alternatives of the generated nonterminals, added after the rules of the file
+    generated: Vec<(String, Vec<Vec<Symbol>>)>,
+}
+
+impl Loader {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _, _)| token)
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, EbnfError> {
+        let (found, line, column) = match self.tokens.get(self.position) {
+            Some((token, line, column)) => (token.to_string(), *line, *column),
+            None => match self.tokens.last() {
+                Some((_, line, column)) => ("end of file".to_string(), *line, *column),
+                None => ("end of file".to_string(), 1, 1),
+            },
+        };
+        Err(EbnfError {
+            line,
+            column,
+            kind: EbnfErrorKind::UnexpectedToken {
+                found,
+                expected: expected.to_string(),
+            },
+        })
+    }
+
+    /// Whether the next tokens start a new rule
+    fn at_rule(&self) -> bool {
+        matches!(self.peek(), Some(Token::Name(_)))
+            && matches!(self.tokens.get(self.position + 1), Some((Token::Define, _, _)))
+    }
+
+    fn grammar(&mut self) -> Result<Grammar, EbnfError> {
+        if self.tokens.is_empty() {
+            return Err(EbnfError {
+                line: 1,
+                column: 1,
+                kind: EbnfErrorKind::EmptyGrammar,
+            });
+        }
+        let mut exprs: Vec<(String, Expr)> = vec![];
+        while self.position < self.tokens.len() {
+            let name = match self.peek() {
+                Some(Token::Name(name)) if self.at_rule() => name.clone(),
+                _ => return self.unexpected("a rule definition"),
+            };
+            self.position += 2;
+            let expr = self.alternatives()?;
+            if self.peek() == Some(&Token::End) {
+                self.position += 1;
+            } else if self.position < self.tokens.len() && !self.at_rule() {
+                return self.unexpected("end of rule");
+            }
+            exprs.push((name, expr));
+        }
+
+        let mut grammar = Grammar::new(&exprs[0].0);
+        let mut counters: Vec<usize> = vec![0; exprs.len()];
+        let names: Vec<String> = exprs.iter().map(|(name, _)| name.clone()).collect();
+        for (index, (name, expr)) in exprs.into_iter().enumerate() {
+            let alternatives = match expr {
+                Expr::Alternatives(exprs) => exprs,
+                expr => vec![expr],
+            };
+            for alternative in alternatives {
+                let rhs = self.symbols(&name, &names, &mut counters[index], alternative);
+                grammar.add_production(&name, rhs);
+            }
+        }
+        for (name, alternatives) in self.generated.drain(..) {
+            for rhs in alternatives {
+                grammar.add_production(&name, rhs);
+            }
+        }
+        Ok(grammar)
+    }
+
+    /// Symbols of an expression of the given rule, generating nonterminals for groups that
+    /// reference other rules
+    fn symbols(&mut self, rule: &str, names: &[String], counter: &mut usize, expr: Expr) -> Vec<Symbol> {
+        if !expr.has_nonterminal() {
+            return match expr {
+                Expr::Sequence(exprs) => exprs
+                    .into_iter()
+                    .map(|expr| Symbol::Terminal(expr.component()))
+                    .collect(),
+                expr => vec![Symbol::Terminal(expr.component())],
+            };
+        }
+        match expr {
+            Expr::NonTerminal(name) => vec![Symbol::NonTerminal(name)],
+            Expr::Sequence(exprs) => exprs
+                .into_iter()
+                .flat_map(|expr| self.symbols(rule, names, counter, expr))
+                .collect(),
+            Expr::OneOrMore(expr) => {
+                let mut symbols = self.symbols(rule, names, counter, (*expr).clone());
+                symbols.extend(self.symbols(rule, names, counter, Expr::ZeroOrMore(expr)));
+                symbols
+            }
+            Expr::ZeroOrMore(expr) => {
+                let name = Self::fresh(rule, names, counter);
+                let mut rhs = self.symbols(rule, names, counter, *expr);
+                rhs.push(Symbol::NonTerminal(name.clone()));
+                self.generated.push((name.clone(), vec![rhs, vec![]]));
+                vec![Symbol::NonTerminal(name)]
+            }
+            Expr::ZeroOrOne(expr) => {
+                let name = Self::fresh(rule, names, counter);
+                let rhs = self.symbols(rule, names, counter, *expr);
+                self.generated.push((name.clone(), vec![rhs, vec![]]));
+                vec![Symbol::NonTerminal(name)]
+            }
+            Expr::Alternatives(exprs) => {
+                let name = Self::fresh(rule, names, counter);
+                let alternatives = exprs
+                    .into_iter()
+                    .map(|expr| self.symbols(rule, names, counter, expr))
+                    .collect();
+                self.generated.push((name.clone(), alternatives));
+                vec![Symbol::NonTerminal(name)]
+            }
+            Expr::Terminal(component) => vec![Symbol::Terminal(component)],
+        }
+    }
+
+    fn fresh(rule: &str, names: &[String], counter: &mut usize) -> String {
+        loop {
+            *counter += 1;
+            let name = format!("{}'{}", rule, counter);
+            if !names.contains(&name) {
+                return name;
+            }
+        }
+    }
+
+    /// alternatives := sequence ('|' sequence)*
+    fn alternatives(&mut self) -> Result<Expr, EbnfError> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.position += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Expr::Alternatives(alternatives),
+        })
+    }
+
+    /// sequence := ((count '*')? item ','?)*
+    fn sequence(&mut self) -> Result<Expr, EbnfError> {
+        let mut items = vec![];
+        loop {
+            match self.peek() {
+                Some(Token::Comma) => self.position += 1,
+                Some(Token::Name(_)) if self.at_rule() => break,
+                Some(&Token::Count(count)) => {
+                    self.position += 1;
+                    if self.peek() != Some(&Token::Postfix('*')) {
+                        return self.unexpected("`*` after a repetition count");
+                    }
+                    self.position += 1;
+                    let item = self.item()?;
+                    items.extend(vec![item; count]);
+                }
+                Some(Token::Name(_) | Token::Text(_) | Token::Class(_) | Token::Open(_)) => {
+                    items.push(self.item()?)
+                }
+                _ => break,
+            }
+        }
+        Ok(match items.len() {
+            1 => items.remove(0),
+            _ => Expr::Sequence(items),
+        })
+    }
+
+    /// item := (name | string | class | '(' alternatives ')' | '{' ... '}' | '[' ... ']') postfix*
+    fn item(&mut self) -> Result<Expr, EbnfError> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        let mut expr = match token {
+            Some(Token::Name(name)) => Expr::NonTerminal(name),
+            Some(Token::Text(text)) => Expr::Terminal(RegexComponent::Literal(text)),
+            Some(Token::Class(class)) => Expr::Terminal(match <[RegexComponent; 1]>::try_from(class.components) {
+                Ok([component]) => component,
+                Err(components) => RegexComponent::SubRegex(Regex::new(components)),
+            }),
+            Some(Token::Open(open)) => {
+                let inner = self.alternatives()?;
+                let close = match open {
+                    '(' => ')',
+                    '{' => '}',
+                    _ => ']',
+                };
+                if self.peek() != Some(&Token::Close(close)) {
+                    return self.unexpected(&format!("`{}`", close));
+                }
+                self.position += 1;
+                match open {
+                    '(' => inner,
+                    '{' => Expr::ZeroOrMore(Box::new(inner)),
+                    _ => Expr::ZeroOrOne(Box::new(inner)),
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return self.unexpected("a name, a string or a group");
+            }
+        };
+        while let Some(Token::Postfix(c)) = self.peek() {
+            expr = match c {
+                '*' => Expr::ZeroOrMore(Box::new(expr)),
+                '+' => Expr::OneOrMore(Box::new(expr)),
+                _ => Expr::ZeroOrOne(Box::new(expr)),
+            };
+            self.position += 1;
+        }
+        Ok(expr)
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::grammar::test_symbols::{literal, nonterminal};
+    use crate::parser::{llparsers::LLParser, slrparsers::SLRParser, Parser};
+
+    #[test]
+    fn test_ebnf_iso() {
+        let grammar = load(
+            "(* statements *)
+            stmt = \"if\" , cond , \"then\" , stmt , [ \"else\" , stmt ]
+                 | \"x\" , { \"+\" , \"x\" } ;
+            cond = 'c' ;",
+        )
+        .unwrap();
+        assert_eq!(grammar.get_start(), "stmt");
+        assert_eq!(
+            grammar.to_string(),
+            "stmt -> \"if\" cond \"then\" stmt stmt'1\n\
+             stmt -> \"x\" /(?:\\+ x)*/\n\
+             cond -> \"c\"\n\
+             stmt'1 -> \"else\" stmt\n\
+             stmt'1 -> ε\n"
+        );
+        assert_eq!(
+            grammar.get_productions()[1].rhs,
+            vec![
+                literal("x"),
+                Symbol::Terminal(RegexComponent::ZeroOrMore(Regex::new(vec![
+                    RegexComponent::Literal("+".to_string()),
+                    RegexComponent::Literal("x".to_string()),
+                ]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ebnf_iso_spellings() {
+        // Quoted letters build words: they are not keywords, which would need a boundary after them
+        let grammar = load("ident = letter , { letter | digit } ; letter = \"a\" | \"b\" ; digit = \"0\" | \"1\" ;");
+        let parser = LLParser::new(&grammar.unwrap()).unwrap();
+        assert_eq!(parser.parse("ab1", None).unwrap(), vec!["a", "b", "1"]);
+
+        let grammar = load("a = 3 * 'x' , (/ 'y' /) , (: 'z' ! 'w' :) ; b = 'u' / 0 * 'v' ;").unwrap();
+        assert_eq!(
+            grammar.to_string(),
+            "a -> \"x\" \"x\" \"x\" /y?/ /(?:z|w)*/\n\
+             b -> \"u\"\n\
+             b -> ε\n"
+        );
+        let parser = LLParser::new(&grammar).unwrap();
+        assert_eq!(parser.parse("xxx zwz", None).unwrap(), vec!["x", "x", "x", "z", "w", "z"]);
+        assert_eq!(parser.parse("xxxy", None).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_ebnf_w3c() {
+        let grammar = load(
+            "/* sums of numbers */
+            sum ::= num ('+' num)*
+            <num> ::= [0-9]+",
+        )
+        .unwrap();
+        assert_eq!(grammar.get_productions()[0].rhs[0], nonterminal("num"));
+        assert_eq!(grammar.get_productions()[0].lhs, "sum");
+        assert_eq!(
+            grammar.get_productions()[2].rhs,
+            vec![literal("+"), nonterminal("num"), nonterminal("sum'1")]
+        );
+
+        let parser = SLRParser::new(&grammar).unwrap();
+        assert_eq!(parser.parse("1 + 23", None).unwrap(), vec!["1", "+", "2", "3"]);
+    }
+
+    #[test]
+    fn test_ebnf_parsers_agree() {
+        let grammar: Grammar = "list = '(' , { list | 'x' } , ')' ;".parse().unwrap();
+        let ll = LLParser::new(&grammar).unwrap();
+        let slr = SLRParser::new(&grammar).unwrap();
+        for input in ["()", "(x (x x) ())"] {
+            assert_eq!(ll.parse(input, None), slr.parse(input, None));
+            assert!(ll.parse(input, None).is_ok());
+        }
+        assert!(ll.parse("(x", None).is_err());
+    }
+
+    #[test]
+    fn test_ebnf_errors() {
+        let error = |text: &str| load(text).unwrap_err();
+        assert_eq!(
+            error("a = 'x' ;\nb = 'y' ) ;"),
+            EbnfError {
+                line: 2,
+                column: 9,
+                kind: EbnfErrorKind::UnexpectedToken {
+                    found: "`)`".to_string(),
+                    expected: "end of rule".to_string()
+                }
+            }
+        );
+        assert_eq!(
+            error("a = ( 'x' ;").kind,
+            EbnfErrorKind::UnexpectedToken {
+                found: "end of rule".to_string(),
+                expected: "`)`".to_string()
+            }
+        );
+        assert_eq!(error("a = \"x ;").to_string(), "1:5: unterminated string");
+        assert_eq!(error("\n  a = 'x' - 'y'").to_string(), "2:11: unsupported exception `-`");
+        assert_eq!(error("a = ? letters ? ;").to_string(), "1:5: unsupported special sequence `? ... ?`");
+        assert_eq!(error("a = 3x ;").to_string(), "1:5: invalid name `3x`, names can't start with a digit");
+        assert_eq!(
+            error("a = 3 'x' ;").kind,
+            EbnfErrorKind::UnexpectedToken {
+                found: "string \"x\"".to_string(),
+                expected: "`*` after a repetition count".to_string()
+            }
+        );
+        assert_eq!(error("(* only a comment *)").kind, EbnfErrorKind::EmptyGrammar);
+        assert_eq!(error("a ::= [z-a]").to_string(), "1:8: invalid character class: invalid range `z-a`");
+    }
+}
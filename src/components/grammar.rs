@@ -0,0 +1,695 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use crate::components::items::{Flags, RegexComponent};
+use crate::components::regex::Regex;
+use crate::components::token::TokenSlice;
+
+/// enum Symbol
+/// A symbol on the right-hand side of a production: either a terminal, matched against the input
+/// through its RegexComponent, or a reference to a nonterminal of the grammar.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    Terminal(RegexComponent),
+    NonTerminal(String),
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::Terminal(component) => match component.as_text() {
+                Some(text) => write!(f, "{:?}", text),
+                None => write!(f, "/{}/", component),
+            },
+            Symbol::NonTerminal(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Production:
+/// A rule `lhs -> rhs` of a context-free grammar. An empty right-hand side is an epsilon production.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Production {
+    pub lhs: String,
+    pub rhs: Vec<Symbol>,
+}
+
+impl fmt::Display for Production {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ->", self.lhs)?;
+        if self.rhs.is_empty() {
+            return write!(f, " ε");
+        }
+        for symbol in self.rhs.iter() {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Grammar:
+/// Context-free grammar made of a start symbol and a list of productions. Nonterminals are
+/// identified by name and are defined by the productions having them as left-hand side.
+/// A grammar can be consumed by NaiveParser, LLParser and SLRParser alike.
+///
+/// ```rust
+/// use pilator::components::{grammar::{Grammar, Symbol}, items::RegexComponent};
+///
+/// // list -> "(" list ")" list | ε
+/// let mut grammar = Grammar::new("list");
+/// grammar.add_production("list", vec![
+///     Symbol::Terminal(RegexComponent::Literal("(".to_string())),
+///     Symbol::NonTerminal("list".to_string()),
+///     Symbol::Terminal(RegexComponent::Literal(")".to_string())),
+///     Symbol::NonTerminal("list".to_string()),
+/// ]);
+/// grammar.add_production("list", vec![]);
+/// assert!(grammar.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    start: String,
+    productions: Vec<Production>,
+}
+
+/// Implemented methods:
+/// - new: Creates an empty grammar with the given start symbol
+/// - add_production: Adds a new production and returns its index
+/// - get_start: Returns the start symbol
+/// - get_productions: Returns a reference to the list of productions
+/// - productions_of: Returns the productions having the given nonterminal as left-hand side
+/// - nonterminals: Returns the defined nonterminals, in order of definition
+/// - terminals: Returns the terminals used by the productions, in order of appearance
+/// - validate: Checks that the grammar is well formed
+/// - lowered: Returns an equivalent grammar whose terminals are all plain pieces of text
+impl Grammar {
+    pub fn new(start: &str) -> Grammar {
+        Grammar {
+            start: start.to_string(),
+            productions: vec![],
+        }
+    }
+
+    pub fn add_production(&mut self, lhs: &str, rhs: Vec<Symbol>) -> usize {
+        self.productions.push(Production {
+            lhs: lhs.to_string(),
+            rhs,
+        });
+        self.productions.len() - 1
+    }
+
+    pub fn get_start(&self) -> &str {
+        &self.start
+    }
+
+    pub fn get_productions(&self) -> &Vec<Production> {
+        &self.productions
+    }
+
+    pub fn productions_of<'a>(&'a self, nonterminal: &'a str) -> impl Iterator<Item = &'a Production> + 'a {
+        self.productions
+            .iter()
+            .filter(move |production| production.lhs == nonterminal)
+    }
+
+    pub fn nonterminals(&self) -> Vec<&str> {
+        let mut nonterminals: Vec<&str> = vec![];
+        for production in self.productions.iter() {
+            if !nonterminals.contains(&production.lhs.as_str()) {
+                nonterminals.push(&production.lhs);
+            }
+        }
+        nonterminals
+    }
+
+    pub fn terminals(&self) -> Vec<&RegexComponent> {
+        let mut terminals: Vec<&RegexComponent> = vec![];
+        for production in self.productions.iter() {
+            for symbol in production.rhs.iter() {
+                if let Symbol::Terminal(component) = symbol {
+                    if !terminals.contains(&component) {
+                        terminals.push(component);
+                    }
+                }
+            }
+        }
+        terminals
+    }
+
+    /// validate
+    /// Checks that the start symbol and every referenced nonterminal are defined.
+    pub fn validate(&self) -> Result<(), GrammarError> {
+        let nonterminals = self.nonterminals();
+        if !nonterminals.contains(&self.start.as_str()) {
+            return Err(GrammarError::UndefinedStart(self.start.clone()));
+        }
+        for (index, production) in self.productions.iter().enumerate() {
+            for symbol in production.rhs.iter() {
+                match symbol {
+                    Symbol::NonTerminal(name) if !nonterminals.contains(&name.as_str()) => {
+                        return Err(GrammarError::UndefinedNonTerminal {
+                            nonterminal: name.clone(),
+                            production: index,
+                        });
+                    }
+                    _ => (),
+                }
+            }
+        }
+        // Terminals are lowered one production at a time, to know which production they come from
+        let mut lowering = Lowering::new(self);
+        for (index, production) in self.productions.iter().enumerate() {
+            let generated = lowering.generated.len();
+            let rhs = lowering.symbols(&production.lhs, &production.rhs);
+            let lowered = rhs.iter().chain(lowering.generated[generated..].iter().flat_map(|p| p.rhs.iter()));
+            for symbol in lowered {
+                if let Symbol::Terminal(component) = symbol {
+                    if let Some(unsupported) = unsupported_terminal(component) {
+                        return Err(GrammarError::UnsupportedTerminal {
+                            terminal: unsupported.to_string(),
+                            production: index,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// lowered
+    /// Terminals can be any RegexComponent: this method returns an equivalent grammar where
+    /// SubRegex and NamedSubRegex terminals are inlined, and ZeroOrMore, OneOrMore, ZeroOrOne and Or terminals are
+    /// replaced by generated nonterminals named after the production left-hand side (`list'1`,
+    /// `list'2`, ...). Repeat terminals are first expanded into copies of their Regex followed by
+    /// a ZeroOrMore or nested ZeroOrOne. Flagged terminals are inlined as well, each of their terminals
+    /// becoming a Flagged terminal of its own with the flags in effect. The original productions keep
+    /// their index, generated ones are appended.
+    pub fn lowered(&self) -> Grammar {
+        let mut lowering = Lowering::new(self);
+        let mut productions: Vec<Production> = self
+            .productions
+            .iter()
+            .map(|production| Production {
+                lhs: production.lhs.clone(),
+                rhs: lowering.symbols(&production.lhs, &production.rhs),
+            })
+            .collect();
+        productions.append(&mut lowering.generated);
+        Grammar {
+            start: self.start.clone(),
+            productions,
+        }
+    }
+}
+
+/// State of Grammar::lowered: the names already in use, the generated productions and the flags
+/// in effect in the terminal being lowered
+struct Lowering {
+    names: HashSet<String>,
+    counters: HashMap<String, usize>,
+    generated: Vec<Production>,
+    flags: Flags,
+}
+
+impl Lowering {
+    fn new(grammar: &Grammar) -> Lowering {
+        Lowering {
+            names: grammar.nonterminals().into_iter().map(|name| name.to_string()).collect(),
+            counters: HashMap::new(),
+            generated: vec![],
+            flags: Flags::new(),
+        }
+    }
+
+    fn symbols(&mut self, lhs: &str, symbols: &[Symbol]) -> Vec<Symbol> {
+        let mut lowered = vec![];
+        for symbol in symbols.iter() {
+            match symbol {
+                Symbol::Terminal(component) => lowered.extend(self.component(lhs, component)),
+                Symbol::NonTerminal(_) => lowered.push(symbol.clone()),
+            }
+        }
+        lowered
+    }
+
+    fn regex(&mut self, lhs: &str, regex: &Regex) -> Vec<Symbol> {
+        let mut lowered = vec![];
+        for component in regex.components.iter() {
+            lowered.extend(self.component(lhs, component));
+        }
+        lowered
+    }
+
+    fn component(&mut self, lhs: &str, component: &RegexComponent) -> Vec<Symbol> {
+        match component {
+            RegexComponent::SubRegex(regex) | RegexComponent::NamedSubRegex(_, regex) => self.regex(lhs, regex),
+            RegexComponent::Flagged(flags, regex) => {
+                let outer = self.flags;
+                self.flags = flags.within(outer);
+                let lowered = self.regex(lhs, regex);
+                self.flags = outer;
+                lowered
+            }
+            RegexComponent::ZeroOrMore(regex) => {
+                let name = self.fresh(lhs);
+                let mut rhs = self.regex(lhs, regex);
+                rhs.push(Symbol::NonTerminal(name.clone()));
+                self.generate(&name, vec![rhs, vec![]])
+            }
+            RegexComponent::OneOrMore(regex) => {
+                let mut lowered = self.regex(lhs, regex);
+                lowered.extend(self.component(lhs, &RegexComponent::ZeroOrMore(regex.clone())));
+                lowered
+            }
+            // A grammar describes the language only, so the greediness makes no difference
+            RegexComponent::Repeat { regex, min, max, .. } => {
+                let mut lowered = vec![];
+                for _ in 0..*min {
+                    lowered.extend(self.regex(lhs, regex));
+                }
+                let optional = match max {
+                    None => Regex::new(vec![RegexComponent::ZeroOrMore(regex.clone())]),
+                    // x{0,2} is (x (x)?)?
+                    Some(max) => (*min..*max).fold(Regex::new(vec![]), |rest, _| {
+                        Regex::new(vec![RegexComponent::ZeroOrOne(regex.clone() + rest)])
+                    }),
+                };
+                lowered.extend(self.regex(lhs, &optional));
+                lowered
+            }
+            RegexComponent::ZeroOrOne(regex) => {
+                let name = self.fresh(lhs);
+                let rhs = self.regex(lhs, regex);
+                self.generate(&name, vec![rhs, vec![]])
+            }
+            RegexComponent::Or(regex1, regex2) => {
+                let name = self.fresh(lhs);
+                let mut alternatives = vec![self.regex(lhs, regex1)];
+                let mut rest = regex2;
+                // a|b|c is nested as Or(a, Or(b, c)): all the alternatives go to the same nonterminal
+                while let [RegexComponent::Or(next1, next2)] = rest.components.as_slice() {
+                    alternatives.push(self.regex(lhs, next1));
+                    rest = next2;
+                }
+                alternatives.push(self.regex(lhs, rest));
+                self.generate(&name, alternatives)
+            }
+            _ if self.flags == Flags::new() => vec![Symbol::Terminal(component.clone())],
+            _ => {
+                let flagged = RegexComponent::Flagged(self.flags, Regex::new(vec![component.clone()]));
+                vec![Symbol::Terminal(flagged)]
+            }
+        }
+    }
+
+    /// Adds the productions of a generated nonterminal and returns a reference to it
+    fn generate(&mut self, name: &str, alternatives: Vec<Vec<Symbol>>) -> Vec<Symbol> {
+        for rhs in alternatives {
+            self.generated.push(Production {
+                lhs: name.to_string(),
+                rhs,
+            });
+        }
+        vec![Symbol::NonTerminal(name.to_string())]
+    }
+
+    fn fresh(&mut self, lhs: &str) -> String {
+        let counter = self.counters.entry(lhs.to_string()).or_insert(0);
+        loop {
+            *counter += 1;
+            let name = format!("{}'{}", lhs, counter);
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for production in self.productions.iter() {
+            writeln!(f, "{}", production)?;
+        }
+        Ok(())
+    }
+}
+
+/// enum GrammarError
+/// Errors found while checking that a grammar is well formed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrammarError {
+    /// The start symbol has no production
+    UndefinedStart(String),
+    /// A production references a nonterminal that has no production
+    UndefinedNonTerminal { nonterminal: String, production: usize },
+    /// A production uses a terminal that lowering can't turn into terminals matching text, such as
+    /// an assertion or a lookaround, which match no character on their own
+    UnsupportedTerminal { terminal: String, production: usize },
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarError::UndefinedStart(start) => {
+                write!(f, "start symbol `{}` has no production", start)
+            }
+            GrammarError::UndefinedNonTerminal { nonterminal, production } => write!(
+                f,
+                "nonterminal `{}` used in production {} has no production",
+                nonterminal, production
+            ),
+            GrammarError::UnsupportedTerminal { terminal, production } => write!(
+                f,
+                "terminal `{}` used in production {} does not match any text on its own",
+                terminal, production
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+/// Grammar symbol with terminals and nonterminals replaced by their index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum Sym {
+    T(usize),
+    N(usize),
+}
+
+/// Analysis:
+/// Indexed form of a lowered grammar shared by the table-driven parsers, with the nullable, FIRST
+/// and FOLLOW sets of every nonterminal. Terminal `terminals.len()` is the end of input marker, and
+/// production `productions.len()` is the augmented production `start' -> start`.
+#[derive(Debug, Clone)]
+pub(crate) struct Analysis {
+    pub grammar: Grammar,
+    pub terminals: Vec<RegexComponent>,
+    pub nonterminals: Vec<String>,
+    pub productions: Vec<(usize, Vec<Sym>)>,
+    pub start: usize,
+    pub nullable: Vec<bool>,
+    pub first: Vec<BTreeSet<usize>>,
+    pub follow: Vec<BTreeSet<usize>>,
+    augmented: Vec<Sym>,
+}
+
+impl Analysis {
+    pub fn new(grammar: &Grammar) -> Result<Analysis, GrammarError> {
+        grammar.validate()?;
+        let grammar = grammar.lowered();
+
+        let terminals: Vec<RegexComponent> = grammar.terminals().into_iter().cloned().collect();
+        let nonterminals: Vec<String> = grammar
+            .nonterminals()
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect();
+        let terminal_ids: HashMap<&RegexComponent, usize> =
+            terminals.iter().enumerate().map(|(i, t)| (t, i)).collect();
+        let nonterminal_ids: HashMap<&str, usize> = nonterminals
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.as_str(), i))
+            .collect();
+
+        let productions = grammar
+            .get_productions()
+            .iter()
+            .map(|production| {
+                let rhs = production
+                    .rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Terminal(component) => Sym::T(terminal_ids[component]),
+                        Symbol::NonTerminal(name) => Sym::N(nonterminal_ids[name.as_str()]),
+                    })
+                    .collect();
+                (nonterminal_ids[production.lhs.as_str()], rhs)
+            })
+            .collect();
+
+        let start = nonterminal_ids[grammar.get_start()];
+        let mut analysis = Analysis {
+            start,
+            augmented: vec![Sym::N(start)],
+            nullable: vec![false; nonterminals.len()],
+            first: vec![BTreeSet::new(); nonterminals.len()],
+            follow: vec![BTreeSet::new(); nonterminals.len()],
+            terminals,
+            nonterminals,
+            productions,
+            grammar,
+        };
+        analysis.compute_first();
+        analysis.compute_follow();
+        Ok(analysis)
+    }
+
+    /// Index of the end of input marker
+    pub fn end(&self) -> usize {
+        self.terminals.len()
+    }
+
+    /// Left-hand side and right-hand side of a production, including the augmented one
+    pub fn production(&self, index: usize) -> (usize, &[Sym]) {
+        match self.productions.get(index) {
+            Some((lhs, rhs)) => (*lhs, rhs),
+            None => (self.nonterminals.len(), &self.augmented),
+        }
+    }
+
+    /// FIRST set of a sequence of symbols, and whether the whole sequence is nullable
+    pub fn first_of(&self, symbols: &[Sym]) -> (BTreeSet<usize>, bool) {
+        let mut first = BTreeSet::new();
+        for symbol in symbols.iter() {
+            match *symbol {
+                Sym::T(t) => {
+                    first.insert(t);
+                    return (first, false);
+                }
+                Sym::N(n) => {
+                    first.extend(self.first[n].iter().copied());
+                    if !self.nullable[n] {
+                        return (first, false);
+                    }
+                }
+            }
+        }
+        (first, true)
+    }
+
+    pub fn terminal_name(&self, terminal: usize) -> String {
+        match self.terminals.get(terminal) {
+            Some(component) => Symbol::Terminal(component.clone()).to_string(),
+            None => "end of input".to_string(),
+        }
+    }
+
+    pub fn symbol_name(&self, symbol: Sym) -> String {
+        match symbol {
+            Sym::T(t) => self.terminal_name(t),
+            Sym::N(n) => self.nonterminals[n].clone(),
+        }
+    }
+
+    /// scan
+    /// Splits the input into terminals, always picking the longest terminal matching at the current
+    /// position (the first declared one on ties). Whitespace that no terminal matches is skipped.
+    /// Returns the terminal index and byte range of every token, or the offset of the first
+    /// character that no terminal matches.
+    pub fn scan(&self, input: &str) -> Result<Vec<(usize, usize, usize)>, usize> {
+        let mut tokens = vec![];
+        let mut idx = 0;
+        while idx < input.len() {
+            let rest = &input[idx..];
+            let mut best: Option<(usize, usize)> = None;
+            for (terminal, component) in self.terminals.iter().enumerate() {
+                if let Some(len) = component.match_terminal(rest) {
+                    if best.is_none_or(|(_, best)| len > best) {
+                        best = Some((terminal, len));
+                    }
+                }
+            }
+            match best {
+                Some((terminal, len)) => {
+                    tokens.push((terminal, idx, idx + len));
+                    idx += len;
+                }
+                None => match rest.chars().next() {
+                    Some(c) if c.is_whitespace() => idx += c.len_utf8(),
+                    _ => return Err(idx),
+                },
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Token of a terminal found by scan
+    pub fn token<'a>(&'a self, input: &'a str, (terminal, start, stop): (usize, usize, usize)) -> TokenSlice<'a> {
+        TokenSlice::new(&self.terminals[terminal], &input[start..stop], start..stop)
+    }
+
+    fn compute_first(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, rhs) in self.productions.iter() {
+                let (first, nullable) = self.first_of(rhs);
+                if nullable && !self.nullable[*lhs] {
+                    self.nullable[*lhs] = true;
+                    changed = true;
+                }
+                let before = self.first[*lhs].len();
+                self.first[*lhs].extend(first);
+                changed |= self.first[*lhs].len() != before;
+            }
+        }
+    }
+
+    fn compute_follow(&mut self) {
+        let end = self.end();
+        self.follow[self.start].insert(end);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (lhs, rhs) in self.productions.iter() {
+                for (i, symbol) in rhs.iter().enumerate() {
+                    if let Sym::N(n) = *symbol {
+                        let (mut follow, nullable) = self.first_of(&rhs[i + 1..]);
+                        if nullable {
+                            follow.extend(self.follow[*lhs].iter().copied());
+                        }
+                        let before = self.follow[n].len();
+                        self.follow[n].extend(follow);
+                        changed |= self.follow[n].len() != before;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Component of a lowered terminal that can't be a terminal, as it matches no character
+fn unsupported_terminal(component: &RegexComponent) -> Option<&RegexComponent> {
+    match component {
+        RegexComponent::Flagged(_, regex) => regex.components.iter().find_map(unsupported_terminal),
+        RegexComponent::Assertion(_) | RegexComponent::Lookahead { .. } | RegexComponent::Lookbehind { .. } => {
+            Some(component)
+        }
+        _ => None,
+    }
+}
+
+/// Shorthands for the symbols of the grammars built by the unit tests
+#[cfg(test)]
+pub(crate) mod test_symbols {
+    use super::Symbol;
+    use crate::components::items::RegexComponent;
+
+    pub(crate) fn literal(text: &str) -> Symbol {
+        Symbol::Terminal(RegexComponent::Literal(text.to_string()))
+    }
+
+    pub(crate) fn nonterminal(name: &str) -> Symbol {
+        Symbol::NonTerminal(name.to_string())
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::test_symbols::{literal, nonterminal};
+    use super::*;
+    use crate::components::class::CharClass;
+
+    #[test]
+    fn test_grammar_undefined_nonterminal() {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("a"), nonterminal("b")]);
+        assert_eq!(
+            grammar.validate(),
+            Err(GrammarError::UndefinedNonTerminal {
+                nonterminal: "b".to_string(),
+                production: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_grammar_unsupported_terminal() {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("a")]);
+        grammar.add_production("s", vec![Symbol::Terminal(RegexComponent::SubRegex("a \\b".parse().unwrap()))]);
+        assert_eq!(
+            grammar.validate(),
+            Err(GrammarError::UnsupportedTerminal {
+                terminal: "\\b".to_string(),
+                production: 1
+            })
+        );
+        assert_eq!(
+            grammar.validate().unwrap_err().to_string(),
+            "terminal `\\b` used in production 1 does not match any text on its own"
+        );
+    }
+
+    #[test]
+    fn test_grammar_undefined_start() {
+        let mut grammar = Grammar::new("start");
+        grammar.add_production("s", vec![literal("a")]);
+        assert_eq!(
+            grammar.validate(),
+            Err(GrammarError::UndefinedStart("start".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_grammar_first_and_follow() {
+        // e -> t e'; e' -> "+" t e' | ε; t -> "x" | "(" e ")"
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("t"), nonterminal("e'")]);
+        grammar.add_production("e'", vec![literal("+"), nonterminal("t"), nonterminal("e'")]);
+        grammar.add_production("e'", vec![]);
+        grammar.add_production("t", vec![literal("x")]);
+        grammar.add_production("t", vec![literal("("), nonterminal("e"), literal(")")]);
+
+        let analysis = Analysis::new(&grammar).unwrap();
+        let names = |set: &BTreeSet<usize>| -> Vec<String> {
+            set.iter().map(|t| analysis.terminal_name(*t)).collect()
+        };
+        assert_eq!(analysis.nullable, vec![false, true, false]);
+        assert_eq!(names(&analysis.first[0]), vec!["\"x\"", "\"(\""]);
+        assert_eq!(names(&analysis.first[1]), vec!["\"+\""]);
+        assert_eq!(names(&analysis.follow[0]), vec!["\")\"", "end of input"]);
+        assert_eq!(names(&analysis.follow[2]), vec!["\"+\"", "\")\"", "end of input"]);
+    }
+
+    #[test]
+    fn test_grammar_lowered_repeat() {
+        // date -> /[0-9]{4}/ "-" /[0-9]{1,2}/
+        let digit = Symbol::Terminal(RegexComponent::Class(CharClass::range('0', '9')));
+        let mut grammar = Grammar::new("date");
+        grammar.add_production(
+            "date",
+            vec![
+                Symbol::Terminal("[0-9]{4}".parse::<Regex>().unwrap().components.remove(0)),
+                literal("-"),
+                Symbol::Terminal("[0-9]{1,2}".parse::<Regex>().unwrap().components.remove(0)),
+            ],
+        );
+        let lowered = grammar.lowered();
+        let productions = lowered.productions_of("date").collect::<Vec<_>>();
+        assert_eq!(productions[0].rhs[..5], [digit.clone(), digit.clone(), digit.clone(), digit.clone(), literal("-")]);
+        assert_eq!(productions[0].rhs[5..], [digit.clone(), nonterminal("date'1")]);
+        let optional: Vec<_> = lowered.productions_of("date'1").map(|production| production.rhs.clone()).collect();
+        assert_eq!(optional, vec![vec![digit], vec![]]);
+    }
+
+    #[test]
+    fn test_grammar_display() {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("a"), nonterminal("s")]);
+        grammar.add_production("s", vec![]);
+        assert_eq!(grammar.to_string(), "s -> \"a\" s\ns -> ε\n");
+    }
+}
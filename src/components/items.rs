@@ -1,9 +1,13 @@
+use std::fmt;
+
+use crate::components::class::{fold_case, CharClass};
 use crate::components::regex::Regex;
+use crate::constants::NEWLINE;
 
 /// enum RegexComponent
 /// This enum is implemented by all the components that can be part of a regex.
 /// It has the method is_nullable that returns a boolean indicating if the component can be nullable or not.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RegexComponent {
     Literal(String),
     Keyword(String),
@@ -12,8 +16,229 @@ pub enum RegexComponent {
     ZeroOrMore(Regex),
     OneOrMore(Regex),
     ZeroOrOne(Regex),
+    /// At least `min` and at most `max` repetitions of the Regex, without upper bound if `max` is
+    /// None, trying more or fewer repetitions first depending on the greediness
+    Repeat { regex: Regex, min: usize, max: Option<usize>, greediness: Greediness },
     Or(Regex, Regex),
+    /// Group of components, which is also a numbered capture group (see regex::Regex::group_names)
     SubRegex(Regex),
+    /// SubRegex whose capture group can also be found by its name
+    NamedSubRegex(String, Regex),
+    /// Group of components matched with the given flags, which is not a capture group
+    Flagged(Flags, Regex),
+    /// One character of the class
+    Class(CharClass),
+    /// Any character but a newline, or any character with the dot_matches_newline flag
+    Dot,
+    /// Condition on the characters around the current position, which matches no character
+    Assertion(Assertion),
+    /// Matches no character, only if the Regex matches from the current position (or does not
+    /// when negated). Only the backtracking engine supports it.
+    Lookahead { regex: Regex, negated: bool },
+    /// Matches no character, only if the Regex matches a text ending at the current position (or
+    /// does not when negated). Only the backtracking engine supports it.
+    Lookbehind { regex: Regex, negated: bool },
+}
+
+/// enum Assertion
+/// Zero-width conditions on the characters before and after a position of the input:
+/// - Start, End: StartOfInput and EndOfInput, or StartOfLine and EndOfLine with the multiline flag
+/// - StartOfInput, EndOfInput: the position is the start, or the end, of the whole input
+/// - StartOfLine, EndOfLine: the position is the start or the end of the input, or right after,
+///   or right before, a newline
+/// - WordBoundary: exactly one of the characters around the position is a word character, that is
+///   a Unicode alphanumeric or `_` (see CharClass::unicode_word), the start and the end of the
+///   input counting as non-word characters
+/// - NotWordBoundary: both or none of the characters around the position are word characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assertion {
+    Start,
+    End,
+    StartOfInput,
+    EndOfInput,
+    StartOfLine,
+    EndOfLine,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+/// Implemented methods:
+/// - holds: Returns true if the assertion holds between the given characters, None standing for
+///   the start or the end of the input, with every flag off
+/// - with_flags: Returns the assertion that this one stands for with the given flags
+impl Assertion {
+    pub fn holds(self, before: Option<char>, after: Option<char>) -> bool {
+        let boundary = || before.is_some_and(CharClass::is_word) != after.is_some_and(CharClass::is_word);
+        match self {
+            Assertion::Start | Assertion::StartOfInput => before.is_none(),
+            Assertion::End | Assertion::EndOfInput => after.is_none(),
+            Assertion::StartOfLine => before.is_none_or(|ch| ch == NEWLINE),
+            Assertion::EndOfLine => after.is_none_or(|ch| ch == NEWLINE),
+            Assertion::WordBoundary => boundary(),
+            Assertion::NotWordBoundary => !boundary(),
+        }
+    }
+
+    pub fn with_flags(self, flags: Flags) -> Assertion {
+        match self {
+            Assertion::Start if flags.is_multiline() => Assertion::StartOfLine,
+            Assertion::End if flags.is_multiline() => Assertion::EndOfLine,
+            Assertion::Start => Assertion::StartOfInput,
+            Assertion::End => Assertion::EndOfInput,
+            assertion => assertion,
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Assertion::Start => write!(f, "^"),
+            Assertion::End => write!(f, "$"),
+            Assertion::StartOfInput => write!(f, "\\A"),
+            Assertion::EndOfInput => write!(f, "\\z"),
+            Assertion::StartOfLine => write!(f, "(?m:^)"),
+            Assertion::EndOfLine => write!(f, "(?m:$)"),
+            Assertion::WordBoundary => write!(f, "\\b"),
+            Assertion::NotWordBoundary => write!(f, "\\B"),
+        }
+    }
+}
+
+/// enum Greediness
+/// How a Repeat chooses its number of repetitions, when several let the rest of the Regex match:
+/// - Greedy: as many as possible, giving some back if the rest does not match
+/// - Lazy: as few as possible, taking more if the rest does not match
+/// - Possessive: as many as possible, never giving any back, even if the rest then does not match.
+///   This changes what the Regex matches in a way that automata can't express: only the
+///   backtracking engine (automata::backtrack) supports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Greediness {
+    #[default]
+    Greedy,
+    Lazy,
+    Possessive,
+}
+
+/// Flags:
+/// Options changing how the components inside a Flagged component match. Each of them is turned on
+/// with Some(true), off with Some(false), or left as it is around the component with None, every
+/// flag being off outside of any Flagged component:
+/// - case_insensitive: text and classes match the characters with the same case folding as theirs
+///   (see class::fold_case), so that `Keyword("select")` also matches `SELECT`
+/// - dot_matches_newline: Dot matches a newline as well
+/// - multiline: the Start and End assertions (`^` and `$`) hold at the start and the end of every
+///   line, instead of the whole input
+///
+/// In the textual syntax, `(?i)`, `(?s)` and `(?m)` turn them on for the rest of the group and
+/// `(?i:...)` within the parentheses only, while `(?-i)` turns them off (see components::syntax).
+///
+/// ```rust
+/// use pilator::{
+///     automata::nfa::Nfa,
+///     components::{items::{Flags, RegexComponent}, regex::Regex},
+/// };
+///
+/// let select = Regex::new(vec![RegexComponent::Keyword("select".to_string())]);
+/// let insensitive = select.clone().with_flags(Flags::new().with_case_insensitive(true));
+/// assert_eq!(insensitive.to_string(), "(?i:select)");
+/// assert!(Nfa::new(&insensitive).unwrap().is_match("SeLeCt"));
+/// assert!(!Nfa::new(&select).unwrap().is_match("SeLeCt"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Flags {
+    pub case_insensitive: Option<bool>,
+    pub dot_matches_newline: Option<bool>,
+    pub multiline: Option<bool>,
+}
+
+/// Implemented methods:
+/// - new: Creates the flags leaving every flag as it is
+/// - with_case_insensitive, with_dot_matches_newline, with_multiline: Return the flags turning the
+///   flag on or off
+/// - within: Returns the flags in effect inside a component with these flags, the given ones being
+///   in effect around it
+/// - is_case_insensitive, is_dot_matches_newline, is_multiline: Return true if the flag is on
+impl Flags {
+    pub fn new() -> Flags {
+        Flags::default()
+    }
+
+    pub fn with_case_insensitive(self, on: bool) -> Flags {
+        Flags { case_insensitive: Some(on), ..self }
+    }
+
+    pub fn with_dot_matches_newline(self, on: bool) -> Flags {
+        Flags { dot_matches_newline: Some(on), ..self }
+    }
+
+    pub fn with_multiline(self, on: bool) -> Flags {
+        Flags { multiline: Some(on), ..self }
+    }
+
+    pub fn within(self, outer: Flags) -> Flags {
+        Flags {
+            case_insensitive: self.case_insensitive.or(outer.case_insensitive),
+            dot_matches_newline: self.dot_matches_newline.or(outer.dot_matches_newline),
+            multiline: self.multiline.or(outer.multiline),
+        }
+    }
+
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive == Some(true)
+    }
+
+    pub fn is_dot_matches_newline(&self) -> bool {
+        self.dot_matches_newline == Some(true)
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.multiline == Some(true)
+    }
+}
+
+/// Display implementation: the letters of the flags turned on, then a `-` and the letters of the
+/// flags turned off, as in `(?i-s:...)`
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letters = [('i', self.case_insensitive), ('s', self.dot_matches_newline), ('m', self.multiline)];
+        for (letter, _) in letters.iter().filter(|(_, flag)| *flag == Some(true)) {
+            write!(f, "{}", letter)?;
+        }
+        if letters.iter().any(|(_, flag)| *flag == Some(false)) {
+            write!(f, "-")?;
+        }
+        for (letter, _) in letters.iter().filter(|(_, flag)| *flag == Some(false)) {
+            write!(f, "{}", letter)?;
+        }
+        Ok(())
+    }
+}
+
+/// Length in bytes of the prefix of the input matching the text, None if the input does not start
+/// with it. Case-insensitively, the prefix can have another length than the text: `ſ` is matched
+/// by `s`.
+pub(crate) fn match_text(text: &str, input: &str, case_insensitive: bool) -> Option<usize> {
+    match_text_prefix(text, input, case_insensitive).ok()
+}
+
+/// Length in bytes of the prefix of the input matching the text, or when the input does not start
+/// with the text, of the prefix of the input matching the start of the text.
+pub(crate) fn match_text_prefix(text: &str, input: &str, case_insensitive: bool) -> Result<usize, usize> {
+    if !case_insensitive && input.starts_with(text) {
+        return Ok(text.len());
+    }
+    let mut len = 0;
+    let mut chars = input.chars();
+    for expected in text.chars() {
+        match chars.next() {
+            Some(ch) if ch == expected || (case_insensitive && fold_case(ch) == fold_case(expected)) => {
+                len += ch.len_utf8()
+            }
+            _ => return Err(len),
+        }
+    }
+    Ok(len)
 }
 
 impl RegexComponent {
@@ -21,15 +246,23 @@ impl RegexComponent {
     /// This method returns a boolean indicating if the component can be nullable or not.
     pub fn is_nullable(&self) -> bool {
         match self {
-            RegexComponent::Literal(_) => false,
-            RegexComponent::Keyword(_) => false,
-            RegexComponent::Operator(_) => false,
-            RegexComponent::Identifier(_) => false,
+            RegexComponent::Literal(value) => value.is_empty(),
+            RegexComponent::Keyword(value) => value.is_empty(),
+            RegexComponent::Operator(value) => value.is_empty(),
+            RegexComponent::Identifier(value) => value.is_empty(),
             RegexComponent::ZeroOrMore(_) => true,
-            RegexComponent::OneOrMore(_) => false,
+            RegexComponent::OneOrMore(regex) => regex.components.iter().all(|c| c.is_nullable()),
             RegexComponent::ZeroOrOne(_) => true,
-            RegexComponent::Or(_, _) => false,
-            RegexComponent::SubRegex(regex) => {
+            RegexComponent::Repeat { regex, min, .. } => {
+                *min == 0 || regex.components.iter().all(|c| c.is_nullable())
+            }
+            RegexComponent::Or(regex1, regex2) => {
+                regex1.components.iter().all(|c| c.is_nullable())
+                    || regex2.components.iter().all(|c| c.is_nullable())
+            }
+            RegexComponent::SubRegex(regex)
+            | RegexComponent::NamedSubRegex(_, regex)
+            | RegexComponent::Flagged(_, regex) => {
                 for component in regex.components.iter() {
                     if !component.is_nullable() {
                         return false;
@@ -37,6 +270,289 @@ impl RegexComponent {
                 }
                 true
             },
+            RegexComponent::Class(_) | RegexComponent::Dot => false,
+            RegexComponent::Assertion(_)
+            | RegexComponent::Lookahead { .. }
+            | RegexComponent::Lookbehind { .. } => true,
+        }
+    }
+
+    /// as_text
+    /// Returns the text matched by the component when it is a plain piece of text
+    /// (Literal, Keyword, Operator or Identifier), None otherwise.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            RegexComponent::Literal(value)
+            | RegexComponent::Keyword(value)
+            | RegexComponent::Operator(value)
+            | RegexComponent::Identifier(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// trailing_assertion
+    /// Returns the assertion that must hold right after the text of the component: a word boundary
+    /// after a Keyword ending with a word character, so that `if` does not match the start of `iffy`.
+    pub fn trailing_assertion(&self) -> Option<Assertion> {
+        match self {
+            RegexComponent::Keyword(value) if value.chars().next_back().is_some_and(CharClass::is_word) => {
+                Some(Assertion::WordBoundary)
+            }
+            _ => None,
+        }
+    }
+
+    /// backtracking_only
+    /// Returns the first component, this one or one of its inner components, that only the
+    /// backtracking engine can match, such as a possessive Repeat or a lookaround.
+    pub fn backtracking_only(&self) -> Option<&RegexComponent> {
+        match self {
+            RegexComponent::Repeat { greediness: Greediness::Possessive, .. }
+            | RegexComponent::Lookahead { .. }
+            | RegexComponent::Lookbehind { .. } => Some(self),
+            RegexComponent::ZeroOrMore(regex)
+            | RegexComponent::OneOrMore(regex)
+            | RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::Repeat { regex, .. }
+            | RegexComponent::SubRegex(regex)
+            | RegexComponent::NamedSubRegex(_, regex)
+            | RegexComponent::Flagged(_, regex) => {
+                regex.components.iter().find_map(RegexComponent::backtracking_only)
+            }
+            RegexComponent::Or(regex1, regex2) => regex1
+                .components
+                .iter()
+                .chain(regex2.components.iter())
+                .find_map(RegexComponent::backtracking_only),
+            _ => None,
+        }
+    }
+
+    /// unbounded_lookbehind
+    /// Returns the first Lookbehind, this component or one of its inner components, whose Regex
+    /// matches texts of unbounded length, which the backtracking engine can't look behind for.
+    pub fn unbounded_lookbehind(&self) -> Option<&RegexComponent> {
+        let regexes: Vec<&Regex> = match self {
+            RegexComponent::Lookbehind { regex, .. }
+                if regex.components.iter().map(RegexComponent::max_len).sum::<Option<usize>>().is_none() =>
+            {
+                return Some(self);
+            }
+            RegexComponent::ZeroOrMore(regex)
+            | RegexComponent::OneOrMore(regex)
+            | RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::Repeat { regex, .. }
+            | RegexComponent::SubRegex(regex)
+            | RegexComponent::NamedSubRegex(_, regex)
+            | RegexComponent::Flagged(_, regex)
+            | RegexComponent::Lookahead { regex, .. }
+            | RegexComponent::Lookbehind { regex, .. } => vec![regex],
+            RegexComponent::Or(regex1, regex2) => vec![regex1, regex2],
+            _ => vec![],
+        };
+        regexes
+            .into_iter()
+            .flat_map(|regex| regex.components.iter())
+            .find_map(RegexComponent::unbounded_lookbehind)
+    }
+
+    /// groups
+    /// Appends the capture groups of the component, itself first if it is one, and then the groups
+    /// of its inner components in order.
+    pub(crate) fn groups<'a>(&'a self, groups: &mut Vec<&'a RegexComponent>) {
+        let regexes: Vec<&Regex> = match self {
+            RegexComponent::SubRegex(regex) | RegexComponent::NamedSubRegex(_, regex) => {
+                groups.push(self);
+                vec![regex]
+            }
+            RegexComponent::ZeroOrMore(regex)
+            | RegexComponent::OneOrMore(regex)
+            | RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::Repeat { regex, .. }
+            | RegexComponent::Flagged(_, regex)
+            | RegexComponent::Lookahead { regex, .. }
+            | RegexComponent::Lookbehind { regex, .. } => vec![regex],
+            RegexComponent::Or(regex1, regex2) => vec![regex1, regex2],
+            _ => vec![],
+        };
+        for component in regexes.into_iter().flat_map(|regex| regex.components.iter()) {
+            component.groups(groups);
+        }
+    }
+
+    /// max_len
+    /// Returns the largest number of characters the component can match, None if it is unbounded.
+    pub fn max_len(&self) -> Option<usize> {
+        let sum = |regex: &Regex| regex.components.iter().map(RegexComponent::max_len).sum::<Option<usize>>();
+        match self {
+            RegexComponent::Literal(value)
+            | RegexComponent::Keyword(value)
+            | RegexComponent::Operator(value)
+            | RegexComponent::Identifier(value) => Some(value.chars().count()),
+            RegexComponent::ZeroOrMore(regex) | RegexComponent::OneOrMore(regex) => {
+                // A loop over nothing is nothing
+                sum(regex).filter(|&len| len == 0)
+            }
+            RegexComponent::Repeat { regex, max, .. } => match (sum(regex)?, max) {
+                (0, _) => Some(0),
+                (len, max) => max.map(|max| len * max),
+            },
+            RegexComponent::ZeroOrOne(regex)
+            | RegexComponent::SubRegex(regex)
+            | RegexComponent::NamedSubRegex(_, regex)
+            | RegexComponent::Flagged(_, regex) => sum(regex),
+            RegexComponent::Or(regex1, regex2) => Some(sum(regex1)?.max(sum(regex2)?)),
+            RegexComponent::Class(_) | RegexComponent::Dot => Some(1),
+            RegexComponent::Assertion(_)
+            | RegexComponent::Lookahead { .. }
+            | RegexComponent::Lookbehind { .. } => Some(0),
+        }
+    }
+
+    /// match_terminal
+    /// Returns the length in bytes of the prefix of the input matched by a terminal component: its
+    /// text, followed by its trailing assertion if any, or one character of its class or of Dot.
+    /// A Flagged component made of a single terminal matches like it with its flags. None if it
+    /// does not match, for empty text, for assertions and for the other components made of other
+    /// components.
+    pub fn match_terminal(&self, input: &str) -> Option<usize> {
+        self.match_terminal_with(input, Flags::new())
+    }
+
+    /// match_terminal with the given flags in effect around the component
+    pub(crate) fn match_terminal_with(&self, input: &str, flags: Flags) -> Option<usize> {
+        let first = input.chars().next();
+        match self {
+            RegexComponent::Class(class) if flags.is_case_insensitive() => {
+                first.filter(|&ch| class.contains_ignoring_case(ch)).map(char::len_utf8)
+            }
+            RegexComponent::Class(class) => first.filter(|&ch| class.contains(ch)).map(char::len_utf8),
+            RegexComponent::Dot => first
+                .filter(|&ch| ch != NEWLINE || flags.is_dot_matches_newline())
+                .map(char::len_utf8),
+            RegexComponent::Flagged(inner, regex) => match regex.components.as_slice() {
+                [component] => component.match_terminal_with(input, inner.within(flags)),
+                _ => None,
+            },
+            component => {
+                let text = component.as_text().filter(|text| !text.is_empty())?;
+                let len = match_text(text, input, flags.is_case_insensitive())?;
+                let holds = component.trailing_assertion().is_none_or(|assertion| {
+                    assertion.holds(input[..len].chars().next_back(), input[len..].chars().next())
+                });
+                holds.then_some(len)
+            }
+        }
+    }
+}
+
+/// Characters that have a meaning in the textual regex syntax and need to be escaped
+/// when a piece of text is displayed.
+const SPECIAL_CHARS: &str = "\\*+?|()[]{}.^$";
+
+fn write_escaped(f: &mut fmt::Formatter<'_>, text: &str) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '\t' => write!(f, "\\t")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            c if c == ' ' || SPECIAL_CHARS.contains(c) => write!(f, "\\{}", c)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the regex as an operand of a postfix quantifier, adding parentheses
+/// unless it is a single character, a named group or a Flagged component.
+fn write_quantified(f: &mut fmt::Formatter<'_>, regex: &Regex, quantifier: &str) -> fmt::Result {
+    match regex.components.as_slice() {
+        [component @ (RegexComponent::Class(_)
+        | RegexComponent::Dot
+        | RegexComponent::Or(_, _)
+        | RegexComponent::SubRegex(_)
+        | RegexComponent::NamedSubRegex(_, _)
+        | RegexComponent::Flagged(_, _))] => {
+            write!(f, "{}{}", component, quantifier)
+        }
+        [component] if component.as_text().is_some_and(|text| text.chars().count() == 1) => {
+            write!(f, "{}{}", component, quantifier)
+        }
+        // A group that does not capture, as the operand is not a SubRegex
+        _ => write!(f, "(?:{}){}", regex, quantifier),
+    }
+}
+
+/// Writes the Regex inside the parentheses of a group: the parentheses of the group are enough for
+/// an alternation, whose alternatives are written one after the other
+fn write_alternatives(f: &mut fmt::Formatter<'_>, regex: &Regex) -> fmt::Result {
+    match regex.components.as_slice() {
+        [RegexComponent::Or(regex1, regex2)] => {
+            write!(f, "{}|", regex1)?;
+            write_alternatives(f, regex2)
+        }
+        _ => write!(f, "{}", regex),
+    }
+}
+
+/// Display implementation: components are written in the textual regex syntax
+impl fmt::Display for RegexComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexComponent::Literal(value)
+            | RegexComponent::Keyword(value)
+            | RegexComponent::Operator(value)
+            | RegexComponent::Identifier(value) => write_escaped(f, value),
+            RegexComponent::ZeroOrMore(regex) => write_quantified(f, regex, "*"),
+            RegexComponent::OneOrMore(regex) => write_quantified(f, regex, "+"),
+            RegexComponent::ZeroOrOne(regex) => write_quantified(f, regex, "?"),
+            RegexComponent::Repeat { regex, min, max, greediness } => {
+                let suffix = match greediness {
+                    Greediness::Greedy => "",
+                    Greediness::Lazy => "?",
+                    Greediness::Possessive => "+",
+                };
+                // `*`, `+` and `?` alone are the other components: only the other greediness use them
+                let quantifier = match (min, max) {
+                    (0, None) if !suffix.is_empty() => "*".to_string(),
+                    (1, None) if !suffix.is_empty() => "+".to_string(),
+                    (0, Some(1)) if !suffix.is_empty() => "?".to_string(),
+                    (min, Some(max)) if max == min => format!("{{{}}}", min),
+                    (min, Some(max)) => format!("{{{},{}}}", min, max),
+                    (min, None) => format!("{{{},}}", min),
+                };
+                write_quantified(f, regex, &format!("{}{}", quantifier, suffix))
+            }
+            // A group that does not capture, as a capture group is a SubRegex
+            RegexComponent::Or(regex1, regex2) => {
+                write!(f, "(?:{}|", regex1)?;
+                write_alternatives(f, regex2)?;
+                write!(f, ")")
+            }
+            RegexComponent::SubRegex(regex) => {
+                write!(f, "(")?;
+                write_alternatives(f, regex)?;
+                write!(f, ")")
+            }
+            RegexComponent::NamedSubRegex(name, regex) => {
+                write!(f, "(?<{}>", name)?;
+                write_alternatives(f, regex)?;
+                write!(f, ")")
+            }
+            RegexComponent::Flagged(flags, regex) => {
+                write!(f, "(?{}:", flags)?;
+                write_alternatives(f, regex)?;
+                write!(f, ")")
+            }
+            RegexComponent::Class(class) => write!(f, "{}", class),
+            RegexComponent::Dot => write!(f, "."),
+            RegexComponent::Assertion(assertion) => write!(f, "{}", assertion),
+            RegexComponent::Lookahead { regex, negated } => {
+                write!(f, "(?{}{})", if *negated { "!" } else { "=" }, regex)
+            }
+            RegexComponent::Lookbehind { regex, negated } => {
+                write!(f, "(?<{}{})", if *negated { "!" } else { "=" }, regex)
+            }
         }
     }
 }
@@ -1,2 +1,9 @@
 pub mod regex;
-pub mod items;
\ No newline at end of file
+pub mod items;
+pub mod grammar;
+pub mod syntax;
+pub mod ebnf;
+pub mod token;
+pub mod class;
+pub mod unicode_tables;
+pub mod captures;
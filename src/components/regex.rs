@@ -1,16 +1,51 @@
+use std::fmt;
 use std::ops::Add;
+use std::str::FromStr;
 
-use crate::components::items::RegexComponent;
+use crate::components::items::{Flags, RegexComponent};
+use crate::components::syntax::{self, RegexSyntaxError};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Regex {
     pub components: Vec<RegexComponent>,
 }
 
+/// Implemented methods:
+/// - new: Creates a new Regex from its components
+/// - with_flags: Returns the Regex matching its components with the given flags
+/// - group_names: Returns the names of the capture groups, by number
 impl Regex {
     pub fn new(components: Vec<RegexComponent>) -> Self {
         Regex { components }
     }
+
+    /// with_flags
+    /// The components are wrapped in a Flagged component, which does not change the tokens or
+    /// the capture groups of the Regex.
+    pub fn with_flags(self, flags: Flags) -> Self {
+        Regex::new(vec![RegexComponent::Flagged(flags, self)])
+    }
+
+    /// group_names
+    /// Capture groups are numbered from 1 in the order of their opening parenthesis: a SubRegex or
+    /// NamedSubRegex comes before the groups it contains, which come before the groups after it.
+    /// Group 0 stands for the whole match, and has no name like the SubRegex groups.
+    pub fn group_names(&self) -> Vec<Option<&str>> {
+        let names = self.groups().into_iter().map(|group| match group {
+            RegexComponent::NamedSubRegex(name, _) => Some(name.as_str()),
+            _ => None,
+        });
+        std::iter::once(None).chain(names).collect()
+    }
+
+    /// SubRegex and NamedSubRegex components of the Regex, group 1 first
+    pub(crate) fn groups(&self) -> Vec<&RegexComponent> {
+        let mut groups = vec![];
+        for component in self.components.iter() {
+            component.groups(&mut groups);
+        }
+        groups
+    }
 }
 
 impl Add<Regex> for Regex {
@@ -21,4 +56,39 @@ impl Add<Regex> for Regex {
         components.extend(other.components);
         Regex { components }
     }
-}
\ No newline at end of file
+}
+
+/// Display implementation: the regex is written in the textual regex syntax, with its components
+/// separated by a space
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", component)?;
+        }
+        Ok(())
+    }
+}
+
+/// FromStr implementation: compiles a pattern written in the textual regex syntax
+/// (see components::syntax), so that `"b a* c".parse::<Regex>()` is the same as
+/// `Regex::new(vec![Literal("b"), ZeroOrMore(Regex::new(vec![Literal("a")])), Literal("c")])`.
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::components::{items::RegexComponent, regex::Regex};
+///
+/// let regex = Regex::from_str("b a* c").unwrap();
+/// assert_eq!(regex.components[0], RegexComponent::Literal("b".to_string()));
+/// assert_eq!(regex.to_string(), "b a* c");
+/// assert_eq!(Regex::from_str("a)").unwrap_err().position, 1);
+/// ```
+impl FromStr for Regex {
+    type Err = RegexSyntaxError;
+
+    fn from_str(pattern: &str) -> Result<Regex, RegexSyntaxError> {
+        syntax::parse(pattern)
+    }
+}
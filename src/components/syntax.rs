@@ -0,0 +1,884 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::components::class::{CharClass, UnicodeClass};
+use crate::components::items::{Assertion, Flags, Greediness, RegexComponent};
+use crate::components::regex::Regex;
+
+/// Textual regex syntax:
+/// - consecutive characters form a single Literal, unescaped whitespace separates components
+///   and is otherwise ignored: `if x` is `Literal("if"), Literal("x")`
+/// - `*`, `+` and `?` apply to the previous character or group (ZeroOrMore, OneOrMore, ZeroOrOne),
+///   and so do `{n}`, `{n,}` and `{n,m}` (Repeat exactly n times, at least n times, or n to m times)
+/// - a quantifier followed by `?` is lazy, and followed by `+` is possessive (see items::Greediness):
+///   `a*?`, `a++`, `a{2,}?`
+/// - `a|b` is an Or of the two sides, `(...)` is a SubRegex, the alternations it contains included:
+///   `(a|b)` is a capture group like `(a b)`, while an Or alone is written `(?:a|b)`
+/// - `(?<name>...)`, or `(?P<name>...)`, is a NamedSubRegex, whose name is made of word characters
+///   and does not start with a digit. SubRegex and NamedSubRegex are capture groups, which capture
+///   every iteration when repeated by a quantifier: `(a b)*` and `(?<ab>a b)*` record each `ab`,
+///   while `(?:a b)*` captures nothing
+/// - `(?i:...)` is a Flagged component whose flags are given by letters: `i` for case_insensitive,
+///   `s` for dot_matches_newline and `m` for multiline, the ones after a `-` being turned off, as
+///   in `(?i-s:...)` (see items::Flags). `(?:...)` is a group without flags, which does not
+///   capture. `(?i)` turns the flags on for the rest of the group, its next alternatives included:
+///   `a (?i) b|c` is `a (?i:b)|(?i:c)`.
+/// - `(?=...)` and `(?!...)` are a Lookahead and a negated one, `(?<=...)` and `(?<!...)` a
+///   Lookbehind and a negated one, whose Regex must match texts of a bounded length. Like
+///   assertions, they can't be repeated.
+/// - `[abc]` and `[a-z]` are Class components matching one of the characters, `[^abc]` matches one
+///   character that is not listed
+/// - `\d`, `\w` and `\s` are the predefined classes of digits, word characters and whitespace,
+///   `\D`, `\W` and `\S` their negations; they can also be used inside `[...]`
+/// - `\p{Name}` (or `\pL` for a one letter name) is a Unicode class, such as `\p{Alphabetic}`, the
+///   general category `\p{Nd}` or the script `\p{Greek}`, and `\P{Name}` its negation (see
+///   components::class::UnicodeClass)
+/// - `.` is Dot, matching any character but a newline
+/// - `^` and `$` assert the start and the end of the input, or of a line with the multiline flag,
+///   while `\A` and `\z` always assert the start and the end of the input; `\b` asserts a word
+///   boundary and `\B` its absence (see items::Assertion). Assertions can't be repeated.
+/// - `\` escapes any special character, `\ `, `\t`, `\n` and `\r` are whitespace characters
+pub(crate) fn parse(pattern: &str) -> Result<Regex, RegexSyntaxError> {
+    let mut parser = SyntaxParser {
+        chars: pattern.char_indices().peekable(),
+        len: pattern.len(),
+        names: HashSet::new(),
+        flags: Flags::new(),
+    };
+    let regex = parser.alternation()?;
+    match parser.chars.next() {
+        Some((position, c)) => Err(RegexSyntaxError {
+            position,
+            kind: SyntaxErrorKind::UnexpectedChar(c),
+        }),
+        None => Ok(regex),
+    }
+}
+
+/// enum SyntaxErrorKind
+/// Reasons why a pattern is not valid in the textual regex syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// A character that can't appear at this position, such as an unmatched `)`
+    UnexpectedChar(char),
+    /// A `(` or `[` that is never closed
+    Unclosed(char),
+    /// A quantifier with nothing, or another quantifier, before it
+    NothingToRepeat(char),
+    /// A `\` followed by a character that has no escape meaning
+    UnknownEscape(char),
+    /// A `\` at the end of the pattern
+    TrailingBackslash,
+    /// A range whose start is greater than its end, such as `z-a`
+    InvalidRange(char, char),
+    /// A repetition that is not `{n}`, `{n,}` or `{n,m}` with n <= m, such as `{3,1}`
+    InvalidRepetition(String),
+    /// A class with no character, such as `[]`
+    EmptyClass,
+    /// A `\p{...}` naming no Unicode class
+    UnknownClass(String),
+    /// A group name that is empty, starts with a digit or has a non word character
+    InvalidGroupName(String),
+    /// A group name already used by another group of the pattern
+    DuplicateGroupName(String),
+    /// A lookbehind whose Regex can match texts of any length, such as `(?<=a*)`
+    UnboundedLookbehind,
+    /// A character that is not a flag, between `(?` and the `:` or `)` ending the flags
+    UnknownFlag(char),
+    /// A construct of the syntax that RegexComponent can't express
+    Unsupported(char),
+}
+
+/// RegexSyntaxError:
+/// Error returned when compiling a pattern, with the byte offset of the offending character.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexSyntaxError {
+    pub position: usize,
+    pub kind: SyntaxErrorKind,
+}
+
+impl fmt::Display for SyntaxErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxErrorKind::UnexpectedChar(c) => write!(f, "unexpected `{}`", c),
+            SyntaxErrorKind::Unclosed(c) => write!(f, "unclosed `{}`", c),
+            SyntaxErrorKind::NothingToRepeat(c) => write!(f, "nothing to repeat before `{}`", c),
+            SyntaxErrorKind::UnknownEscape(c) => write!(f, "unknown escape `\\{}`", c),
+            SyntaxErrorKind::TrailingBackslash => write!(f, "trailing `\\`"),
+            SyntaxErrorKind::InvalidRange(start, end) => {
+                write!(f, "invalid range `{}-{}`", start, end)
+            }
+            SyntaxErrorKind::InvalidRepetition(repetition) => {
+                write!(f, "invalid repetition `{{{}}}`", repetition)
+            }
+            SyntaxErrorKind::EmptyClass => write!(f, "empty character class"),
+            SyntaxErrorKind::UnknownClass(name) => write!(f, "unknown Unicode class `{}`", name),
+            SyntaxErrorKind::InvalidGroupName(name) => write!(f, "invalid group name `{}`", name),
+            SyntaxErrorKind::DuplicateGroupName(name) => write!(f, "duplicate group name `{}`", name),
+            SyntaxErrorKind::UnboundedLookbehind => write!(f, "lookbehind of unbounded length"),
+            SyntaxErrorKind::UnknownFlag(c) => write!(f, "unknown flag `{}`", c),
+            SyntaxErrorKind::Unsupported(c) => write!(f, "unsupported `{}`", c),
+        }
+    }
+}
+
+impl fmt::Display for RegexSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.position)
+    }
+}
+
+impl std::error::Error for RegexSyntaxError {}
+
+/// Kind of a group, given by what follows its `(`: a SubRegex, a NamedSubRegex, a lookaround,
+/// negated or not, a Flagged component, or flags for the rest of the enclosing group, which end
+/// with the `)`
+enum Group {
+    Plain,
+    Named(String),
+    Lookahead(bool),
+    Lookbehind(bool),
+    Flagged(Flags),
+    Directive(Flags),
+}
+
+/// Recursive descent parser over the characters of a pattern, with their byte offsets
+struct SyntaxParser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len: usize,
+    /// Names of the named groups parsed so far
+    names: HashSet<String>,
+    /// Flags turned on by `(?flags)` in the alternative being parsed, which also apply to the next
+    /// alternatives of its group
+    flags: Flags,
+}
+
+impl SyntaxParser<'_> {
+    fn error<T>(&self, position: usize, kind: SyntaxErrorKind) -> Result<T, RegexSyntaxError> {
+        Err(RegexSyntaxError { position, kind })
+    }
+
+    /// alternation := sequence ('|' sequence)*
+    fn alternation(&mut self) -> Result<Regex, RegexSyntaxError> {
+        let first = self.sequence()?;
+        if !matches!(self.chars.peek(), Some((_, '|'))) {
+            return Ok(first);
+        }
+        self.chars.next();
+        let flags = std::mem::take(&mut self.flags);
+        let rest = self.alternation()?;
+        let rest = if flags == Flags::new() { rest } else { rest.with_flags(flags) };
+        Ok(Regex::new(vec![RegexComponent::Or(first, rest)]))
+    }
+
+    /// sequence := (atom quantifier?)*
+    fn sequence(&mut self) -> Result<Regex, RegexSyntaxError> {
+        let mut components: Vec<RegexComponent> = vec![];
+        // Characters of the Literal being built, not yet pushed to the components
+        let mut text = String::new();
+        while let Some(&(position, c)) = self.chars.peek() {
+            match c {
+                '|' | ')' => break,
+                c if c.is_whitespace() => {
+                    self.chars.next();
+                    Self::flush(&mut text, &mut components);
+                }
+                '*' | '+' | '?' | '{' => {
+                    self.chars.next();
+                    let bounds = match c {
+                        '{' => self.bounds(position)?,
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        _ => (0, Some(1)),
+                    };
+                    let greediness = match self.chars.peek() {
+                        Some((_, '?')) => Greediness::Lazy,
+                        Some((_, '+')) => Greediness::Possessive,
+                        _ => Greediness::Greedy,
+                    };
+                    if greediness != Greediness::Greedy {
+                        self.chars.next();
+                    }
+                    let operand = match text.pop() {
+                        Some(last) => {
+                            Self::flush(&mut text, &mut components);
+                            Regex::new(vec![RegexComponent::Literal(last.to_string())])
+                        }
+                        None => match components.pop() {
+                            Some(RegexComponent::Literal(mut previous)) => {
+                                let last = previous.pop().unwrap_or_default();
+                                Self::flush(&mut previous, &mut components);
+                                Regex::new(vec![RegexComponent::Literal(last.to_string())])
+                            }
+                            Some(
+                                component @ (RegexComponent::Or(_, _)
+                                | RegexComponent::SubRegex(_)
+                                | RegexComponent::Class(_)
+                                | RegexComponent::Dot
+                                | RegexComponent::NamedSubRegex(_, _)
+                                | RegexComponent::Flagged(_, _)),
+                            ) => Regex::new(vec![component]),
+                            _ => return self.error(position, SyntaxErrorKind::NothingToRepeat(c)),
+                        },
+                    };
+                    components.push(match (c, greediness) {
+                        ('*', Greediness::Greedy) => RegexComponent::ZeroOrMore(operand),
+                        ('+', Greediness::Greedy) => RegexComponent::OneOrMore(operand),
+                        ('?', Greediness::Greedy) => RegexComponent::ZeroOrOne(operand),
+                        _ => RegexComponent::Repeat { regex: operand, min: bounds.0, max: bounds.1, greediness },
+                    });
+                }
+                '(' => {
+                    self.chars.next();
+                    Self::flush(&mut text, &mut components);
+                    let group = self.group(position)?;
+                    if let Group::Directive(flags) = group {
+                        self.flags = flags.within(self.flags);
+                        let rest = self.sequence()?;
+                        components.push(RegexComponent::Flagged(flags, rest));
+                        continue;
+                    }
+                    // The flags of the alternatives of the enclosing group do not leak into this one
+                    let outer = std::mem::take(&mut self.flags);
+                    let regex = self.alternation()?;
+                    self.flags = outer;
+                    if self.chars.next().is_none() {
+                        return self.error(position, SyntaxErrorKind::Unclosed('('));
+                    }
+                    components.push(match group {
+                        Group::Named(name) => RegexComponent::NamedSubRegex(name, regex),
+                        Group::Flagged(flags) => RegexComponent::Flagged(flags, regex),
+                        Group::Directive(_) => unreachable!("flags for the rest of the group have no `)`"),
+                        Group::Lookahead(negated) => RegexComponent::Lookahead { regex, negated },
+                        Group::Lookbehind(negated) => {
+                            if regex.components.iter().map(RegexComponent::max_len).sum::<Option<usize>>().is_none() {
+                                return self.error(position, SyntaxErrorKind::UnboundedLookbehind);
+                            }
+                            RegexComponent::Lookbehind { regex, negated }
+                        }
+                        Group::Plain => RegexComponent::SubRegex(regex),
+                    });
+                }
+                '[' => {
+                    self.chars.next();
+                    Self::flush(&mut text, &mut components);
+                    components.push(self.class(position)?);
+                }
+                '\\' => {
+                    self.chars.next();
+                    if let Some(assertion) = self.assertion() {
+                        Self::flush(&mut text, &mut components);
+                        components.push(RegexComponent::Assertion(assertion));
+                    } else if let Some(class) = self.predefined(position)? {
+                        Self::flush(&mut text, &mut components);
+                        components.push(RegexComponent::Class(class));
+                    } else {
+                        text.push(self.escape(position)?);
+                    }
+                }
+                '^' | '$' => {
+                    self.chars.next();
+                    Self::flush(&mut text, &mut components);
+                    let assertion = if c == '^' { Assertion::Start } else { Assertion::End };
+                    components.push(RegexComponent::Assertion(assertion));
+                }
+                '.' => {
+                    self.chars.next();
+                    Self::flush(&mut text, &mut components);
+                    components.push(RegexComponent::Dot);
+                }
+                ']' | '}' => return self.error(position, SyntaxErrorKind::UnexpectedChar(c)),
+                _ => {
+                    self.chars.next();
+                    text.push(c);
+                }
+            }
+        }
+        Self::flush(&mut text, &mut components);
+        Ok(Regex::new(components))
+    }
+
+    /// class := '[' '^'? (char ('-' char)? | predefined)+ ']', the opening bracket being at the given
+    /// position
+    fn class(&mut self, start: usize) -> Result<RegexComponent, RegexSyntaxError> {
+        let negated = matches!(self.chars.peek(), Some((_, '^')));
+        if negated {
+            self.chars.next();
+        }
+        let mut ranges: Vec<(char, char)> = vec![];
+        loop {
+            let (position, c) = match self.chars.next() {
+                Some(next) => next,
+                None => return self.error(start, SyntaxErrorKind::Unclosed('[')),
+            };
+            let first = match c {
+                ']' => break,
+                '\\' => match self.predefined(position)? {
+                    Some(class) => {
+                        ranges.extend(class.matched_ranges());
+                        continue;
+                    }
+                    None => self.escape(position)?,
+                },
+                c => c,
+            };
+            let is_range = matches!(self.chars.peek(), Some((_, '-')))
+                && self.chars.clone().nth(1).is_some_and(|(_, c)| c != ']');
+            if !is_range {
+                ranges.push((first, first));
+                continue;
+            }
+            self.chars.next();
+            let last = match self.chars.next() {
+                Some((position, '\\')) => self.escape(position)?,
+                Some((_, c)) => c,
+                None => return self.error(start, SyntaxErrorKind::Unclosed('[')),
+            };
+            if first > last {
+                return self.error(position, SyntaxErrorKind::InvalidRange(first, last));
+            }
+            ranges.push((first, last));
+        }
+
+        if ranges.is_empty() {
+            return self.error(start, SyntaxErrorKind::EmptyClass);
+        }
+        let class = CharClass::new(ranges);
+        Ok(RegexComponent::Class(if negated { class.negated() } else { class }))
+    }
+
+    /// bounds := '{' digits (',' digits?)? '}', the opening brace being at the given position
+    fn bounds(&mut self, start: usize) -> Result<(usize, Option<usize>), RegexSyntaxError> {
+        let mut repetition = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '}')) => break,
+                Some((_, c)) => repetition.push(c),
+                None => return self.error(start, SyntaxErrorKind::Unclosed('{')),
+            }
+        }
+        let count = |text: &str| {
+            let digits = text.bytes().all(|b| b.is_ascii_digit());
+            digits.then_some(text).and_then(|text| text.parse::<usize>().ok())
+        };
+        let bounds = match repetition.split_once(',') {
+            None => count(&repetition).map(|min| (min, Some(min))),
+            Some((min, "")) => count(min).map(|min| (min, None)),
+            Some((min, max)) => count(min).zip(count(max)).map(|(min, max)| (min, Some(max))),
+        };
+        match bounds {
+            Some((min, max)) if max.is_none_or(|max| min <= max) => Ok((min, max)),
+            _ => self.error(start, SyntaxErrorKind::InvalidRepetition(repetition)),
+        }
+    }
+
+    /// group := ('?' ('=' | '!' | '<=' | '<!' | 'P'? '<' word+ '>' | flags))?, the kind of the group
+    /// whose `(` is at the given position, right after it
+    fn group(&mut self, position: usize) -> Result<Group, RegexSyntaxError> {
+        let Some(&(question, '?')) = self.chars.peek() else {
+            return Ok(Group::Plain);
+        };
+        self.chars.next();
+        match self.chars.next() {
+            Some((offset, c @ ('i' | 's' | 'm' | '-' | ':'))) => return self.flags((offset, c), position),
+            Some((_, '=')) => return Ok(Group::Lookahead(false)),
+            Some((_, '!')) => return Ok(Group::Lookahead(true)),
+            Some((_, '<')) => {
+                if let Some((_, '=' | '!')) = self.chars.peek() {
+                    let negated = matches!(self.chars.next(), Some((_, '!')));
+                    return Ok(Group::Lookbehind(negated));
+                }
+            }
+            Some((_, 'P')) if matches!(self.chars.peek(), Some((_, '<'))) => {
+                self.chars.next();
+            }
+            _ => return self.error(question, SyntaxErrorKind::Unsupported('?')),
+        }
+        let mut name = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '>')) => break,
+                Some((_, c)) => name.push(c),
+                None => return self.error(position, SyntaxErrorKind::Unclosed('(')),
+            }
+        }
+        let valid = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && name.chars().next().is_some_and(|c| !c.is_ascii_digit());
+        if !valid {
+            return self.error(position, SyntaxErrorKind::InvalidGroupName(name));
+        }
+        if !self.names.insert(name.clone()) {
+            return self.error(position, SyntaxErrorKind::DuplicateGroupName(name));
+        }
+        Ok(Group::Named(name))
+    }
+
+    /// flags := [ism]* ('-' [ism]*)? (':' | ')'), starting with the given character, after the `(?`
+    /// of the group at the given position
+    fn flags(&mut self, (mut offset, mut c): (usize, char), position: usize) -> Result<Group, RegexSyntaxError> {
+        let mut flags = Flags::new();
+        let mut on = true;
+        loop {
+            match c {
+                ':' => return Ok(Group::Flagged(flags)),
+                ')' => return Ok(Group::Directive(flags)),
+                '-' if on => on = false,
+                'i' => flags.case_insensitive = Some(on),
+                's' => flags.dot_matches_newline = Some(on),
+                'm' => flags.multiline = Some(on),
+                c => return self.error(offset, SyntaxErrorKind::UnknownFlag(c)),
+            }
+            (offset, c) = match self.chars.next() {
+                Some(next) => next,
+                None => return self.error(position, SyntaxErrorKind::Unclosed('(')),
+            };
+        }
+    }
+
+    /// Assertion named by what follows a `\`, which is consumed if it names one
+    fn assertion(&mut self) -> Option<Assertion> {
+        let assertion = match self.chars.peek()? {
+            (_, 'b') => Assertion::WordBoundary,
+            (_, 'B') => Assertion::NotWordBoundary,
+            (_, 'A') => Assertion::StartOfInput,
+            (_, 'z') => Assertion::EndOfInput,
+            _ => return None,
+        };
+        self.chars.next();
+        Some(assertion)
+    }
+
+    /// Predefined or Unicode class named by what follows the `\` at the given position, which is
+    /// consumed if it names one
+    fn predefined(&mut self, position: usize) -> Result<Option<CharClass>, RegexSyntaxError> {
+        let Some(&(_, c)) = self.chars.peek() else {
+            return Ok(None);
+        };
+        let class = match c {
+            'd' => CharClass::digits(),
+            'w' => CharClass::word(),
+            's' => CharClass::whitespace(),
+            'D' => CharClass::digits().negated(),
+            'W' => CharClass::word().negated(),
+            'S' => CharClass::whitespace().negated(),
+            'p' | 'P' => {
+                self.chars.next();
+                let class = CharClass::unicode(self.unicode_class(position)?);
+                return Ok(Some(if c == 'P' { class.negated() } else { class }));
+            }
+            _ => return Ok(None),
+        };
+        self.chars.next();
+        Ok(Some(class))
+    }
+
+    /// Name of a Unicode class, `{Name}` or a single letter, after the `\p` at the given position
+    fn unicode_class(&mut self, position: usize) -> Result<UnicodeClass, RegexSyntaxError> {
+        let name = match self.chars.next() {
+            Some((_, '{')) => {
+                let mut name = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some((_, '}')) => break name,
+                        Some((_, c)) => name.push(c),
+                        None => return self.error(position, SyntaxErrorKind::Unclosed('{')),
+                    }
+                }
+            }
+            Some((_, c)) => c.to_string(),
+            None => return self.error(self.len - 1, SyntaxErrorKind::TrailingBackslash),
+        };
+        match UnicodeClass::from_name(&name) {
+            Some(class) => Ok(class),
+            None => self.error(position, SyntaxErrorKind::UnknownClass(name)),
+        }
+    }
+
+    /// Character escaped by the `\` at the given position
+    fn escape(&mut self, position: usize) -> Result<char, RegexSyntaxError> {
+        match self.chars.next() {
+            Some((_, 't')) => Ok('\t'),
+            Some((_, 'n')) => Ok('\n'),
+            Some((_, 'r')) => Ok('\r'),
+            Some((_, c)) if c.is_alphanumeric() => {
+                self.error(position, SyntaxErrorKind::UnknownEscape(c))
+            }
+            Some((_, c)) => Ok(c),
+            None => self.error(self.len - 1, SyntaxErrorKind::TrailingBackslash),
+        }
+    }
+
+    /// Pushes the pending characters, if any, as a Literal
+    fn flush(text: &mut String, components: &mut Vec<RegexComponent>) {
+        if !text.is_empty() {
+            components.push(RegexComponent::Literal(std::mem::take(text)));
+        }
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::unicode_tables::{GeneralCategory, Script};
+
+    fn literal(text: &str) -> RegexComponent {
+        RegexComponent::Literal(text.to_string())
+    }
+
+    fn regex(components: Vec<RegexComponent>) -> Regex {
+        Regex::new(components)
+    }
+
+    #[test]
+    fn test_syntax_literals_and_quantifiers() {
+        assert_eq!(
+            parse("b a* c").unwrap(),
+            regex(vec![
+                literal("b"),
+                RegexComponent::ZeroOrMore(regex(vec![literal("a")])),
+                literal("c"),
+            ])
+        );
+        assert_eq!(
+            parse("if ab+c?").unwrap(),
+            regex(vec![
+                literal("if"),
+                literal("a"),
+                RegexComponent::OneOrMore(regex(vec![literal("b")])),
+                RegexComponent::ZeroOrOne(regex(vec![literal("c")])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_syntax_repetitions() {
+        let repeat = |regex, min, max| RegexComponent::Repeat { regex, min, max, greediness: Greediness::Greedy };
+        assert_eq!(
+            parse("ab{2} (cd){1,3} [0-9]{4,}").unwrap(),
+            regex(vec![
+                literal("a"),
+                repeat(regex(vec![literal("b")]), 2, Some(2)),
+                repeat(regex(vec![RegexComponent::SubRegex(regex(vec![literal("cd")]))]), 1, Some(3)),
+                repeat(regex(vec![RegexComponent::Class(CharClass::range('0', '9'))]), 4, None),
+            ])
+        );
+        assert_eq!(parse("\\\\u\\{ [0-9a-f]{4} \\}").unwrap().to_string(), "\\\\u\\{ [0-9a-f]{4} \\}");
+        assert_eq!(parse("(a b){0,2}").unwrap().to_string(), "(a b){0,2}");
+        assert_eq!(parse("(?:a b){0,2}").unwrap().to_string(), "(?:a b){0,2}");
+
+        let error = |pattern: &str| parse(pattern).unwrap_err();
+        assert_eq!(
+            error("a{3,1}"),
+            RegexSyntaxError { position: 1, kind: SyntaxErrorKind::InvalidRepetition("3,1".to_string()) }
+        );
+        assert_eq!(error("a{,2}").to_string(), "invalid repetition `{,2}` at position 1");
+        assert_eq!(error("a{x}").to_string(), "invalid repetition `{x}` at position 1");
+        assert_eq!(error("{2}").to_string(), "nothing to repeat before `{` at position 0");
+        assert_eq!(error("a{2").to_string(), "unclosed `{` at position 1");
+        assert_eq!(error("a}").to_string(), "unexpected `}` at position 1");
+    }
+
+    #[test]
+    fn test_syntax_lazy_and_possessive() {
+        let repeat = |min, max, greediness| RegexComponent::Repeat {
+            regex: regex(vec![literal("a")]),
+            min,
+            max,
+            greediness,
+        };
+        assert_eq!(
+            parse("a*? a+? a?? a{2,}?").unwrap(),
+            regex(vec![
+                repeat(0, None, Greediness::Lazy),
+                repeat(1, None, Greediness::Lazy),
+                repeat(0, Some(1), Greediness::Lazy),
+                repeat(2, None, Greediness::Lazy),
+            ])
+        );
+        assert_eq!(
+            parse("a*+ a++ a?+ a{2}+").unwrap(),
+            regex(vec![
+                repeat(0, None, Greediness::Possessive),
+                repeat(1, None, Greediness::Possessive),
+                repeat(0, Some(1), Greediness::Possessive),
+                repeat(2, Some(2), Greediness::Possessive),
+            ])
+        );
+        for pattern in ["a*? b++ c?? d{2,3}?", "(a b)*+ [0-9]{4}+", "a{0,} a{1,} a{0,1}"] {
+            assert_eq!(parse(pattern).unwrap().to_string(), pattern);
+        }
+        assert_eq!(parse("a{0,}? a{1,}?").unwrap().to_string(), "a*? a+?");
+        assert_eq!(parse("a*??").unwrap_err().kind, SyntaxErrorKind::NothingToRepeat('?'));
+    }
+
+    #[test]
+    fn test_syntax_groups_and_alternation() {
+        assert_eq!(
+            parse("a|b c").unwrap(),
+            regex(vec![RegexComponent::Or(
+                regex(vec![literal("a")]),
+                regex(vec![literal("b"), literal("c")]),
+            )])
+        );
+        assert_eq!(
+            parse("(a|b)* (c d)").unwrap(),
+            regex(vec![
+                RegexComponent::ZeroOrMore(regex(vec![RegexComponent::SubRegex(regex(vec![RegexComponent::Or(
+                    regex(vec![literal("a")]),
+                    regex(vec![literal("b")]),
+                )]))])),
+                RegexComponent::SubRegex(regex(vec![literal("c"), literal("d")])),
+            ])
+        );
+        // Only a group that does not capture is a bare alternation
+        assert_eq!(parse("(a|b|c)").unwrap().to_string(), "(a|b|c)");
+        assert_eq!(parse("(?:a|b) c").unwrap().to_string(), "(?:a|b) c");
+        let alternation = parse("(?:a|b)* (a|b)+ (?<c>c|d)").unwrap();
+        assert_eq!(parse(&alternation.to_string()).unwrap(), alternation);
+    }
+
+    #[test]
+    fn test_syntax_classes_and_escapes() {
+        assert_eq!(
+            parse("[a-c]").unwrap(),
+            regex(vec![RegexComponent::Class(CharClass::range('a', 'c'))])
+        );
+        assert_eq!(
+            parse("[-]").unwrap(),
+            regex(vec![RegexComponent::Class(CharClass::set(&['-']))])
+        );
+        assert_eq!(
+            parse("ab *").unwrap(),
+            regex(vec![literal("a"), RegexComponent::ZeroOrMore(regex(vec![literal("b")]))])
+        );
+        assert_eq!(
+            parse("a\\ \\*\\tb").unwrap(),
+            regex(vec![literal("a *\tb")])
+        );
+    }
+
+    #[test]
+    fn test_syntax_negated_and_predefined_classes() {
+        assert_eq!(
+            parse("[^a-z_]+").unwrap(),
+            regex(vec![RegexComponent::OneOrMore(regex(vec![RegexComponent::Class(
+                CharClass::new(vec![('a', 'z'), ('_', '_')]).negated()
+            )]))])
+        );
+        assert_eq!(
+            parse("x\\d+").unwrap(),
+            regex(vec![
+                literal("x"),
+                RegexComponent::OneOrMore(regex(vec![RegexComponent::Class(CharClass::digits())])),
+            ])
+        );
+        assert_eq!(
+            parse("[\\da-f]").unwrap(),
+            regex(vec![RegexComponent::Class(CharClass::digits().union(&CharClass::range('a', 'f')))])
+        );
+        assert_eq!(parse("\\S").unwrap(), regex(vec![RegexComponent::Class(CharClass::whitespace().negated())]));
+        // A `^` that does not start the class is a character of the class
+        assert_eq!(parse("[a^]").unwrap().to_string(), "[\\^a]");
+        assert_eq!(parse("[^\\]]").unwrap().to_string(), "[^\\]]");
+        assert_eq!(parse("[a-z]*").unwrap().to_string(), "[a-z]*");
+    }
+
+    #[test]
+    fn test_syntax_unicode_classes() {
+        let letter = || RegexComponent::Class(CharClass::unicode(UnicodeClass::Alphabetic));
+        assert_eq!(
+            parse("\\p{Alpha} [\\p{Alphabetic}\\pN_]*").unwrap(),
+            regex(vec![
+                letter(),
+                RegexComponent::ZeroOrMore(regex(vec![RegexComponent::Class(
+                    CharClass::unicode(UnicodeClass::Alphabetic)
+                        .union(&CharClass::unicode(UnicodeClass::Category(GeneralCategory::N)))
+                        .union(&CharClass::set(&['_']))
+                )])),
+            ])
+        );
+        assert_eq!(parse("\\P{Alpha}+").unwrap().to_string(), "\\P{Alphabetic}+");
+        assert_eq!(parse("\\p{Grek}").unwrap().to_string(), "\\p{Greek}");
+        assert_eq!(parse("\\pL \\p{Letter} \\P{Nd}").unwrap().to_string(), "\\p{L} \\p{L} \\P{Nd}");
+        assert_eq!(
+            parse("\\p{Greek}+").unwrap(),
+            regex(vec![RegexComponent::OneOrMore(regex(vec![RegexComponent::Class(CharClass::unicode(
+                UnicodeClass::Script(Script::Greek)
+            ))]))])
+        );
+        assert_eq!(
+            parse("\\p{InGreek}").unwrap_err(),
+            RegexSyntaxError { position: 0, kind: SyntaxErrorKind::UnknownClass("InGreek".to_string()) }
+        );
+        assert_eq!(
+            parse("a \\p{Klingon}").unwrap_err(),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::UnknownClass("Klingon".to_string()) }
+        );
+        assert_eq!(parse("\\p{N").unwrap_err().to_string(), "unclosed `{` at position 0");
+    }
+
+    #[test]
+    fn test_syntax_assertions() {
+        let assertion = RegexComponent::Assertion;
+        assert_eq!(
+            parse("^\\bif\\b x$").unwrap(),
+            regex(vec![
+                assertion(Assertion::Start),
+                assertion(Assertion::WordBoundary),
+                literal("if"),
+                assertion(Assertion::WordBoundary),
+                literal("x"),
+                assertion(Assertion::End),
+            ])
+        );
+        assert_eq!(
+            parse("\\A a\\B\\z").unwrap(),
+            regex(vec![
+                assertion(Assertion::StartOfInput),
+                literal("a"),
+                assertion(Assertion::NotWordBoundary),
+                assertion(Assertion::EndOfInput),
+            ])
+        );
+        for pattern in ["^ a* $", "\\b [a-z]+ \\b", "(a|\\B)", "\\A a \\z"] {
+            assert_eq!(parse(pattern).unwrap().to_string(), pattern);
+        }
+        assert_eq!(parse("a ^*").unwrap_err().kind, SyntaxErrorKind::NothingToRepeat('*'));
+        assert_eq!(parse("\\b+").unwrap_err().kind, SyntaxErrorKind::NothingToRepeat('+'));
+    }
+
+    #[test]
+    fn test_syntax_named_groups() {
+        let class = |class| regex(vec![RegexComponent::Class(class)]);
+        let letters = regex(vec![RegexComponent::OneOrMore(class(CharClass::range('a', 'z')))]);
+        let digits = class(CharClass::digits()) + regex(vec![RegexComponent::ZeroOrOne(regex(vec![literal(",")]))]);
+        assert_eq!(
+            parse("(?<key>[a-z]+) = (?P<value>\\d ,?)* (x)").unwrap(),
+            regex(vec![
+                RegexComponent::NamedSubRegex("key".to_string(), letters),
+                literal("="),
+                RegexComponent::ZeroOrMore(regex(vec![RegexComponent::NamedSubRegex("value".to_string(), digits)])),
+                RegexComponent::SubRegex(regex(vec![literal("x")])),
+            ])
+        );
+        let names = parse("(?<key>[a-z]+) = (?P<value>\\d ,?)* (x)").unwrap();
+        assert_eq!(names.group_names(), vec![None, Some("key"), Some("value"), None]);
+        for pattern in ["(?<a>x y)*", "(?<a>x|y) (b)", "(?<a>(?<b>x) y)+?", "(?<a>(x|y) z)"] {
+            assert_eq!(parse(pattern).unwrap().to_string(), pattern);
+        }
+
+        let error = |pattern: &str| parse(pattern).unwrap_err();
+        assert_eq!(
+            error("a (?<1a>x)"),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::InvalidGroupName("1a".to_string()) }
+        );
+        assert_eq!(error("(?<>x)").kind, SyntaxErrorKind::InvalidGroupName(String::new()));
+        assert_eq!(error("(?<a>x) (?<a>y)").to_string(), "duplicate group name `a` at position 8");
+        assert_eq!(error("(?~a)"), RegexSyntaxError { position: 1, kind: SyntaxErrorKind::Unsupported('?') });
+        assert_eq!(error("(?<a"), RegexSyntaxError { position: 0, kind: SyntaxErrorKind::Unclosed('(') });
+    }
+
+    #[test]
+    fn test_syntax_lookarounds() {
+        assert_eq!(
+            parse("-(?![0-9])").unwrap(),
+            regex(vec![
+                literal("-"),
+                RegexComponent::Lookahead {
+                    regex: regex(vec![RegexComponent::Class(CharClass::range('0', '9'))]),
+                    negated: true,
+                },
+            ])
+        );
+        assert_eq!(
+            parse("(?<=a|bc) x").unwrap().components[0],
+            RegexComponent::Lookbehind {
+                regex: regex(vec![RegexComponent::Or(regex(vec![literal("a")]), regex(vec![literal("bc")]))]),
+                negated: false,
+            }
+        );
+        for pattern in ["(?=a b) (?!c)", "(?<=a{2,3} [a-z]?) (?<!\\b)", "(?<=(?<x>a))"] {
+            assert_eq!(parse(pattern).unwrap().to_string(), pattern);
+        }
+        assert_eq!(parse("(?=(a) (?<b>b))").unwrap().group_names(), vec![None, None, Some("b")]);
+
+        assert_eq!(
+            parse("a (?<=a b*)").unwrap_err(),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::UnboundedLookbehind }
+        );
+        assert_eq!(parse("(?<!a{2,})").unwrap_err().to_string(), "lookbehind of unbounded length at position 0");
+        assert_eq!(parse("(?<=(a*)?)").unwrap_err().kind, SyntaxErrorKind::UnboundedLookbehind);
+        // A loop over nothing does not make the lookbehind unbounded
+        assert!(parse("(?<=a (\\b)*)").is_ok());
+        assert_eq!(parse("(?=a)*").unwrap_err().kind, SyntaxErrorKind::NothingToRepeat('*'));
+    }
+
+    #[test]
+    fn test_syntax_flags() {
+        let insensitive = Flags::new().with_case_insensitive(true);
+        assert_eq!(
+            parse("(?i:select) x (?s-i:.)*").unwrap(),
+            regex(vec![
+                RegexComponent::Flagged(insensitive, regex(vec![literal("select")])),
+                literal("x"),
+                RegexComponent::ZeroOrMore(regex(vec![RegexComponent::Flagged(
+                    Flags::new().with_dot_matches_newline(true).with_case_insensitive(false),
+                    regex(vec![RegexComponent::Dot]),
+                )])),
+            ])
+        );
+        // The flags of `(?i)` apply to the rest of the group, including its next alternatives
+        assert_eq!(
+            parse("a (?i) b|c").unwrap(),
+            regex(vec![RegexComponent::Or(
+                regex(vec![literal("a"), RegexComponent::Flagged(insensitive, regex(vec![literal("b")]))]),
+                regex(vec![RegexComponent::Flagged(insensitive, regex(vec![literal("c")]))]),
+            )])
+        );
+        assert_eq!(parse("(?i) a|b").unwrap(), parse("(?i:a)|(?i:b)").unwrap());
+        assert_eq!(parse("(a (?m) b) c|d").unwrap(), parse("(a (?m:b)) c|d").unwrap());
+        assert_eq!(parse("(?m)^").unwrap().to_string(), "(?m:^)");
+        // A group without flags does not capture
+        assert_eq!(parse("(?:a b)+ (c)").unwrap().group_names(), vec![None, None]);
+        for pattern in ["(?i:a b)*", "(?-i:a|b)", "(?im-s:. $)", "(?:a)+?", ". .?", "(?m:(?i:a) ^)"] {
+            assert_eq!(parse(pattern).unwrap().to_string(), pattern);
+        }
+
+        let error = |pattern: &str| parse(pattern).unwrap_err();
+        assert_eq!(error("a (?ix:b)"), RegexSyntaxError { position: 5, kind: SyntaxErrorKind::UnknownFlag('x') });
+        assert_eq!(error("(?i-s-m)").to_string(), "unknown flag `-` at position 5");
+        assert_eq!(error("(?i"), RegexSyntaxError { position: 0, kind: SyntaxErrorKind::Unclosed('(') });
+        assert_eq!(error("(?i)*").kind, SyntaxErrorKind::NothingToRepeat('*'));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let error = |pattern: &str| parse(pattern).unwrap_err();
+        assert_eq!(
+            error("ab)"),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::UnexpectedChar(')') }
+        );
+        assert_eq!(
+            error("a (b"),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::Unclosed('(') }
+        );
+        assert_eq!(
+            error("a **"),
+            RegexSyntaxError { position: 3, kind: SyntaxErrorKind::NothingToRepeat('*') }
+        );
+        assert_eq!(
+            error("[z-a]"),
+            RegexSyntaxError { position: 1, kind: SyntaxErrorKind::InvalidRange('z', 'a') }
+        );
+        assert_eq!(
+            error("ab\\q"),
+            RegexSyntaxError { position: 2, kind: SyntaxErrorKind::UnknownEscape('q') }
+        );
+        assert_eq!(error("a\\").to_string(), "trailing `\\` at position 1");
+        assert_eq!(error("[ab").to_string(), "unclosed `[` at position 0");
+        assert_eq!(error("a []").to_string(), "empty character class at position 2");
+    }
+}
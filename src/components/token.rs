@@ -0,0 +1,150 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::components::items::RegexComponent;
+
+/// Token:
+/// Piece of the input recognised by a parser: the RegexComponent that matched it, the matched text,
+/// and the byte range of the match in the input string given to the parser.
+///
+/// ```rust
+/// use pilator::{
+///     components::{items::RegexComponent, regex::Regex, token::Token},
+///     parser::{naive_parser::NaiveParser, Parser},
+/// };
+///
+/// let mut parser = NaiveParser::new();
+/// parser.add_regex(Regex::new(vec![
+///     RegexComponent::Keyword("let".to_string()),
+///     RegexComponent::Literal(" ".to_string()),
+///     RegexComponent::Identifier("x".to_string()),
+/// ]));
+/// let tokens = parser.tokenize("let x", None).unwrap();
+/// assert_eq!(tokens[2], Token::new(RegexComponent::Identifier("x".to_string()), "x", 4..5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub kind: RegexComponent,
+    pub text: String,
+    pub span: Range<usize>,
+}
+
+/// Implemented methods:
+/// - new: Creates a new Token
+/// - start: Returns the byte offset where the token starts
+/// - end: Returns the byte offset right after the end of the token
+/// - len: Returns the length of the token in bytes
+/// - is_empty: Returns true if the token matched no text
+impl Token {
+    pub fn new(kind: RegexComponent, text: impl Into<String>, span: Range<usize>) -> Token {
+        Token {
+            kind,
+            text: text.into(),
+            span,
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.span.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}..{}", self.text, self.span.start, self.span.end)
+    }
+}
+
+/// TokenSlice:
+/// Borrowed form of Token, returned by Parser::tokenize_slices: the matched text is a slice of the
+/// input string and the kind a reference to the RegexComponent of the parser, so that no part of
+/// the input is copied while matching.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenSlice<'a> {
+    pub kind: &'a RegexComponent,
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+/// Implemented methods:
+/// - new: Creates a new TokenSlice
+/// - start: Returns the byte offset where the token starts
+/// - end: Returns the byte offset right after the end of the token
+/// - len: Returns the length of the token in bytes
+/// - is_empty: Returns true if the token matched no text
+/// - to_token: Returns the owned Token, copying the text and the kind
+impl<'a> TokenSlice<'a> {
+    pub fn new(kind: &'a RegexComponent, text: &'a str, span: Range<usize>) -> TokenSlice<'a> {
+        TokenSlice { kind, text, span }
+    }
+
+    pub fn start(&self) -> usize {
+        self.span.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.span.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.span.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
+
+    pub fn to_token(&self) -> Token {
+        Token::new(self.kind.clone(), self.text, self.span.clone())
+    }
+}
+
+impl From<TokenSlice<'_>> for Token {
+    fn from(token: TokenSlice<'_>) -> Token {
+        token.to_token()
+    }
+}
+
+impl fmt::Display for TokenSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}..{}", self.text, self.span.start, self.span.end)
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_token() {
+        let token = Token::new(RegexComponent::Operator("+=".to_string()), "+=", 3..5);
+        assert_eq!(token.start(), 3);
+        assert_eq!(token.end(), 5);
+        assert_eq!(token.len(), 2);
+        assert!(!token.is_empty());
+        assert_eq!(token.to_string(), "\"+=\" at 3..5");
+    }
+
+    #[test]
+    fn test_token_slice() {
+        let input = String::from("a += 1");
+        let kind = RegexComponent::Operator("+=".to_string());
+        let slice = TokenSlice::new(&kind, &input[2..4], 2..4);
+        assert_eq!(slice.len(), 2);
+        assert_eq!(slice.to_string(), "\"+=\" at 2..4");
+        assert_eq!(Token::from(slice), Token::new(kind.clone(), "+=", 2..4));
+    }
+}
@@ -18,4 +18,6 @@ pub const ALPHANUMS: [char; 62] = [
 pub const WHITE: char = ' ';
 pub const NEWLINE: char = '\n';
 pub const TAB: char = '\t';
-pub const BACKSPACE: char = '\x08';
\ No newline at end of file
+pub const BACKSPACE: char = '\x08';
+pub const CARRIAGE_RETURN: char = '\r';
+pub const UNDERSCORE: char = '_';
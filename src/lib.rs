@@ -1,3 +1,4 @@
 pub mod components;
 pub mod parser;
-pub mod constants;
\ No newline at end of file
+pub mod constants;
+pub mod automata;
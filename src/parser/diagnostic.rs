@@ -0,0 +1,422 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::parser::error::{char_boundary, ParseError, Position};
+
+/// Label:
+/// Span of the input (byte range) pointed at by a diagnostic, with a message. Primary labels are
+/// underlined with `^`, secondary ones with `-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+    pub primary: bool,
+}
+
+/// Diagnostic:
+/// rustc-style report of a failure: a message, the source lines covered by its labels with the
+/// labelled spans underlined, and some notes. Labels can span multiple lines.
+///
+/// ```rust
+/// use pilator::{
+///     components::{items::RegexComponent, regex::Regex},
+///     parser::{diagnostic::Diagnostic, naive_parser::NaiveParser, Parser},
+/// };
+///
+/// let parser = NaiveParser::with_regexes(vec![Regex::new(vec![
+///     RegexComponent::Literal("a".to_string()),
+///     RegexComponent::Literal("(".to_string()),
+/// ])]);
+/// let input = "ab";
+/// let error = parser.parse(input, None).unwrap_err();
+/// assert_eq!(
+///     Diagnostic::from_error(&error, input).render(input),
+///     "error: unexpected `b`\n --> 1:2\n  |\n1 | ab\n  |  ^ expected one of: `(`\n  = note: while matching regex 0 `a \\(`\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    message: String,
+    origin: Option<String>,
+    labels: Vec<Label>,
+    notes: Vec<String>,
+}
+
+/// Implemented methods:
+/// - new: Creates a diagnostic with the given message and no label
+/// - from_error: Creates the diagnostic of a ParseError raised while parsing the given input
+/// - with_origin: Sets the name of the input, such as a file name, shown next to the location
+/// - with_label: Adds a primary label
+/// - with_secondary_label: Adds a secondary label
+/// - with_note: Adds a note shown after the source lines
+/// - render: Renders the diagnostic as plain text
+/// - render_colored: Renders the diagnostic as text colored with ANSI escape codes
+impl Diagnostic {
+    pub fn new(message: &str) -> Diagnostic {
+        Diagnostic {
+            message: message.to_string(),
+            origin: None,
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn from_error(error: &ParseError, input: &str) -> Diagnostic {
+        match error {
+            ParseError::Mismatch {
+                position,
+                furthest,
+                found,
+                expected,
+                context,
+            } => {
+                let message = match found {
+                    Some(found) => format!("unexpected `{}`", found),
+                    None => "unexpected end of input".to_string(),
+                };
+                let length = found.as_ref().map_or(0, |found| found.len());
+                let label = if expected.is_empty() {
+                    String::new()
+                } else {
+                    format!("expected one of: {}", expected_list(expected))
+                };
+                let mut diagnostic =
+                    Diagnostic::new(&message).with_label(position.offset..position.offset + length, &label);
+                if furthest.offset > position.offset {
+                    diagnostic = diagnostic
+                        .with_secondary_label(furthest.offset..furthest.offset, "furthest point reached");
+                }
+                if let Some(context) = context {
+                    diagnostic = diagnostic.with_note(&format!("while matching {}", context));
+                }
+                diagnostic
+            }
+            ParseError::UnknownToken { position } => {
+                let length = input
+                    .get(position.offset..)
+                    .and_then(|rest| rest.chars().next())
+                    .map_or(0, |c| c.len_utf8());
+                Diagnostic::new("unknown token").with_label(
+                    position.offset..position.offset + length,
+                    "no terminal starts with this character",
+                )
+            }
+            ParseError::InvalidGrammar(error) => Diagnostic::new(&format!("invalid grammar: {}", error)),
+            ParseError::BudgetExhausted { position, budget } => {
+                Diagnostic::new(&format!("backtracking budget of {} steps exhausted", budget))
+                    .with_label(position.offset..position.offset, "last step taken here")
+                    .with_note("the search may need exponential time on this input: raise the budget or use another engine")
+            }
+            ParseError::Unsupported { index, error } => {
+                Diagnostic::new(&format!("regex {} can not be compiled: {}", index, error))
+                    .with_note("only Engine::Backtrack can parse with this regex")
+            }
+        }
+    }
+
+    pub fn with_origin(mut self, origin: &str) -> Diagnostic {
+        self.origin = Some(origin.to_string());
+        self
+    }
+
+    pub fn with_label(mut self, span: Range<usize>, message: &str) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+            primary: true,
+        });
+        self
+    }
+
+    pub fn with_secondary_label(mut self, span: Range<usize>, message: &str) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+            primary: false,
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn render(&self, source: &str) -> String {
+        self.render_with(source, false)
+    }
+
+    pub fn render_colored(&self, source: &str) -> String {
+        self.render_with(source, true)
+    }
+
+    fn render_with(&self, source: &str, colored: bool) -> String {
+        let style = Style { colored };
+        let mut out = String::new();
+        let _ = writeln!(out, "{}{}", style.error("error"), style.bold(&format!(": {}", self.message)));
+
+        let lines = source_lines(source);
+        // Labels with their first and last line index and the columns (in characters) of their ends
+        let mut spans: Vec<(&Label, usize, usize, usize, usize)> = self
+            .labels
+            .iter()
+            .map(|label| {
+                let (start_line, start_column) = locate(&lines, source, label.span.start);
+                let (end_line, end_column) = locate(&lines, source, label.span.end.max(label.span.start));
+                (label, start_line, start_column, end_line, end_column)
+            })
+            .collect();
+        spans.sort_by_key(|&(label, start_line, start_column, _, _)| (start_line, start_column, !label.primary));
+
+        let width = spans
+            .iter()
+            .map(|span| (span.3 + 1).to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = |line: Option<usize>| -> String {
+            let number = line.map_or(String::new(), |line| (line + 1).to_string());
+            style.gutter(&format!("{:>width$} |", number, width = width))
+        };
+
+        if let Some(&(label, ..)) = spans.iter().find(|span| span.0.primary).or(spans.first()) {
+            let position = Position::new(source, label.span.start.min(source.len()));
+            let origin = self.origin.as_ref().map_or(String::new(), |origin| format!("{}:", origin));
+            let _ = writeln!(
+                out,
+                "{}{} {}{}:{}",
+                " ".repeat(width),
+                style.gutter("-->"),
+                origin,
+                position.line,
+                position.column
+            );
+        }
+        if !spans.is_empty() {
+            let _ = writeln!(out, "{}", gutter(None));
+        }
+
+        // Lines to show, and whether a multi-line label needs a margin on the left
+        let mut shown: Vec<usize> = vec![];
+        for &(_, start_line, _, end_line, _) in spans.iter() {
+            shown.push(start_line);
+            shown.push(end_line);
+            if end_line > start_line {
+                shown.extend(start_line + 1..end_line);
+            }
+        }
+        shown.sort();
+        shown.dedup();
+        let margin = spans.iter().any(|span| span.3 > span.1);
+
+        let mut previous: Option<usize> = None;
+        for &line in shown.iter() {
+            if previous.is_some_and(|previous| line > previous + 1) {
+                let _ = writeln!(out, "{}", style.gutter("..."));
+            }
+            previous = Some(line);
+            let inside = spans
+                .iter()
+                .find(|span| span.3 > span.1 && span.1 < line && line <= span.3);
+            let prefix = match (margin, inside) {
+                (false, _) => String::new(),
+                (true, Some(span)) => format!("{} ", style.marker("|", span.0.primary)),
+                (true, None) => "  ".to_string(),
+            };
+            let _ = writeln!(out, "{} {}{}", gutter(Some(line)), prefix, lines[line].1);
+
+            for &(label, start_line, start_column, end_line, end_column) in spans.iter() {
+                let marker = if label.primary { '^' } else { '-' };
+                let message = if label.message.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", label.message)
+                };
+                if start_line == end_line && start_line == line {
+                    let length = (end_column - start_column).max(1);
+                    let underline: String = std::iter::repeat_n(marker, length).collect();
+                    let indent = " ".repeat(start_column + if margin { 2 } else { 0 });
+                    let _ = writeln!(
+                        out,
+                        "{} {}{}",
+                        gutter(None),
+                        indent,
+                        style.marker(&format!("{}{}", underline, message), label.primary)
+                    );
+                } else if start_line != end_line && start_line == line {
+                    let underline = format!(" {}{}", "_".repeat(start_column + 1), marker);
+                    let _ = writeln!(out, "{} {}", gutter(None), style.marker(&underline, label.primary));
+                } else if start_line != end_line && end_line == line {
+                    let underline = format!("|{}{}{}", "_".repeat(end_column.max(1)), marker, message);
+                    let _ = writeln!(out, "{} {}", gutter(None), style.marker(&underline, label.primary));
+                }
+            }
+        }
+
+        for note in self.notes.iter() {
+            let _ = writeln!(out, "{} {} {}", " ".repeat(width), style.gutter("="), style.bold(&format!("note: {}", note)));
+        }
+        out
+    }
+}
+
+/// Quoted terminal names such as `"a"` are shown as `` `a` ``, other names are kept as they are
+fn expected_list(expected: &[String]) -> String {
+    expected
+        .iter()
+        .map(|name| match name.strip_prefix('"').and_then(|name| name.strip_suffix('"')) {
+            Some(text) => format!("`{}`", text),
+            None => name.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Byte offset where every line starts, and its text without the line terminator
+fn source_lines(source: &str) -> Vec<(usize, &str)> {
+    let mut lines = vec![];
+    let mut start = 0;
+    for line in source.split('\n') {
+        lines.push((start, line.strip_suffix('\r').unwrap_or(line)));
+        start += line.len() + 1;
+    }
+    lines
+}
+
+/// Line index and column (in characters) of a byte offset
+fn locate(lines: &[(usize, &str)], source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let line = lines
+        .iter()
+        .rposition(|(start, _)| *start <= offset)
+        .unwrap_or(0);
+    let (start, text) = lines[line];
+    (line, text[..char_boundary(text, offset - start)].chars().count())
+}
+
+/// ANSI styling, doing nothing when colors are disabled
+struct Style {
+    colored: bool,
+}
+
+impl Style {
+    fn paint(&self, text: &str, code: &str) -> String {
+        if self.colored {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn error(&self, text: &str) -> String {
+        self.paint(text, "1;31")
+    }
+
+    fn bold(&self, text: &str) -> String {
+        self.paint(text, "1")
+    }
+
+    fn gutter(&self, text: &str) -> String {
+        self.paint(text, "1;34")
+    }
+
+    fn marker(&self, text: &str, primary: bool) -> String {
+        if primary {
+            self.paint(text, "1;31")
+        } else {
+            self.paint(text, "1;34")
+        }
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::grammar::{Grammar, Symbol};
+    use crate::components::items::RegexComponent;
+    use crate::parser::{llparsers::LLParser, naive_parser::NaiveParser, Parser};
+
+    #[test]
+    fn test_diagnostic_table_parser_error() {
+        // s -> "(" s ")" | "id"
+        let mut grammar = Grammar::new("s");
+        grammar.add_production(
+            "s",
+            vec![
+                Symbol::Terminal(RegexComponent::Literal("(".to_string())),
+                Symbol::NonTerminal("s".to_string()),
+                Symbol::Terminal(RegexComponent::Literal(")".to_string())),
+            ],
+        );
+        grammar.add_production("s", vec![Symbol::Terminal(RegexComponent::Identifier("id".to_string()))]);
+        let parser = LLParser::new(&grammar).unwrap();
+
+        let input = "(\n  (id\n  id)";
+        let error = parser.parse(input, None).unwrap_err();
+        assert_eq!(
+            Diagnostic::from_error(&error, input).with_origin("input.txt").render(input),
+            "error: unexpected `id`\n \
+             --> input.txt:3:3\n  \
+               |\n\
+             3 |   id)\n  \
+               |   ^^ expected one of: `)`\n  \
+               = note: while matching production `s -> \"(\" s \")\"`\n"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_furthest_point() {
+        // The keyword is reported where it starts, and was matched up to the `x`
+        let parser = NaiveParser::with_regexes(vec!["select \\* from".parse().unwrap()]);
+        let input = "select*frxm";
+        let error = parser.parse(input, None).unwrap_err();
+        assert_eq!(
+            Diagnostic::from_error(&error, input).render(input),
+            "error: unexpected `f`\n \
+             --> 1:8\n  \
+               |\n\
+             1 | select*frxm\n  \
+               |        ^ expected one of: `from`\n  \
+               |          - furthest point reached\n  \
+               = note: while matching regex 0 `select \\* from`\n"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_multiline_and_secondary_labels() {
+        let source = "let x = (1 +\n  2\n  3;";
+        let diagnostic = Diagnostic::new("unclosed parenthesis")
+            .with_label(8..source.len(), "this group")
+            .with_secondary_label(4..5, "bound here");
+        assert_eq!(
+            diagnostic.render(source),
+            "error: unclosed parenthesis\n \
+             --> 1:9\n  \
+               |\n\
+             1 |   let x = (1 +\n  \
+               |       - bound here\n  \
+               |  _________^\n\
+             2 | |   2\n\
+             3 | |   3;\n  \
+               | |____^ this group\n"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_inside_character() {
+        // Offsets inside a multi-byte character point at the character
+        let source = "x = ün";
+        let rendered = Diagnostic::new("oops").with_label(5..6, "here").render(source);
+        assert!(rendered.contains(" --> 1:5\n"), "{}", rendered);
+        let error = ParseError::UnknownToken { position: Position::new(source, 5) };
+        assert!(Diagnostic::from_error(&error, source).render(source).contains("1:5"));
+    }
+
+    #[test]
+    fn test_diagnostic_colored() {
+        let diagnostic = Diagnostic::new("oops").with_label(0..1, "here");
+        let rendered = diagnostic.render_colored("a");
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: oops\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;31m^ here\x1b[0m"));
+    }
+}
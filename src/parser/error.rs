@@ -0,0 +1,196 @@
+use std::fmt;
+
+use crate::automata::nfa::CompileError;
+use crate::components::grammar::{GrammarError, Production};
+use crate::components::regex::Regex;
+
+/// Position:
+/// Location in the input string: byte offset, and 1-based line and column (counted in characters).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Position of the given byte offset of the input. An offset inside a character counts as the
+    /// start of that character for the line and column.
+    pub fn new(input: &str, offset: usize) -> Position {
+        let before = &input[..char_boundary(input, offset)];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// Greatest offset not after the given one that is at a character boundary of the input
+pub(crate) fn char_boundary(input: &str, offset: usize) -> usize {
+    let mut offset = offset.min(input.len());
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// enum ParseContext
+/// What the parser was trying to match when it failed: one of the registered Regex, with its
+/// index, or a production of the grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseContext {
+    Regex { index: usize, regex: Regex },
+    Production(Production),
+}
+
+impl fmt::Display for ParseContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseContext::Regex { index, regex } => write!(f, "regex {} `{}`", index, regex),
+            ParseContext::Production(production) => write!(f, "production `{}`", production),
+        }
+    }
+}
+
+/// enum ParseError
+/// Error returned by Parser::parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input does not match at `position`, where `found` is the text found there (None at the
+    /// end of input) and `expected` the tokens that could have been accepted. `furthest` is the
+    /// furthest position reached by the parser, which is past `position` when the expected tokens
+    /// were partly matched, or when another alternative went further.
+    Mismatch {
+        position: Position,
+        furthest: Position,
+        found: Option<String>,
+        expected: Vec<String>,
+        context: Option<Box<ParseContext>>,
+    },
+    /// The input contains a character that does not start any terminal of the grammar
+    UnknownToken { position: Position },
+    /// The grammar given to the parser is not well formed
+    InvalidGrammar(GrammarError),
+    /// A backtracking search used all its `budget` of steps, the last one at `position`, before
+    /// finding whether the input matches
+    BudgetExhausted { position: Position, budget: usize },
+    /// The Regex at `index` can't be matched by the engine that was asked for
+    Unsupported { index: usize, error: CompileError },
+}
+
+/// Implemented methods:
+/// - position: Returns the position of the failure, if any
+/// - furthest: Returns the furthest position reached by the parser, if any
+/// - expected: Returns the tokens that could have been accepted at the position of the failure
+/// - context: Returns the Regex or production that was being matched, if known
+impl ParseError {
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            ParseError::Mismatch { position, .. }
+            | ParseError::UnknownToken { position }
+            | ParseError::BudgetExhausted { position, .. } => Some(*position),
+            ParseError::InvalidGrammar(_) | ParseError::Unsupported { .. } => None,
+        }
+    }
+
+    pub fn furthest(&self) -> Option<Position> {
+        match self {
+            ParseError::Mismatch { furthest, .. } => Some(*furthest),
+            _ => self.position(),
+        }
+    }
+
+    pub fn expected(&self) -> &[String] {
+        match self {
+            ParseError::Mismatch { expected, .. } => expected,
+            _ => &[],
+        }
+    }
+
+    pub fn context(&self) -> Option<&ParseContext> {
+        match self {
+            ParseError::Mismatch { context, .. } => context.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Mismatch {
+                position,
+                found,
+                expected,
+                context,
+                ..
+            } => {
+                match found {
+                    Some(found) => write!(f, "unexpected `{}` at {}", found, position)?,
+                    None => write!(f, "unexpected end of input at {}", position)?,
+                }
+                if !expected.is_empty() {
+                    write!(f, ", expected one of: {}", expected.join(", "))?;
+                }
+                if let Some(context) = context {
+                    write!(f, ", while matching {}", context)?;
+                }
+                Ok(())
+            }
+            ParseError::UnknownToken { position } => write!(f, "unknown token at {}", position),
+            ParseError::InvalidGrammar(error) => write!(f, "invalid grammar: {}", error),
+            ParseError::BudgetExhausted { position, budget } => {
+                write!(f, "backtracking budget of {} steps exhausted at {}", budget, position)
+            }
+            ParseError::Unsupported { index, error } => write!(f, "regex {} can not be compiled: {}", index, error),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<GrammarError> for ParseError {
+    fn from(error: GrammarError) -> ParseError {
+        ParseError::InvalidGrammar(error)
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "ab\ncdé\nf";
+        assert_eq!(Position::new(input, 0), Position { offset: 0, line: 1, column: 1 });
+        assert_eq!(Position::new(input, 3), Position { offset: 3, line: 2, column: 1 });
+        assert_eq!(Position::new(input, 7), Position { offset: 7, line: 2, column: 4 });
+        assert_eq!(Position::new(input, 9), Position { offset: 9, line: 3, column: 2 });
+        // Inside the two bytes of `é`
+        assert_eq!(Position::new(input, 6), Position { offset: 6, line: 2, column: 3 });
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let error = ParseError::Mismatch {
+            position: Position::new("a b", 2),
+            furthest: Position::new("a b", 2),
+            found: Some("b".to_string()),
+            expected: vec!["\"a\"".to_string(), "\"(\"".to_string()],
+            context: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "unexpected `b` at line 1, column 3, expected one of: \"a\", \"(\""
+        );
+    }
+}
@@ -0,0 +1,639 @@
+use std::mem;
+use std::sync::OnceLock;
+
+use crate::automata::backtrack;
+use crate::automata::nfa::{CompileError, Nfa};
+use crate::components::class::CharClass;
+use crate::components::grammar::Symbol;
+use crate::components::items::RegexComponent;
+use crate::components::regex::Regex;
+use crate::components::token::TokenSlice;
+use crate::constants::NEWLINE;
+use crate::parser::error::{ParseError, Position};
+use crate::parser::Parser;
+
+/// Rule:
+/// Token rule of a Lexer: the Regex matching the tokens, and the RegexComponent given to them as
+/// their kind. A rule whose kind is a Keyword is a keyword rule, which wins over the other rules
+/// matching a text of the same length (see Lexer).
+/// The tokens of a skipped rule, such as whitespace and comments, are consumed but not emitted.
+/// A rule with a closing delimiter matches nested blocks: its Regex matches the opening delimiter,
+/// and the token extends up to the closing delimiter matching it, so that `/* a /* b */ c */` is a
+/// single block comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub kind: RegexComponent,
+    pub regex: Regex,
+    pub skip: bool,
+    pub close: Option<String>,
+}
+
+/// Implemented methods:
+/// - new: Creates a rule giving the kind to the tokens matched by the Regex
+/// - keyword: Creates the keyword rule matching the word, as a whole word only
+/// - whitespace: Creates the skipped rule matching whitespace, with a Literal(" ") kind
+/// - line_comment: Creates the skipped rule matching the comments from the given start to the end
+///   of the line, the newline excluded, with a Literal kind of the start
+/// - block_comment: Creates the skipped rule matching the nested comments between the given
+///   delimiters, with a Literal kind of the opening delimiter
+/// - with_skip: Returns the rule, whose tokens are skipped or not
+/// - is_keyword: Returns true if the kind of the rule is a Keyword
+impl Rule {
+    pub fn new(kind: RegexComponent, regex: Regex) -> Rule {
+        Rule {
+            kind,
+            regex,
+            skip: false,
+            close: None,
+        }
+    }
+
+    pub fn keyword(word: &str) -> Rule {
+        let kind = RegexComponent::Keyword(word.to_string());
+        Rule::new(kind.clone(), Regex::new(vec![kind]))
+    }
+
+    pub fn whitespace() -> Rule {
+        let whitespace = Regex::new(vec![RegexComponent::Class(CharClass::whitespace())]);
+        Rule::new(RegexComponent::Literal(" ".to_string()), Regex::new(vec![RegexComponent::OneOrMore(whitespace)]))
+            .with_skip(true)
+    }
+
+    pub fn line_comment(start: &str) -> Rule {
+        let kind = RegexComponent::Literal(start.to_string());
+        let rest = RegexComponent::ZeroOrMore(Regex::new(vec![RegexComponent::Dot]));
+        Rule::new(kind.clone(), Regex::new(vec![kind, rest])).with_skip(true)
+    }
+
+    pub fn block_comment(open: &str, close: &str) -> Rule {
+        let kind = RegexComponent::Literal(open.to_string());
+        Rule {
+            close: Some(close.to_string()),
+            ..Rule::new(kind.clone(), Regex::new(vec![kind]))
+        }
+        .with_skip(true)
+    }
+
+    pub fn with_skip(mut self, skip: bool) -> Rule {
+        self.skip = skip;
+        self
+    }
+
+    pub fn is_keyword(&self) -> bool {
+        matches!(self.kind, RegexComponent::Keyword(_))
+    }
+}
+
+/// Lexer:
+/// Splits the input into a stream of tokens, from left to right. At every position, the rule
+/// matching the longest text gives the next token (maximal munch). On ties, keyword rules win over
+/// the other ones, then the rule declared first wins: `if` is a keyword even when an identifier
+/// rule is declared before it, while `iffy` is an identifier.
+/// The text a rule matches is its first match in order of priority, as a backtracking engine would
+/// find it, so that a lazy rule such as `/\* .*? \*/` stops at the first `*/`. The rules are matched
+/// with the LexerEngine of the Lexer: Nfa by default (see automata::nfa), failing with
+/// ParseError::Unsupported on the rules that can't be compiled, such as the ones with lookarounds,
+/// or Backtrack (see automata::backtrack). Every character of the input must belong to a token: a
+/// position where no rule matches a non-empty text fails with ParseError::UnknownToken, and a
+/// nested block that is not closed with ParseError::Mismatch at the end of the input.
+/// Tokens of skipped rules are left out of the token stream, unless they are kept as trivia of the
+/// tokens around them (see tokens_with_trivia).
+///
+/// ```rust
+/// use std::str::FromStr;
+/// use pilator::{
+///     components::{items::RegexComponent, regex::Regex},
+///     parser::{lexer::{Lexer, Rule}, Parser},
+/// };
+///
+/// let identifier = RegexComponent::Identifier("identifier".to_string());
+/// let lexer = Lexer::with_rules(vec![
+///     Rule::new(identifier.clone(), Regex::from_str("[a-z]+").unwrap()),
+///     Rule::keyword("if"),
+///     Rule::new(RegexComponent::Operator("=".to_string()), Regex::from_str("= =?").unwrap()),
+/// ]);
+/// let tokens = lexer.tokenize("if==iffy", None).unwrap();
+/// assert_eq!(tokens[0].kind, RegexComponent::Keyword("if".to_string()));
+/// assert_eq!(tokens[1].text, "==");
+/// assert_eq!(tokens[2].kind, identifier);
+/// assert!(lexer.tokenize("if x", None).is_err());
+/// ```
+pub struct Lexer {
+    /// Rules of the tokens, in declaration order
+    rules: Vec<Rule>,
+    /// Indices of the rules in order of priority, keyword rules first
+    priority: OnceLock<Vec<usize>>,
+    /// Nfa compiled from every rule, in order of priority, or the index of the first rule that can't
+    /// be compiled; compiled the first time it is needed after the rules change
+    nfas: OnceLock<Result<Vec<Nfa>, (usize, CompileError)>>,
+    /// Engine matching the rules when none is given
+    engine: LexerEngine,
+}
+
+/// Implemented methods:
+/// - new: Creates a Lexer without rules
+/// - with_rules: Creates a Lexer with a list of rules
+/// - add_rule: Adds a new rule after the other ones, returning its index
+/// - get_rules: Returns the rules, in declaration order
+/// - set_engine: Sets the LexerEngine used when tokenize is not given one
+/// - get_engine: Returns the LexerEngine used when tokenize is not given one
+/// - next_token: Returns the first token not skipped from the given offset, None if there is none
+/// - tokens: Returns an iterator over the tokens of the input, skipped tokens left out
+/// - tokens_with_trivia: Returns the tokens of the input with the skipped tokens around them as
+///   their trivia, and the trivia at the end of the input
+impl Lexer {
+    pub fn new() -> Lexer {
+        Lexer::with_rules(vec![])
+    }
+
+    pub fn with_rules(rules: Vec<Rule>) -> Lexer {
+        Lexer {
+            rules,
+            priority: OnceLock::new(),
+            nfas: OnceLock::new(),
+            engine: LexerEngine::default(),
+        }
+    }
+
+    pub fn add_rule(&mut self, rule: Rule) -> usize {
+        self.priority = OnceLock::new();
+        self.nfas = OnceLock::new();
+        self.rules.push(rule);
+        self.rules.len() - 1
+    }
+
+    pub fn get_rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn set_engine(&mut self, engine: LexerEngine) {
+        self.engine = engine;
+    }
+
+    pub fn get_engine(&self) -> LexerEngine {
+        self.engine
+    }
+
+    pub fn next_token<'a>(&'a self, input: &'a str, offset: usize) -> Result<Option<TokenSlice<'a>>, ParseError> {
+        self.next_token_with(input, offset, self.engine)
+    }
+
+    pub fn tokens<'a>(&'a self, input: &'a str) -> Tokens<'a> {
+        Tokens {
+            lexer: self,
+            input,
+            engine: self.engine,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// tokens_with_trivia
+    /// Skipped tokens following a token on its line, before any newline, are its trailing trivia,
+    /// while the other ones are leading trivia of the next token. Skipped tokens after the line of
+    /// the last token, or all of them if there is no token, are returned as the trivia at the end.
+    /// A skipped token of whitespace only is split at its first newline, so that the whitespace
+    /// ending the line of a token is its trailing trivia: in `a  \n b`, `"  "` trails `a` and `"\n "`
+    /// leads `b`.
+    pub fn tokens_with_trivia<'a>(
+        &'a self,
+        input: &'a str,
+    ) -> Result<(Vec<TriviaToken<'a>>, Vec<TokenSlice<'a>>), ParseError> {
+        let mut tokens: Vec<TriviaToken<'a>> = vec![];
+        let mut trivia = vec![];
+        // Whether no newline was skipped since the last token
+        let mut same_line = false;
+        let mut offset = 0;
+        while let Some((rule, token)) = self.lex(input, offset, self.engine)? {
+            offset = token.end();
+            if !rule.skip {
+                let leading = mem::take(&mut trivia);
+                tokens.push(TriviaToken { leading, token, trailing: vec![] });
+                same_line = true;
+                continue;
+            }
+            let mut token = token;
+            if let (Some(last), Some(newline)) = (tokens.last_mut(), token.text.find(NEWLINE)) {
+                if same_line && newline > 0 && token.text.trim().is_empty() {
+                    let start = token.span.start;
+                    let split = start + newline;
+                    last.trailing.push(TokenSlice::new(token.kind, &input[start..split], start..split));
+                    token = TokenSlice::new(token.kind, &input[split..token.end()], split..token.end());
+                }
+            }
+            same_line &= !token.text.contains(NEWLINE);
+            match tokens.last_mut() {
+                Some(last) if same_line => last.trailing.push(token),
+                _ => trivia.push(token),
+            }
+        }
+        Ok((tokens, trivia))
+    }
+
+    /// First token not skipped from the given offset, matched with the given engine
+    fn next_token_with<'a>(
+        &'a self,
+        input: &'a str,
+        offset: usize,
+        engine: LexerEngine,
+    ) -> Result<Option<TokenSlice<'a>>, ParseError> {
+        let mut offset = offset;
+        while let Some((rule, token)) = self.lex(input, offset, engine)? {
+            if !rule.skip {
+                return Ok(Some(token));
+            }
+            offset = token.end();
+        }
+        Ok(None)
+    }
+
+    /// Token starting at the given offset, skipped or not, with its rule, None at the end of the
+    /// input: the longest of the matches of the rules, the first one in order of priority on ties
+    fn lex<'a>(
+        &'a self,
+        input: &'a str,
+        offset: usize,
+        engine: LexerEngine,
+    ) -> Result<Option<(&'a Rule, TokenSlice<'a>)>, ParseError> {
+        if offset >= input.len() {
+            return Ok(None);
+        }
+        let mut longest: Option<(usize, usize)> = None;
+        for index in 0..self.rules.len() {
+            if let Some(end) = self.match_at(index, input, offset, engine)? {
+                if end > longest.map_or(offset, |(_, longest)| longest) {
+                    longest = Some((index, end));
+                }
+            }
+        }
+        match longest {
+            Some((index, end)) => {
+                let rule = &self.rules[self.priority()[index]];
+                let end = match &rule.close {
+                    Some(close) => self.block_end(input, index, end, close, engine)?,
+                    None => end,
+                };
+                Ok(Some((rule, TokenSlice::new(&rule.kind, &input[offset..end], offset..end))))
+            }
+            None => Err(ParseError::UnknownToken {
+                position: Position::new(input, offset),
+            }),
+        }
+    }
+
+    /// End of the first match, in order of priority, of the rule at the given index in order of
+    /// priority, starting at the given offset
+    fn match_at(
+        &self,
+        index: usize,
+        input: &str,
+        offset: usize,
+        engine: LexerEngine,
+    ) -> Result<Option<usize>, ParseError> {
+        match engine {
+            LexerEngine::Nfa => Ok(self.nfas()?[index].match_at(input, offset)),
+            LexerEngine::Backtrack { budget } => {
+                let rule = self.priority()[index];
+                let regex = &self.rules[rule].regex;
+                backtrack::supported(regex).map_err(|error| ParseError::Unsupported { index: rule, error })?;
+                backtrack::match_at(regex, input, offset, budget).map_err(|error| ParseError::BudgetExhausted {
+                    position: Position::new(input, error.offset),
+                    budget: error.budget,
+                })
+            }
+        }
+    }
+
+    /// End of the nested block whose opening delimiter, matched by the rule at the given index in
+    /// order of priority, ends at the given offset: the end of the closing delimiter matching it
+    fn block_end(
+        &self,
+        input: &str,
+        index: usize,
+        offset: usize,
+        close: &str,
+        engine: LexerEngine,
+    ) -> Result<usize, ParseError> {
+        let mut offset = offset;
+        let mut depth = 1;
+        while depth > 0 {
+            if input[offset..].starts_with(close) {
+                depth -= 1;
+                offset += close.len();
+            } else if let Some(end) = self.match_at(index, input, offset, engine)?.filter(|&end| end > offset) {
+                depth += 1;
+                offset = end;
+            } else if let Some(ch) = input[offset..].chars().next() {
+                offset += ch.len_utf8();
+            } else {
+                let position = Position::new(input, offset);
+                return Err(ParseError::Mismatch {
+                    position,
+                    furthest: position,
+                    found: None,
+                    expected: vec![Symbol::Terminal(RegexComponent::Literal(close.to_string())).to_string()],
+                    context: None,
+                });
+            }
+        }
+        Ok(offset)
+    }
+
+    /// Indices of the rules in order of priority: keyword rules first, then the other ones, each in
+    /// declaration order
+    fn priority(&self) -> &[usize] {
+        self.priority.get_or_init(|| {
+            let mut priority: Vec<usize> = (0..self.rules.len()).collect();
+            priority.sort_by_key(|&index| !self.rules[index].is_keyword());
+            priority
+        })
+    }
+
+    /// Nfa of every rule, in order of priority, compiled the first time they are needed
+    fn nfas(&self) -> Result<&[Nfa], ParseError> {
+        let nfas = self.nfas.get_or_init(|| {
+            (self.priority().iter())
+                .map(|&index| Nfa::new(&self.rules[index].regex).map_err(|error| (index, error)))
+                .collect()
+        });
+        match nfas {
+            Ok(nfas) => Ok(nfas),
+            Err((index, error)) => Err(ParseError::Unsupported {
+                index: *index,
+                error: error.clone(),
+            }),
+        }
+    }
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// enum LexerEngine
+/// Engine used by a Lexer to match its rules, each giving the end of its first match in order of
+/// priority:
+/// - Nfa: Nfa of every rule, compiled once, matching in linear time; rules that need backtracking,
+///   such as the ones with lookarounds or possessive repetitions, fail with ParseError::Unsupported
+/// - Backtrack: Backtracker of every rule, each search giving up after `budget` steps with
+///   ParseError::BudgetExhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LexerEngine {
+    #[default]
+    Nfa,
+    Backtrack { budget: usize },
+}
+
+/// Tokens:
+/// Iterator over the tokens of an input, returned by Lexer::tokens. It stops after the first
+/// error, which is its last item.
+pub struct Tokens<'a> {
+    lexer: &'a Lexer,
+    input: &'a str,
+    engine: LexerEngine,
+    offset: usize,
+    failed: bool,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Result<TokenSlice<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.lexer.next_token_with(self.input, self.offset, self.engine) {
+            Ok(token) => {
+                let token = token?;
+                self.offset = token.end();
+                Some(Ok(token))
+            }
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// TriviaToken:
+/// Token returned by Lexer::tokens_with_trivia, with the skipped tokens before it and the ones
+/// after it on its line, so that formatters can keep the whitespace and the comments of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriviaToken<'a> {
+    pub leading: Vec<TokenSlice<'a>>,
+    pub token: TokenSlice<'a>,
+    pub trailing: Vec<TokenSlice<'a>>,
+}
+
+/// Parser trait implementation for Lexer, returning the tokens of the whole input matched with the
+/// given LexerEngine, or the one of the Lexer if None
+impl Parser for Lexer {
+    type Config = LexerEngine;
+
+    fn tokenize_slices<'a>(
+        &'a self,
+        input: &'a str,
+        config: Option<Self::Config>,
+    ) -> Result<Vec<TokenSlice<'a>>, ParseError> {
+        let tokens = Tokens {
+            engine: config.unwrap_or(self.engine),
+            ..self.tokens(input)
+        };
+        tokens.collect()
+    }
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn rule(kind: RegexComponent, pattern: &str) -> Rule {
+        Rule::new(kind, Regex::from_str(pattern).unwrap())
+    }
+
+    fn identifier() -> Rule {
+        rule(RegexComponent::Identifier("identifier".to_string()), "[a-z_] \\w*")
+    }
+
+    fn operator(pattern: &str) -> Rule {
+        rule(RegexComponent::Operator(pattern.to_string()), pattern)
+    }
+
+    fn space() -> Rule {
+        rule(RegexComponent::Literal(" ".to_string()), "\\s+")
+    }
+
+    #[test]
+    fn test_lexer_longest_match() {
+        let lexer = Lexer::with_rules(vec![operator("="), operator("= ="), operator("= = ="), space()]);
+        assert_eq!(lexer.parse("====", None).unwrap(), vec!["===", "="]);
+        assert_eq!(lexer.parse("== =", None).unwrap(), vec!["==", " ", "="]);
+        let tokens = lexer.tokenize("==", None).unwrap();
+        assert_eq!(tokens[0].kind, RegexComponent::Operator("= =".to_string()));
+        assert_eq!(tokens[0].span, 0..2);
+    }
+
+    #[test]
+    fn test_lexer_priority() {
+        // Rules matching the same text: the first declared one wins
+        let number = RegexComponent::Literal("number".to_string());
+        let digit = RegexComponent::Literal("digit".to_string());
+        let lexer = Lexer::with_rules(vec![rule(number.clone(), "\\d+"), rule(digit, "\\d")]);
+        let tokens = lexer.tokenize("7", None).unwrap();
+        assert_eq!(tokens[0].kind, number);
+
+        // Keywords win over identifiers of the same length, whatever their order
+        let mut lexer = Lexer::with_rules(vec![identifier(), space()]);
+        assert_eq!(lexer.add_rule(Rule::keyword("if")), 2);
+        assert_eq!(lexer.get_rules().len(), 3);
+        let kinds: Vec<RegexComponent> =
+            (lexer.tokenize("if iffy _if", None).unwrap().into_iter()).map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RegexComponent::Keyword("if".to_string()),
+                RegexComponent::Literal(" ".to_string()),
+                RegexComponent::Identifier("identifier".to_string()),
+                RegexComponent::Literal(" ".to_string()),
+                RegexComponent::Identifier("identifier".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lexer_stream() {
+        let plus = rule(RegexComponent::Operator("+".to_string()), "\\+");
+        let lexer = Lexer::with_rules(vec![identifier(), plus, space()]);
+        let mut tokens = lexer.tokens("a + b");
+        assert_eq!(tokens.next().unwrap().unwrap().text, "a");
+        assert_eq!(lexer.next_token("a + b", 1).unwrap().unwrap().span, 1..2);
+        assert_eq!(tokens.count(), 4);
+        assert_eq!(lexer.next_token("a + b", 5), Ok(None));
+
+        // The stream stops after a character that no rule matches
+        let tokens: Vec<_> = lexer.tokens("a +\n- b").collect();
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(
+            tokens[4],
+            Err(ParseError::UnknownToken { position: Position { offset: 4, line: 2, column: 1 } })
+        );
+        assert_eq!(lexer.tokenize("", None).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_lexer_empty_and_unsupported() {
+        // A rule matching the empty text never gives a token
+        let lexer = Lexer::with_rules(vec![rule(RegexComponent::Literal("a".to_string()), "a*")]);
+        assert_eq!(lexer.parse("aa", None).unwrap(), vec!["aa"]);
+        assert!(matches!(lexer.parse("b", None), Err(ParseError::UnknownToken { .. })));
+
+        let lexer = Lexer::with_rules(vec![identifier(), rule(RegexComponent::Literal("a".to_string()), "a*+")]);
+        assert!(matches!(lexer.parse("a", None), Err(ParseError::Unsupported { index: 1, .. })));
+        let backtrack = LexerEngine::Backtrack { budget: 1000 };
+        let unknown = ParseError::UnknownToken { position: Position { offset: 2, line: 1, column: 3 } };
+        assert_eq!(lexer.parse("ab a", Some(backtrack)), Err(unknown));
+        assert_eq!(lexer.parse("ab", Some(backtrack)).unwrap(), vec!["ab"]);
+    }
+
+    #[test]
+    fn test_lexer_lazy_rule() {
+        // A lazy rule stops at its first match instead of the longest one
+        let comment = RegexComponent::Literal("comment".to_string());
+        let lexer = Lexer::with_rules(vec![identifier(), space(), rule(comment, "/\\* .*? \\*/")]);
+        assert_eq!(lexer.parse("/* a */ x /* b */", None).unwrap(), vec!["/* a */", " ", "x", " ", "/* b */"]);
+        let backtrack = LexerEngine::Backtrack { budget: 1000 };
+        assert_eq!(lexer.parse("/* a */ x /* b */", Some(backtrack)).unwrap().len(), 5);
+
+        // The longest match across the rules still wins
+        let lexer = Lexer::with_rules(vec![rule(RegexComponent::Literal("a".to_string()), "a+?"), operator("a a")]);
+        assert_eq!(lexer.parse("aaa", None).unwrap(), vec!["aa", "a"]);
+
+        // Both engines end a loop at its first iteration matching nothing
+        let b = RegexComponent::Literal("b".to_string());
+        let lexer = Lexer::with_rules(vec![rule(b, "(?:a??)* b?"), operator("a")]);
+        assert_eq!(lexer.parse("aab", None).unwrap(), vec!["a", "a", "b"]);
+        assert_eq!(lexer.parse("aab", Some(backtrack)).unwrap(), vec!["a", "a", "b"]);
+    }
+
+    #[test]
+    fn test_lexer_lookahead_rule() {
+        let mut lexer = Lexer::with_rules(vec![
+            rule(RegexComponent::Literal("number".to_string()), "-? \\d+"),
+            rule(RegexComponent::Operator("-".to_string()), "- (?![0-9])"),
+            identifier(),
+            space(),
+        ]);
+        assert!(matches!(lexer.parse("a - 1", None), Err(ParseError::Unsupported { index: 1, .. })));
+
+        lexer.set_engine(LexerEngine::Backtrack { budget: 1000 });
+        assert_eq!(lexer.get_engine(), LexerEngine::Backtrack { budget: 1000 });
+        assert_eq!(lexer.parse("a - -1", None).unwrap(), vec!["a", " ", "-", " ", "-1"]);
+        assert_eq!(lexer.parse("a -b", None).unwrap(), vec!["a", " ", "-", "b"]);
+        assert_eq!(lexer.tokens("x-1").map(Result::unwrap).count(), 2);
+        let budget = LexerEngine::Backtrack { budget: 1 };
+        assert!(matches!(lexer.parse("a", Some(budget)), Err(ParseError::BudgetExhausted { budget: 1, .. })));
+    }
+
+    #[test]
+    fn test_lexer_skip_rules() {
+        let lexer = Lexer::with_rules(vec![
+            identifier(),
+            operator("/"),
+            Rule::whitespace(),
+            Rule::line_comment("//"),
+            Rule::block_comment("/*", "*/"),
+        ]);
+        assert_eq!(lexer.parse(" a /b // c d\n e ", None).unwrap(), vec!["a", "/", "b", "e"]);
+        assert_eq!(lexer.parse("a/* b /* c */ d */e", None).unwrap(), vec!["a", "e"]);
+        assert_eq!(lexer.parse("a/**/ /*/**/*/", None).unwrap(), vec!["a"]);
+        assert_eq!(lexer.next_token(" // a", 0), Ok(None));
+        let tokens = lexer.tokenize("x /* y */ z", None).unwrap();
+        assert_eq!(tokens[1].span, 10..11);
+
+        // A block comment that is never closed
+        match lexer.parse("a /* b /* c */", None) {
+            Err(ParseError::Mismatch { position, found, expected, .. }) => {
+                assert_eq!(position.offset, 14);
+                assert_eq!(found, None);
+                assert_eq!(expected, vec!["\"*/\"".to_string()]);
+            }
+            other => panic!("Expected a mismatch, got {:?}", other),
+        }
+
+        // A skipped rule can also be emitted
+        let lexer = Lexer::with_rules(vec![identifier(), Rule::whitespace().with_skip(false)]);
+        assert_eq!(lexer.parse("a b", None).unwrap(), vec!["a", " ", "b"]);
+    }
+
+    #[test]
+    fn test_lexer_trivia() {
+        let lexer = Lexer::with_rules(vec![identifier(), Rule::whitespace(), Rule::line_comment("//")]);
+        let (tokens, trivia) = lexer.tokens_with_trivia("  a // one\n  // two\n b c //three\n").unwrap();
+        let texts = |trivia: &[TokenSlice]| trivia.iter().map(|token| token.text.to_string()).collect::<Vec<_>>();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(texts(&tokens[0].leading), vec!["  "]);
+        assert_eq!(tokens[0].token.text, "a");
+        assert_eq!(texts(&tokens[0].trailing), vec![" ", "// one"]);
+        assert_eq!(texts(&tokens[1].leading), vec!["\n  ", "// two", "\n "]);
+        assert_eq!(texts(&tokens[1].trailing), vec![" "]);
+        assert_eq!(texts(&tokens[2].trailing), vec![" ", "//three"]);
+        assert_eq!(texts(&trivia), vec!["\n"]);
+
+        // Whitespace ending a line is split at the newline
+        let (tokens, trivia) = lexer.tokens_with_trivia("a  \n b \n").unwrap();
+        assert_eq!(texts(&tokens[0].trailing), vec!["  "]);
+        assert_eq!(texts(&tokens[1].leading), vec!["\n "]);
+        assert_eq!(tokens[1].leading[0].span, 3..5);
+        assert_eq!(texts(&tokens[1].trailing), vec![" "]);
+        assert_eq!(texts(&trivia), vec!["\n"]);
+
+        let (tokens, trivia) = lexer.tokens_with_trivia(" // only").unwrap();
+        assert!(tokens.is_empty());
+        assert_eq!(trivia[1].kind, &RegexComponent::Literal("//".to_string()));
+    }
+}
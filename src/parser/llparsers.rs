@@ -1,3 +1,336 @@
-// use crate::parser::parser::Parser;
+use std::collections::HashMap;
+use std::fmt;
 
+use crate::components::grammar::{Analysis, Grammar, GrammarError, Production, Sym};
+use crate::components::token::TokenSlice;
+use crate::parser::error::{ParseContext, ParseError, Position};
+use crate::parser::Parser;
 
+/// LLParser:
+/// Predictive LL(1) parser driven by a parsing table built from the FIRST and FOLLOW sets of a
+/// context-free grammar. The input is scanned into the grammar terminals and then parsed in a single
+/// left-to-right pass, so parsing takes linear time in the length of the input.
+///
+/// ```rust
+/// use pilator::{
+///     components::{grammar::{Grammar, Symbol}, items::RegexComponent},
+///     parser::{llparsers::LLParser, Parser},
+/// };
+///
+/// // list -> "(" list ")" list | ε
+/// let mut grammar = Grammar::new("list");
+/// grammar.add_production("list", vec![
+///     Symbol::Terminal(RegexComponent::Literal("(".to_string())),
+///     Symbol::NonTerminal("list".to_string()),
+///     Symbol::Terminal(RegexComponent::Literal(")".to_string())),
+///     Symbol::NonTerminal("list".to_string()),
+/// ]);
+/// grammar.add_production("list", vec![]);
+///
+/// let parser = LLParser::new(&grammar).unwrap();
+/// assert_eq!(parser.parse("(())", None).unwrap(), vec!["(", "(", ")", ")"]);
+/// assert!(parser.parse("(()", None).is_err());
+/// ```
+pub struct LLParser {
+    analysis: Analysis,
+    /// Predictive table: (nonterminal, lookahead terminal) -> production index
+    table: HashMap<(usize, usize), usize>,
+}
+
+/// Implemented methods:
+/// - new: Builds the parsing table of the grammar, failing if the grammar is not LL(1)
+/// - get_grammar_start: Returns the start symbol of the grammar
+impl LLParser {
+    pub fn new(grammar: &Grammar) -> Result<LLParser, LLError> {
+        let analysis = Analysis::new(grammar).map_err(LLError::Grammar)?;
+
+        let mut table: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut conflicts: Vec<LLConflict> = vec![];
+        for (index, (lhs, rhs)) in analysis.productions.iter().enumerate() {
+            let (mut lookaheads, nullable) = analysis.first_of(rhs);
+            if nullable {
+                lookaheads.extend(analysis.follow[*lhs].iter().copied());
+            }
+            for terminal in lookaheads {
+                match table.get(&(*lhs, terminal)) {
+                    Some(&other) => conflicts.push(LLConflict {
+                        nonterminal: analysis.nonterminals[*lhs].clone(),
+                        lookahead: analysis.terminal_name(terminal),
+                        productions: (
+                            analysis.grammar.get_productions()[other].clone(),
+                            analysis.grammar.get_productions()[index].clone(),
+                        ),
+                    }),
+                    None => {
+                        table.insert((*lhs, terminal), index);
+                    }
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(LLError::Conflicts(conflicts));
+        }
+        Ok(LLParser { analysis, table })
+    }
+
+    pub fn get_grammar_start(&self) -> &str {
+        &self.analysis.nonterminals[self.analysis.start]
+    }
+
+    /// Error for the token at the given index of the scanned input
+    fn mismatch(
+        &self,
+        input: &str,
+        tokens: &[(usize, usize, usize)],
+        index: usize,
+        expected: Vec<String>,
+        production: Option<usize>,
+    ) -> ParseError {
+        let (offset, found) = match tokens.get(index) {
+            Some(&(_, start, stop)) => (start, Some(input[start..stop].to_string())),
+            None => (input.len(), None),
+        };
+        // The tokens are consumed in order, up to the first one that can't be accepted: the furthest
+        // point reached is where the parsing fails
+        let position = Position::new(input, offset);
+        ParseError::Mismatch {
+            position,
+            furthest: position,
+            found,
+            expected,
+            context: production.map(|production| {
+                Box::new(ParseContext::Production(
+                    self.analysis.grammar.get_productions()[production].clone(),
+                ))
+            }),
+        }
+    }
+
+    /// Names of the terminals that can be accepted when the given symbol is on top of the stack
+    fn expected(&self, symbol: Sym) -> Vec<String> {
+        match symbol {
+            Sym::T(t) => vec![self.analysis.terminal_name(t)],
+            Sym::N(n) => {
+                let mut terminals: Vec<usize> = self
+                    .table
+                    .keys()
+                    .filter(|(lhs, _)| *lhs == n)
+                    .map(|(_, terminal)| *terminal)
+                    .collect();
+                terminals.sort();
+                terminals
+                    .into_iter()
+                    .map(|t| self.analysis.terminal_name(t))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Parser trait implementation for LLParser, using the grammar it was built from
+impl Parser for LLParser {
+    type Config = ();
+
+    fn tokenize_slices<'a>(
+        &'a self,
+        input: &'a str,
+        _config: Option<Self::Config>,
+    ) -> Result<Vec<TokenSlice<'a>>, ParseError> {
+        let end = self.analysis.end();
+        let tokens = self.analysis.scan(input).map_err(|offset| ParseError::UnknownToken {
+            position: Position::new(input, offset),
+        })?;
+
+        let mut result: Vec<TokenSlice<'a>> = vec![];
+        // Symbols still to be matched, with the production they come from
+        let mut stack: Vec<(Sym, Option<usize>)> = vec![(Sym::N(self.analysis.start), None)];
+        let mut position = 0;
+        while let Some((symbol, from)) = stack.pop() {
+            let lookahead = tokens.get(position).map_or(end, |token| token.0);
+            let next = match symbol {
+                Sym::T(t) if t == lookahead => {
+                    result.push(self.analysis.token(input, tokens[position]));
+                    position += 1;
+                    continue;
+                }
+                Sym::N(n) => self.table.get(&(n, lookahead)),
+                Sym::T(_) => None,
+            };
+            match next {
+                Some(&production) => {
+                    let rhs = &self.analysis.productions[production].1;
+                    stack.extend(rhs.iter().rev().map(|symbol| (*symbol, Some(production))));
+                }
+                None => return Err(self.mismatch(input, &tokens, position, self.expected(symbol), from)),
+            }
+        }
+
+        match tokens.get(position) {
+            Some(_) => Err(self.mismatch(input, &tokens, position, vec!["end of input".to_string()], None)),
+            None => Ok(result),
+        }
+    }
+}
+
+/// LLConflict:
+/// Two productions of the same nonterminal that are both predicted by the same lookahead terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LLConflict {
+    pub nonterminal: String,
+    pub lookahead: String,
+    pub productions: (Production, Production),
+}
+
+impl fmt::Display for LLConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "conflict on `{}` with lookahead {}: `{}` and `{}`",
+            self.nonterminal, self.lookahead, self.productions.0, self.productions.1
+        )
+    }
+}
+
+/// enum LLError
+/// Errors returned while building an LLParser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LLError {
+    /// The grammar is not well formed
+    Grammar(GrammarError),
+    /// The grammar is not LL(1): every conflicting entry of the parsing table is reported
+    Conflicts(Vec<LLConflict>),
+}
+
+impl fmt::Display for LLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LLError::Grammar(error) => write!(f, "invalid grammar: {}", error),
+            LLError::Conflicts(conflicts) => {
+                write!(f, "grammar is not LL(1)")?;
+                for conflict in conflicts.iter() {
+                    write!(f, "\n  {}", conflict)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LLError {}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::grammar::test_symbols::{literal, nonterminal};
+    use crate::components::token::Token;
+    use crate::components::items::RegexComponent;
+
+    /// e -> t e'; e' -> "+" t e' | ε; t -> f t'; t' -> "*" f t' | ε; f -> "id" | "(" e ")"
+    fn expression_grammar() -> Grammar {
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("t"), nonterminal("e'")]);
+        grammar.add_production("e'", vec![literal("+"), nonterminal("t"), nonterminal("e'")]);
+        grammar.add_production("e'", vec![]);
+        grammar.add_production("t", vec![nonterminal("f"), nonterminal("t'")]);
+        grammar.add_production("t'", vec![literal("*"), nonterminal("f"), nonterminal("t'")]);
+        grammar.add_production("t'", vec![]);
+        grammar.add_production("f", vec![literal("id")]);
+        grammar.add_production("f", vec![literal("("), nonterminal("e"), literal(")")]);
+        grammar
+    }
+
+    #[test]
+    fn test_ll_parser_expression() {
+        let parser = LLParser::new(&expression_grammar()).unwrap();
+        match parser.parse("id + id * (id+id)", None) {
+            Ok(r) => {
+                assert_eq!(
+                    r,
+                    vec!["id", "+", "id", "*", "(", "id", "+", "id", ")"]
+                );
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_ll_parser_tokens() {
+        let parser = LLParser::new(&expression_grammar()).unwrap();
+        let tokens = parser.tokenize("id +\n(id)", None).unwrap();
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0], Token::new(RegexComponent::Literal("id".to_string()), "id", 0..2));
+        assert_eq!(tokens[2], Token::new(RegexComponent::Literal("(".to_string()), "(", 5..6));
+        assert_eq!(tokens[4].span, 8..9);
+    }
+
+    #[test]
+    fn test_ll_parser_syntax_error() {
+        let parser = LLParser::new(&expression_grammar()).unwrap();
+        match parser.parse("id + * id", None) {
+            Err(ParseError::Mismatch { position, found, expected, context, .. }) => {
+                assert_eq!(position, Position { offset: 5, line: 1, column: 6 });
+                assert_eq!(found, Some("*".to_string()));
+                assert_eq!(expected, vec!["\"id\"", "\"(\""]);
+                assert_eq!(
+                    context.as_deref(),
+                    Some(&ParseContext::Production(expression_grammar().get_productions()[1].clone()))
+                );
+            }
+            r => panic!("Expected a mismatch, got {:?}", r),
+        }
+        assert_eq!(
+            parser.parse("(id", None).unwrap_err().to_string(),
+            "unexpected end of input at line 1, column 4, expected one of: \")\", \
+             while matching production `f -> \"(\" e \")\"`"
+        );
+        assert_eq!(
+            parser.parse("id\n ?", None),
+            Err(ParseError::UnknownToken { position: Position { offset: 4, line: 2, column: 2 } })
+        );
+        assert_eq!(
+            parser.parse("id id", None).unwrap_err().expected(),
+            ["\"+\"", "\"*\"", "\")\"", "end of input"]
+        );
+    }
+
+    #[test]
+    fn test_ll_parser_longest_terminal() {
+        // s -> "=" "==" | "==" "="
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("="), literal("==")]);
+        grammar.add_production("s", vec![literal("=="), literal("=")]);
+        let parser = LLParser::new(&grammar).unwrap();
+        assert_eq!(parser.parse("===", None).unwrap(), vec!["==", "="]);
+        assert_eq!(parser.parse("= ==", None).unwrap(), vec!["=", "=="]);
+    }
+
+    #[test]
+    fn test_ll_parser_conflict() {
+        // e -> e "+" "id" | "id" is left recursive, so both productions start with "id"
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("e"), literal("+"), literal("id")]);
+        grammar.add_production("e", vec![literal("id")]);
+        match LLParser::new(&grammar) {
+            Err(LLError::Conflicts(conflicts)) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].nonterminal, "e");
+                assert_eq!(conflicts[0].lookahead, "\"id\"");
+                assert_eq!(conflicts[0].productions.0, grammar.get_productions()[0]);
+                assert_eq!(conflicts[0].productions.1, grammar.get_productions()[1]);
+            }
+            _ => panic!("Expected a conflict"),
+        }
+    }
+
+    #[test]
+    fn test_ll_parser_invalid_grammar() {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![nonterminal("missing")]);
+        assert!(matches!(
+            LLParser::new(&grammar),
+            Err(LLError::Grammar(GrammarError::UndefinedNonTerminal { .. }))
+        ));
+    }
+}
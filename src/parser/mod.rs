@@ -1,18 +1,47 @@
 pub mod naive_parser;
+pub mod lexer;
 pub mod llparsers;
 pub mod slrparsers;
+pub mod error;
+pub mod diagnostic;
+
+use crate::components::token::{Token, TokenSlice};
+use crate::parser::error::ParseError;
 
 /// Parser trait:
 /// this is the trait that every parser in the library should implement.
 /// It contains the following methods:
-/// - parse: This is the main method that will be called to parse the input string. It takes the input string
-/// and an optional configuration parameter, and returns a Result<Regex, String>. The Regex is the result of the
-/// parsing, and the String is an error message in case the parsing fails.
+/// - tokenize_slices: This is the main method that will be called to parse the input string. It takes the input
+///   string and an optional configuration parameter, and returns a Result<Vec<TokenSlice>, ParseError>. Every
+///   TokenSlice borrows the RegexComponent that matched it and the matched text from the input, together with its
+///   byte range, and the ParseError describes where and why the parsing failed.
+/// - tokenize: Convenience method on top of tokenize_slices, returning owned Token values.
+/// - parse: Convenience method on top of tokenize_slices, returning only the text of the matched tokens.
 /// - custom_parse: This is an optional method that can be implemented by the parser. It takes the input string
-/// and an optional configuration parameter, and returns a Result<Regex, String>. The Regex is the result of the
-/// parsing, and the String is an error message in case the parsing fails.
+///   and an optional configuration parameter, and returns a Result<Regex, String>. The Regex is the result of the
+///   parsing, and the String is an error message in case the parsing fails.
 pub trait Parser {
     type Config;
 
-    fn parse(&self, input: &str, config: Option<Self::Config>) -> Result<Vec<String>, String>;
-}
\ No newline at end of file
+    fn tokenize_slices<'a>(
+        &'a self,
+        input: &'a str,
+        config: Option<Self::Config>,
+    ) -> Result<Vec<TokenSlice<'a>>, ParseError>;
+
+    fn tokenize(&self, input: &str, config: Option<Self::Config>) -> Result<Vec<Token>, ParseError> {
+        Ok(self
+            .tokenize_slices(input, config)?
+            .into_iter()
+            .map(Token::from)
+            .collect())
+    }
+
+    fn parse(&self, input: &str, config: Option<Self::Config>) -> Result<Vec<String>, ParseError> {
+        Ok(self
+            .tokenize_slices(input, config)?
+            .into_iter()
+            .map(|token| token.text.to_string())
+            .collect())
+    }
+}
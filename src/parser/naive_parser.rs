@@ -1,14 +1,30 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
 use std::vec;
 
-use crate::components::items::RegexComponent;
+use crate::automata::backtrack;
+use crate::automata::derivative;
+use crate::automata::dfa::Dfa;
+use crate::automata::lazy_dfa::LazyDfa;
+use crate::automata::nfa::{CompileError, Nfa};
+use crate::components::captures::Captures;
+use crate::components::grammar::{Grammar, Production, Symbol};
 use crate::components::regex::Regex;
+use crate::components::token::TokenSlice;
+use crate::parser::error::{ParseContext, ParseError, Position};
 use crate::parser::Parser;
 
 /// NaiveParser:  
-/// Simple parser that implements a naive parsing algorithm solely based on brute forcing
-/// the input string with all the regular expressions that have been provided.
+/// Simple parser that implements a naive parsing algorithm solely based on trying, one after the
+/// other, all the regular expressions that have been provided against the whole input string.
+/// Every Regex is compiled into a Nfa (see automata::nfa), and all of them together into a single
+/// Dfa (see automata::dfa) that finds the first Regex matching the input in one linear pass.
+/// Regex with possessive repetitions can't be compiled: they are only parsed by Engine::Backtrack,
+/// the other engines failing with ParseError::Unsupported.
 /// This is a good parser if the language is pretty simple, with low number of regular expressions
-/// to be checked, or if the input string is not too long.
+/// to be checked.
+/// A Grammar can be provided as well, to recognise recursive languages that a list of Regex can't
+/// express: it is tried, by brute force over all its productions, when none of the Regex matches.
 ///
 /// ```rust
 /// use pilator::{parser::{naive_parser::NaiveParser, Parser}, components::items::RegexComponent, components::regex::Regex};
@@ -28,6 +44,20 @@ use crate::parser::Parser;
 pub struct NaiveParser {
     /// Contains a list of all the Regex that we are going to match against the input string
     regexes: Vec<Regex>,
+    /// Nfa compiled from every Regex, used to match them against the input string, or the index of
+    /// the first Regex that can't be compiled; compiled the first time it is needed after the Regex
+    /// change
+    nfas: OnceLock<Result<Vec<Nfa>, (usize, CompileError)>>,
+    /// Dfa of all the Nfa, built the first time it is needed after the Regex change
+    dfa: OnceLock<Dfa>,
+    /// LazyDfa of all the Nfa, created the first time it is needed after the Regex change
+    lazy_dfa: Mutex<Option<LazyDfa>>,
+    /// Engine used when parse is not given one
+    engine: Engine,
+    /// Grammar matched against the input string when none of the Regex matches
+    grammar: Option<Grammar>,
+    /// Lowered form of the Grammar, which the matched tokens borrow their kind from
+    lowered: Option<Grammar>,
 }
 
 /// Implemented methods:
@@ -36,16 +66,56 @@ pub struct NaiveParser {
 /// - add_regex: Adds a new Regex to the list of Regexes
 /// - get_regexes: Returns a reference to the list of Regexes
 /// - remove_regex_with_index: Removes a Regex from the list of Regexes
+/// - with_grammar: Creates a new instance of NaiveParser with a Grammar
+/// - set_grammar: Sets the Grammar, replacing the previous one
+/// - get_grammar: Returns a reference to the Grammar, if any
+/// - get_dfa: Returns the Dfa matching all the Regex at once, or ParseError::Unsupported when one of
+///   them can't be compiled
+/// - set_engine: Sets the Engine used when parse is not given one
+/// - get_engine: Returns the Engine used when parse is not given one
+/// - captures: Returns the index of the first Regex matching the whole input and what its capture
+///   groups matched, None if no Regex matches
 impl NaiveParser {
     pub fn new() -> NaiveParser {
-        NaiveParser { regexes: vec![] }
+        NaiveParser {
+            regexes: vec![],
+            nfas: OnceLock::new(),
+            dfa: OnceLock::new(),
+            lazy_dfa: Mutex::new(None),
+            engine: Engine::default(),
+            grammar: None,
+            lowered: None,
+        }
     }
 
     pub fn with_regexes(regexes: Vec<Regex>) -> NaiveParser {
-        NaiveParser { regexes }
+        NaiveParser {
+            nfas: OnceLock::new(),
+            dfa: OnceLock::new(),
+            lazy_dfa: Mutex::new(None),
+            engine: Engine::default(),
+            regexes,
+            grammar: None,
+            lowered: None,
+        }
+    }
+
+    pub fn with_grammar(grammar: Grammar) -> NaiveParser {
+        NaiveParser {
+            regexes: vec![],
+            nfas: OnceLock::new(),
+            dfa: OnceLock::new(),
+            lazy_dfa: Mutex::new(None),
+            engine: Engine::default(),
+            lowered: Some(grammar.lowered()),
+            grammar: Some(grammar),
+        }
     }
 
     pub fn add_regex(&mut self, regex: Regex) -> usize {
+        self.nfas = OnceLock::new();
+        self.dfa = OnceLock::new();
+        self.lazy_dfa = Mutex::new(None);
         self.regexes.push(regex);
         self.regexes.len() - 1
     }
@@ -56,206 +126,383 @@ impl NaiveParser {
 
     pub fn remove_regex_with_index(&mut self, index: usize) {
         self.regexes.remove(index);
+        self.nfas = OnceLock::new();
+        self.dfa = OnceLock::new();
+        self.lazy_dfa = Mutex::new(None);
     }
 
+    pub fn set_grammar(&mut self, grammar: Grammar) {
+        self.lowered = Some(grammar.lowered());
+        self.grammar = Some(grammar);
+    }
 
-    fn matches(input: String, input_idx: usize, regex: RegexComponent, result: &mut Vec<String>, alarm: &mut bool) -> usize {
-        match regex {
-            RegexComponent::Literal(value) => {
-                if input_idx + value.len() > input.len() {
-                    return 0;
-                }
-                let word = input[input_idx..input_idx + value.len()].to_string();
-                if word != value {
-                    return 0;
-                }
-                return value.len();
-            }
-            RegexComponent::Keyword(value) => {
-                if input_idx + value.len() > input.len() {
-                    return 0;
-                }
-                let word = input[input_idx..input_idx + value.len()].to_string();
-                if word != value {
-                    return 0;
-                }
-                return value.len();
-            }
-            RegexComponent::Identifier(value) => {
-                if input_idx + value.len() > input.len() {
-                    return 0;
-                }
-                let word = input[input_idx..input_idx + value.len()].to_string();
-                if word != value {
-                    return 0;
-                }
-                return value.len();
-            }
-            RegexComponent::Operator(value) => {
-                if input_idx + value.len() > input.len() {
-                    return 0;
-                }
-                let word = input[input_idx..input_idx + value.len()].to_string();
-                if word != value {
-                    return 0;
-                }
-                return value.len();
-            }
-            RegexComponent::ZeroOrMore(value) => {
-                let mut temp_idx = input_idx;
+    pub fn get_grammar(&self) -> Option<&Grammar> {
+        self.grammar.as_ref()
+    }
 
-                while temp_idx < input.len() {
+    pub fn get_dfa(&self) -> Result<&Dfa, ParseError> {
+        let nfas = self.nfas()?;
+        Ok(self.dfa.get_or_init(|| Dfa::new(nfas)))
+    }
 
-                    for component in value.components.iter() {
-                        let temp = Self::matches(input.clone(), temp_idx, component.clone(), result, alarm);
-                        if temp == 0 {
-                            return temp_idx - input_idx;
-                        }
-                        temp_idx += temp;
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    pub fn get_engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// captures
+    /// The input is trimmed as by tokenize, and the spans of the captures are offsets of the
+    /// original input. The Regex is found with the engine, or the Engine of the parser if None, and
+    /// matched by its Nfa, or by its Backtracker with Engine::Backtrack.
+    pub fn captures<'a>(
+        &self,
+        input: &'a str,
+        engine: Option<Engine>,
+    ) -> Result<Option<(usize, Captures<'a>)>, ParseError> {
+        let original = input;
+        let leading = input.len() - input.trim_start().len();
+        let input = input.trim();
+        let found = if let Engine::Backtrack { budget } = engine.unwrap_or(self.engine) {
+            self.backtrackable()?;
+            let mut found = None;
+            for (index, regex) in self.regexes.iter().enumerate() {
+                let captures = backtrack::captures(regex, input, budget).map_err(|error| {
+                    ParseError::BudgetExhausted {
+                        position: Position::new(original, leading + error.offset),
+                        budget: error.budget,
                     }
+                })?;
+                if let Some(captures) = captures {
+                    found = Some((index, captures));
+                    break;
                 }
-                temp_idx - input_idx
             }
-            RegexComponent::OneOrMore(value) => {
-                let mut temp_idx = input_idx;
+            found
+        } else {
+            let nfas = self.nfas()?;
+            (self.first_match(input, engine.unwrap_or(self.engine))?)
+                .and_then(|index| Some((index, nfas[index].captures(input)?)))
+        };
+        Ok(found.map(|(index, captures)| (index, captures.shifted(leading))))
+    }
 
-                while temp_idx < input.len() {
-                    for component in value.components.iter() {
-                        let temp = Self::matches(input.clone(), temp_idx, component.clone(), result, alarm);
-                        if temp == 0 {
-                            return temp_idx - input_idx;
-                        }
-                        temp_idx += temp;
+    /// Nfa of every Regex, compiled the first time they are needed
+    fn nfas(&self) -> Result<&[Nfa], ParseError> {
+        let nfas = self.nfas.get_or_init(|| {
+            (self.regexes.iter().enumerate())
+                .map(|(index, regex)| Nfa::new(regex).map_err(|error| (index, error)))
+                .collect()
+        });
+        match nfas {
+            Ok(nfas) => Ok(nfas),
+            Err((index, error)) => Err(ParseError::Unsupported {
+                index: *index,
+                error: error.clone(),
+            }),
+        }
+    }
+
+    /// Error for the first Regex that even the backtracking engine can't match, checked before
+    /// searching with Engine::Backtrack
+    fn backtrackable(&self) -> Result<(), ParseError> {
+        for (index, regex) in self.regexes.iter().enumerate() {
+            backtrack::supported(regex).map_err(|error| ParseError::Unsupported { index, error })?;
+        }
+        Ok(())
+    }
+
+    /// Index of the first Regex matching the whole input, found with the given engine, which must
+    /// not be Engine::Backtrack
+    fn first_match(&self, input: &str, engine: Engine) -> Result<Option<usize>, ParseError> {
+        let nfas = self.nfas()?;
+        Ok(match engine {
+            Engine::Dfa => self.get_dfa()?.matches(input),
+            Engine::LazyDfa { budget } => {
+                let mut lazy_dfa = self.lazy_dfa.lock().unwrap_or_else(|error| error.into_inner());
+                let lazy_dfa = lazy_dfa.get_or_insert_with(|| LazyDfa::new(nfas.to_vec(), budget));
+                lazy_dfa.set_budget(budget);
+                lazy_dfa.matches(input)
+            }
+            Engine::Nfa => nfas.iter().position(|nfa| nfa.is_match(input)),
+            Engine::Derivative => {
+                let mut first = None;
+                for (index, regex) in self.regexes.iter().enumerate() {
+                    if derivative::matches(regex, input).map_err(|error| ParseError::Unsupported { index, error })? {
+                        first = Some(index);
+                        break;
                     }
                 }
-                temp_idx - input_idx
+                first
             }
-            RegexComponent::ZeroOrOne(value) => {
-                let mut temp_idx = input_idx;
+            Engine::Backtrack { .. } => unreachable!("the backtracking engine tokenizes on its own"),
+        })
+    }
+
+    /// Moves the spans of tokens matched in the trimmed input back to offsets of the original input
+    fn shifted(mut tokens: Vec<TokenSlice<'_>>, leading: usize) -> Vec<TokenSlice<'_>> {
+        for token in tokens.iter_mut() {
+            token.span = token.span.start + leading..token.span.end + leading;
+        }
+        tokens
+    }
+}
+
+/// enum Engine
+/// Engine used by a NaiveParser to find the first Regex matching the input:
+/// - Dfa: Dfa of all the Regex, built once, then matching in a single pass
+/// - LazyDfa: Dfa built while matching, keeping its states in a cache of `budget` bytes, for sets of
+///   Regex whose Dfa is too large to be built ahead of time
+/// - Nfa: Nfa of every Regex, tried one after the other
+/// - Derivative: Brzozowski derivatives of every Regex, tried one after the other; slow, but simple
+///   enough to serve as a reference for the other engines
+/// - Backtrack: Backtracker of every Regex, tried one after the other, each giving up after `budget`
+///   steps with ParseError::BudgetExhausted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    #[default]
+    Dfa,
+    LazyDfa { budget: usize },
+    Nfa,
+    Derivative,
+    Backtrack { budget: usize },
+}
 
-                for component in value.components.iter() {
-                    let temp = Self::matches(input.clone(), temp_idx, component.clone(), result, alarm);
-                    if temp == 0 {
-                        return temp_idx - input_idx;
+impl Default for NaiveParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parser trait implementation for NaiveParser, using its own Regex
+impl Parser for NaiveParser {
+    type Config = Engine;
+
+    fn tokenize_slices<'a>(
+        &'a self,
+        input: &'a str,
+        config: Option<Self::Config>,
+    ) -> Result<Vec<TokenSlice<'a>>, ParseError> {
+        let mut failure: Option<Failure> = None;
+
+        let original = input;
+        let leading = input.len() - input.trim_start().len();
+        let input = input.trim();
+        let mut failures = vec![];
+        if let Engine::Backtrack { budget } = config.unwrap_or(self.engine) {
+            self.backtrackable()?;
+            // The backtracking engine splits the input into tokens itself, as the Regex may not compile
+            for (index, regex) in self.regexes.iter().enumerate() {
+                let tokenized = backtrack::tokenize(regex, input, budget).map_err(|error| {
+                    ParseError::BudgetExhausted {
+                        position: Position::new(original, leading + error.offset),
+                        budget: error.budget,
                     }
-                    temp_idx += temp;
+                })?;
+                match tokenized {
+                    Ok(tokens) => return Ok(Self::shifted(tokens, leading)),
+                    Err(error) => failures.push((index, error)),
                 }
-                temp_idx - input_idx
-                
             }
-            RegexComponent::Or(regex1, regex2) => {
-                let temp1 = Self::matches(input.clone(), input_idx, RegexComponent::ZeroOrOne(regex1), result, alarm);
-                if temp1 != 0 {
-                    return temp1;
+        } else {
+            // The engine finds the first Regex matching the input, whose Nfa then splits the input into tokens
+            let nfas = self.nfas()?;
+            if let Some(index) = self.first_match(input, config.unwrap_or(self.engine))? {
+                if let Ok(tokens) = nfas[index].tokenize(input) {
+                    return Ok(Self::shifted(tokens, leading));
                 }
-                let temp2 = Self::matches(input, input_idx, RegexComponent::ZeroOrOne(regex2), result, alarm);
-                temp2
-            }
-            RegexComponent::SubRegex(regex) => {
-                let temp_idx = input_idx;
-                let mut temp_res = vec![];
-                
-                match Self::tokenize_helper(input.clone(), temp_idx, &regex, &mut temp_res, true) {
-                    Ok(_) => {
-                        let offset: usize = temp_res.iter().map(|x| x.len()).sum();
-                        result.append(&mut temp_res);
-                        *alarm = true;
-                        return offset;
-                    },
-                    Err(_) => {
-                        return 0;
-                    },
+            }
+            // Otherwise every Nfa reports how far it could go, to find the furthest failure
+            for (index, nfa) in nfas.iter().enumerate() {
+                if let Err(error) = nfa.tokenize(input) {
+                    failures.push((index, error));
                 }
             }
         }
-    }
+        // Furthest offset reached by a matcher, which can be past the failures when a component was
+        // partly matched
+        let mut furthest = 0;
+        for (index, error) in failures {
+            furthest = furthest.max(error.furthest);
+            for expected in error.expected {
+                Failure::record(&mut failure, error.offset, expected, || {
+                    Some(ParseContext::Regex { index, regex: self.regexes[index].clone() })
+                })
+            }
+        }
 
-    fn tokenize_helper(
-        input: String,
-        input_idx: usize,
-        regex: &Regex,
-        result: &mut Vec<String>,
-        nested: bool
-    ) -> Result<u32, String> {
-        if input_idx == input.len() {
-            // If we reach the end of the input and regex, the regex is a valid match
-            return Ok(1);
-        } else {
-            // Check what the current component is and call the corresponding method
-            let mut idx = input_idx;
-            let mut regex_idx = 0;
-            for component in regex.components.iter() {
-                let mut alarm: bool = false;
-                let temp = Self::matches(input.clone(), idx, component.clone(), result, &mut alarm);
-                regex_idx += 1;
-                if temp == 0 {
-                    if component.is_nullable() {
-                        continue;
-                    }
-                    return Err("No match".to_string());
-                }
-                // Check if the component contains a subregex
-                if !alarm {
-                    result.push(input[idx..idx + temp].to_string());
-                }
-                idx += temp;
-                if idx >= input.len() {
-                    break;
+        if let (Some(grammar), Some(lowered)) = (&self.grammar, &self.lowered) {
+            grammar.validate()?;
+            let mut matcher = GrammarMatcher::new(lowered, input);
+            if let Some(tokens) = matcher.matches() {
+                return Ok(Self::shifted(tokens, leading));
+            }
+            if let Some(grammar_failure) = matcher.failure {
+                furthest = furthest.max(grammar_failure.offset);
+                for expected in grammar_failure.expected {
+                    let context = grammar_failure.context.clone();
+                    Failure::record(&mut failure, grammar_failure.offset, expected, || context);
                 }
             }
+        }
 
-            if regex_idx < regex.components.len() {
-                for component in regex.components[regex_idx..].iter() {
-                    if !component.is_nullable() {
-                        return Err("No match".to_string());
-                    }
+        let failure = failure.unwrap_or(Failure {
+            offset: 0,
+            expected: vec![],
+            context: None,
+        });
+        let position = Position::new(original, leading + failure.offset);
+        Err(ParseError::Mismatch {
+            position,
+            furthest: Position::new(original, leading + furthest.max(failure.offset)),
+            found: input[failure.offset..].chars().next().map(|c| c.to_string()),
+            expected: failure.expected,
+            context: failure.context.map(Box::new),
+        })
+    }
+}
+
+/// Furthest failure found while trying the Regex or the Grammar: offset in the trimmed input,
+/// components that could have been matched there, and what was being matched
+struct Failure {
+    offset: usize,
+    expected: Vec<String>,
+    context: Option<ParseContext>,
+}
+
+impl Failure {
+    /// Keeps the furthest failure, merging the expected components of failures at the same offset
+    fn record(
+        failure: &mut Option<Failure>,
+        offset: usize,
+        expected: String,
+        context: impl FnOnce() -> Option<ParseContext>,
+    ) {
+        match failure {
+            Some(current) if current.offset > offset => (),
+            Some(current) if current.offset == offset => {
+                if !current.expected.contains(&expected) {
+                    current.expected.push(expected);
                 }
             }
-
-            if idx < input.len() && !nested {
-                return Err("No match".to_string());
+            _ => {
+                *failure = Some(Failure {
+                    offset,
+                    expected: vec![expected],
+                    context: context(),
+                })
             }
-
-            Ok(1)
         }
     }
 }
 
-/// Parser trait implementation for NaiveParser, using its own Regex
-impl Parser for NaiveParser {
-    type Config = ();
+/// GrammarMatcher:
+/// Brute force matcher of a lowered Grammar, computing for every nonterminal and starting offset all the
+/// offsets where a derivation of the nonterminal can end, together with the tokens of one of them.
+/// Results are computed again until they stop growing, so that left recursive productions, which
+/// would otherwise loop forever, get all their derivations.
+struct GrammarMatcher<'a> {
+    grammar: &'a Grammar,
+    input: &'a str,
+    memo: HashMap<(&'a str, usize), BTreeMap<usize, Vec<TokenSlice<'a>>>>,
+    visited: HashSet<(&'a str, usize)>,
+    changed: bool,
+    /// Furthest terminal that could not be matched
+    failure: Option<Failure>,
+}
 
-    fn parse(&self, input: &str, _config: Option<Self::Config>) -> Result<Vec<String>, String> {
-        let mut tokens: Vec<String>;
+impl<'a> GrammarMatcher<'a> {
+    fn new(grammar: &'a Grammar, input: &'a str) -> GrammarMatcher<'a> {
+        GrammarMatcher {
+            grammar,
+            input,
+            memo: HashMap::new(),
+            visited: HashSet::new(),
+            changed: false,
+            failure: None,
+        }
+    }
 
-        let input = input.trim().clone();
-        for component in self.regexes.iter() {
-            tokens = vec![];
-            match Self::tokenize_helper(
-                input.to_string(),
-                0,
-                component,
-                &mut tokens,
-                false
-            ) {
-                Ok(_) => {
-                    return Ok(tokens);
-                },
-                Err(_) => (),
+    /// Tokens of a derivation of the start symbol covering the whole input
+    fn matches(&mut self) -> Option<Vec<TokenSlice<'a>>> {
+        loop {
+            self.visited.clear();
+            self.changed = false;
+            let mut ends = self.nonterminal(self.grammar.get_start(), 0);
+            if !self.changed {
+                let tokens = ends.remove(&self.input.len());
+                if let (None, Some((&end, _))) = (&tokens, ends.last_key_value()) {
+                    Failure::record(&mut self.failure, end, "end of input".to_string(), || None);
+                }
+                return tokens;
             }
         }
+    }
 
-        Err("Unable to parse from given input".to_string())
+    fn nonterminal(&mut self, name: &'a str, input_idx: usize) -> BTreeMap<usize, Vec<TokenSlice<'a>>> {
+        let key = (name, input_idx);
+        // Already computed in this pass, or being computed: use what is known so far
+        if !self.visited.insert(key) {
+            return self.memo.get(&key).cloned().unwrap_or_default();
+        }
 
+        let mut ends = self.memo.get(&key).cloned().unwrap_or_default();
+        let before = ends.len();
+        for production in self.grammar.productions_of(name) {
+            for (end, tokens) in self.sequence(production, input_idx) {
+                ends.entry(end).or_insert(tokens);
+            }
+        }
+        self.changed |= ends.len() != before;
+        self.memo.insert(key, ends.clone());
+        ends
     }
 
-    
+    fn sequence(&mut self, production: &'a Production, input_idx: usize) -> BTreeMap<usize, Vec<TokenSlice<'a>>> {
+        let mut current: BTreeMap<usize, Vec<TokenSlice<'a>>> = BTreeMap::from([(input_idx, vec![])]);
+        for symbol in production.rhs.iter() {
+            let mut next: BTreeMap<usize, Vec<TokenSlice<'a>>> = BTreeMap::new();
+            for (idx, tokens) in current.into_iter() {
+                match symbol {
+                    Symbol::Terminal(component) => {
+                        let start = idx + self.input[idx..].len() - self.input[idx..].trim_start().len();
+                        if let Some(len) = component.match_terminal(&self.input[start..]) {
+                            let mut tokens = tokens;
+                            tokens.push(TokenSlice::new(component, &self.input[start..start + len], start..start + len));
+                            next.entry(start + len).or_insert(tokens);
+                        } else {
+                            Failure::record(&mut self.failure, start, symbol.to_string(), || {
+                                Some(ParseContext::Production(production.clone()))
+                            });
+                        }
+                    }
+                    Symbol::NonTerminal(name) => {
+                        for (end, rest) in self.nonterminal(name, idx) {
+                            next.entry(end).or_insert_with(|| {
+                                let mut tokens = tokens.clone();
+                                tokens.extend(rest);
+                                tokens
+                            });
+                        }
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
 }
 
 // Unit tests for the created structures
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::components::grammar::test_symbols::{literal, nonterminal};
+    use crate::components::class::CharClass;
+    use crate::components::items::{Flags, RegexComponent};
+    use crate::components::token::Token;
 
     #[test]
     fn test_naive_parser_litterals() {
@@ -391,12 +638,13 @@ mod test {
             litteral_1.clone(),
             litteral_2.clone(),
             keyword_1.clone(),
+            RegexComponent::Literal(".".to_string()),
             litteral_1.clone(),
         ]);
 
         let mut s = NaiveParser::new();
         s.add_regex(regex_1);
-        match s.parse("WORDbcWORD", None) {
+        match s.parse("WORDbc.WORD", None) {
             Ok(r) => {
                 assert_eq!(
                     r,
@@ -404,18 +652,24 @@ mod test {
                         "WORD".to_string(),
                         "b".to_string(),
                         "c".to_string(),
+                        ".".to_string(),
                         "WORD".to_string()
                     ]
                 );
             }
             Err(e) => panic!("Error: {}", e),
         }
+
+        // A keyword can't be followed by a word character
+        let mut s = NaiveParser::new();
+        s.add_regex(Regex::new(vec![keyword_1, litteral_1]));
+        assert!(s.parse("cWORD", None).is_err());
     }
 
     #[test]
     fn test_naive_parser_keyword_litterals_with_space() {
         let keyword_1 = RegexComponent::Keyword("a".to_string());
-        let litteral_1 = RegexComponent::Literal("b".to_string());
+        let litteral_1 = RegexComponent::Literal("+".to_string());
         let regex_1 = Regex::new(vec![
             keyword_1.clone(),
             litteral_1.clone(),
@@ -425,13 +679,13 @@ mod test {
 
         let mut s = NaiveParser::new();
         s.add_regex(regex_1);
-        match s.parse("ab a", None) {
+        match s.parse("a+ a", None) {
             Ok(r) => {
                 assert_eq!(
                     r,
                     vec![
                         "a".to_string(),
-                        "b".to_string(),
+                        "+".to_string(),
                         " ".to_string(),
                         "a".to_string()
                     ]
@@ -997,4 +1251,505 @@ mod test {
             Err(e) => panic!("Error: {}", e),
         }
     }
+
+    #[test]
+    fn test_naive_parser_zero_or_more_gives_back() {
+        let litteral_1 = RegexComponent::Literal("a".to_string());
+        let regex_1 = Regex::new(vec![
+            RegexComponent::ZeroOrMore(Regex::new(vec![litteral_1.clone()])),
+            litteral_1.clone(),
+        ]);
+
+        let mut s = NaiveParser::new();
+        s.add_regex(regex_1);
+        match s.parse("aa", None) {
+            Ok(r) => {
+                assert_eq!(r, vec!["a".to_string(), "a".to_string()]);
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_naive_parser_or_is_not_optional() {
+        let regex_1 = Regex::new(vec![
+            RegexComponent::Literal("a".to_string()),
+            RegexComponent::Or(
+                Regex::new(vec![RegexComponent::Literal("b".to_string())]),
+                Regex::new(vec![RegexComponent::Literal("c".to_string())]),
+            ),
+        ]);
+
+        let s = NaiveParser::with_regexes(vec![regex_1]);
+        let error = s.parse("a", None).unwrap_err();
+        assert_eq!(error.expected(), ["\"b\"", "\"c\""]);
+    }
+
+    #[test]
+    fn test_naive_parser_dfa() {
+        let mut s = NaiveParser::new();
+        s.add_regex(Regex::new(vec![
+            RegexComponent::Keyword("if".to_string()),
+            RegexComponent::Literal(" ".to_string()),
+            RegexComponent::Identifier("x".to_string()),
+        ]));
+        s.add_regex(Regex::new(vec![RegexComponent::OneOrMore(Regex::new(vec![
+            RegexComponent::Literal("i".to_string()),
+        ]))]));
+        assert_eq!(s.get_dfa().unwrap().state_count(), 6);
+        assert_eq!(s.get_dfa().unwrap().matches("if x"), Some(0));
+        assert_eq!(s.parse("iii", None).unwrap(), vec!["iii"]);
+
+        s.remove_regex_with_index(1);
+        assert_eq!(s.get_dfa().unwrap().state_count(), 5);
+        assert!(s.parse("iii", None).is_err());
+    }
+
+    #[test]
+    fn test_naive_parser_engines() {
+        let mut s = NaiveParser::with_regexes(vec![
+            Regex::new(vec![
+                RegexComponent::Keyword("if".to_string()),
+                RegexComponent::Literal(" ".to_string()),
+                RegexComponent::Identifier("x".to_string()),
+            ]),
+            Regex::new(vec![
+                RegexComponent::ZeroOrMore(Regex::new(vec![RegexComponent::Literal("i".to_string())])),
+                RegexComponent::Literal("f".to_string()),
+            ]),
+        ]);
+        let engines = [
+            Engine::Dfa,
+            Engine::LazyDfa { budget: 0 },
+            Engine::LazyDfa { budget: 1 << 16 },
+            Engine::Nfa,
+            Engine::Backtrack { budget: 1000 },
+        ];
+        for engine in engines {
+            assert_eq!(s.parse("if x", Some(engine)).unwrap(), vec!["if", " ", "x"]);
+            assert_eq!(s.parse("iiif", Some(engine)).unwrap(), vec!["iii", "f"]);
+            assert!(s.parse("iff", Some(engine)).is_err());
+        }
+
+        s.set_engine(Engine::Nfa);
+        assert_eq!(s.get_engine(), Engine::Nfa);
+        assert_eq!(s.parse("f", None).unwrap(), vec!["f"]);
+    }
+
+    #[test]
+    fn test_naive_parser_backtrack_budget() {
+        let s = NaiveParser::with_regexes(vec!["(a+)+ b".parse::<Regex>().unwrap()]);
+        let input = format!("  {}", "a".repeat(30));
+        let error = s.parse(&input, Some(Engine::Backtrack { budget: 500 })).unwrap_err();
+        match &error {
+            ParseError::BudgetExhausted { position, budget } => {
+                assert_eq!(*budget, 500);
+                assert!(position.offset >= 2);
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert!(error.to_string().starts_with("backtracking budget of 500 steps exhausted at line 1"));
+        // The linear-time engines do not need a budget
+        assert!(matches!(s.parse(&input, Some(Engine::Nfa)), Err(ParseError::Mismatch { .. })));
+        assert_eq!(s.parse("aab", Some(Engine::Backtrack { budget: 500 })).unwrap(), vec!["aa", "b"]);
+    }
+
+    #[test]
+    fn test_naive_parser_lazy_and_possessive() {
+        let comment: Regex = "/\\* [a-z */]*? \\*/".parse().unwrap();
+        let s = NaiveParser::with_regexes(vec![comment]);
+        for engine in [Engine::Dfa, Engine::Nfa, Engine::Backtrack { budget: 1000 }] {
+            assert_eq!(s.parse("/* a * b */", Some(engine)).unwrap(), vec!["/*", " a * b ", "*/"]);
+        }
+
+        // Possessive repetitions are only parsed by the backtracking engine
+        let mut s = NaiveParser::with_regexes(vec!["[a-z]++ [0-9]*+".parse().unwrap()]);
+        let backtrack = Some(Engine::Backtrack { budget: 1000 });
+        assert_eq!(s.parse("abc12", backtrack).unwrap(), vec!["abc", "12"]);
+        let error = s.parse("abc12", None).unwrap_err();
+        match &error {
+            ParseError::Unsupported { index, error } => {
+                assert_eq!(*index, 0);
+                assert_eq!(error.component.to_string(), "[a-z]++");
+            }
+            error => panic!("unexpected error {:?}", error),
+        }
+        assert_eq!(
+            error.to_string(),
+            "regex 0 can not be compiled: `[a-z]++` can only be matched by the backtracking engine"
+        );
+        assert!(s.get_dfa().is_err());
+
+        // and never give back what they matched
+        s.add_regex("a*+ a".parse().unwrap());
+        s.remove_regex_with_index(0);
+        let error = s.parse("aa", backtrack).unwrap_err();
+        assert_eq!(error.expected(), ["\"a\""]);
+        assert_eq!(error.position().map(|position| position.offset), Some(2));
+        assert!(matches!(s.parse("aa", Some(Engine::Nfa)), Err(ParseError::Unsupported { index: 0, .. })));
+    }
+
+    #[test]
+    fn test_naive_parser_assertions() {
+        let keyword = RegexComponent::Keyword("if".to_string());
+        let letters: Regex = "[a-z]*".parse().unwrap();
+        let s = NaiveParser::with_regexes(vec![
+            Regex::new(vec![keyword.clone(), RegexComponent::SubRegex(letters)]),
+            "\\b [a-z]+ \\B [0-9]+ $".parse().unwrap(),
+        ]);
+        let engines = [
+            Engine::Dfa,
+            Engine::LazyDfa { budget: 1 << 16 },
+            Engine::Nfa,
+            Engine::Derivative,
+            Engine::Backtrack { budget: 1000 },
+        ];
+        for engine in engines {
+            assert_eq!(s.parse("if", Some(engine)).unwrap(), vec!["if"]);
+            // `if` does not match the start of a longer word
+            assert!(s.parse("iffy", Some(engine)).is_err());
+            assert_eq!(s.parse("ab12", Some(engine)).unwrap(), vec!["ab", "12"]);
+            assert!(s.parse("ab 12", Some(engine)).is_err());
+        }
+
+        // The keyword terminals of a grammar need a boundary too
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![Symbol::Terminal(keyword), literal("fy")]);
+        grammar.add_production("s", vec![literal("iffy")]);
+        let s = NaiveParser::with_grammar(grammar);
+        assert_eq!(s.parse("iffy", None).unwrap(), vec!["iffy"]);
+    }
+
+
+    #[test]
+    fn test_naive_parser_unicode_word_boundary() {
+        let keyword = RegexComponent::Keyword("si".to_string());
+        let rest: Regex = ".*".parse().unwrap();
+        let s = NaiveParser::with_regexes(vec![
+            Regex::new(vec![keyword, RegexComponent::SubRegex(rest)]),
+            "[a-z] \\B \\p{Alpha}+".parse().unwrap(),
+        ]);
+        let engines = [
+            Engine::Dfa,
+            Engine::LazyDfa { budget: 1 << 16 },
+            Engine::Nfa,
+            Engine::Derivative,
+            Engine::Backtrack { budget: 1000 },
+        ];
+        for engine in engines {
+            assert_eq!(s.parse("si é", Some(engine)).unwrap(), vec!["si", " é"]);
+            // Non-ASCII letters are word characters: `si` does not match the start of `sié`
+            assert_eq!(s.parse("sié", Some(engine)).unwrap(), vec!["s", "ié"]);
+            // There is no word boundary between `c` and `é`
+            assert_eq!(s.parse("cé", Some(engine)).unwrap(), vec!["c", "é"]);
+        }
+    }
+
+    #[test]
+    fn test_naive_parser_flags() {
+        let s = NaiveParser::with_regexes(vec!["(?i) select | from".parse().unwrap(), "[a-z]+".parse().unwrap()]);
+        let engines = [
+            Engine::Dfa,
+            Engine::LazyDfa { budget: 1 << 16 },
+            Engine::Nfa,
+            Engine::Derivative,
+            Engine::Backtrack { budget: 1000 },
+        ];
+        for engine in engines {
+            assert_eq!(s.parse("SELECT", Some(engine)).unwrap(), vec!["SELECT"]);
+            // Flags set inside the first arm reach the arms after it
+            assert_eq!(s.parse("From", Some(engine)).unwrap(), vec!["From"]);
+            assert!(s.parse("Other", Some(engine)).is_err());
+        }
+
+        // A flagged terminal of a grammar keeps its flags once lowered
+        let keyword = Regex::new(vec![RegexComponent::Keyword("if".to_string())]);
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![Symbol::Terminal(RegexComponent::Flagged(
+            Flags::new().with_case_insensitive(true),
+            keyword,
+        ))]);
+        let s = NaiveParser::with_grammar(grammar);
+        assert_eq!(s.parse("IF", None).unwrap(), vec!["IF"]);
+        assert!(s.parse("IFFY", None).is_err());
+    }
+
+    #[test]
+    fn test_naive_parser_captures() {
+        let s = NaiveParser::with_regexes(vec![
+            "(?<key>[a-z]+) = (?<value>[0-9]+ ,?)*".parse().unwrap(),
+            "(?<name>[a-z]+) \\( ([a-z]*) \\)".parse().unwrap(),
+        ]);
+        let engines = [Engine::Dfa, Engine::Nfa, Engine::Derivative, Engine::Backtrack { budget: 1000 }];
+        for engine in engines {
+            let (index, captures) = s.captures("  size=12,3 ", Some(engine)).unwrap().unwrap();
+            assert_eq!(index, 0);
+            assert_eq!(captures.get(0).unwrap().span, 2..11);
+            assert_eq!(captures.name("key").unwrap().span, 2..6);
+            let values: Vec<&str> = captures.iterations(2).iter().map(|capture| capture.text).collect();
+            assert_eq!(values, vec!["12,", "3"]);
+
+            let (index, captures) = s.captures("f(x)", Some(engine)).unwrap().unwrap();
+            assert_eq!((index, captures.name("name").unwrap().text, captures.get(2).unwrap().text), (1, "f", "x"));
+            assert_eq!(s.captures("f(x", Some(engine)).unwrap(), None);
+        }
+        // Regexes that can't be compiled are only matched by the backtracking engine
+        let s = NaiveParser::with_regexes(vec!["(?<a>a*+) b".parse().unwrap()]);
+        assert!(matches!(s.captures("aab", None), Err(ParseError::Unsupported { index: 0, .. })));
+        let (_, captures) = s.captures("aab", Some(Engine::Backtrack { budget: 1000 })).unwrap().unwrap();
+        assert_eq!(captures.name("a").unwrap().text, "aa");
+    }
+
+    #[test]
+    fn test_naive_parser_lookarounds() {
+        let s = NaiveParser::with_regexes(vec![
+            "[a-z]+ \\ ? - (?![0-9]) \\ ? [a-z]+".parse().unwrap(),
+            "[a-z]+ \\ ? - \\ ? (?<=-) [0-9]+".parse().unwrap(),
+        ]);
+        let backtrack = Some(Engine::Backtrack { budget: 1000 });
+        assert_eq!(s.parse("a - b", backtrack).unwrap(), vec!["a", " ", "-", " ", "b"]);
+        assert_eq!(s.parse("a-1", backtrack).unwrap(), vec!["a", "-", "1"]);
+        assert!(s.parse("a - 1", backtrack).is_err());
+
+        // The linear-time engines refuse the lookarounds before matching anything
+        for engine in [Engine::Dfa, Engine::LazyDfa { budget: 1 << 16 }, Engine::Nfa, Engine::Derivative] {
+            let error = s.parse("a-b", Some(engine)).unwrap_err();
+            assert!(matches!(&error, ParseError::Unsupported { index: 0, .. }), "{:?}", error);
+            assert_eq!(
+                error.to_string(),
+                "regex 0 can not be compiled: `(?![0-9])` can only be matched by the backtracking engine"
+            );
+        }
+        // and the backtracking engine refuses a Lookbehind of unbounded length, built without the syntax
+        let unbounded = RegexComponent::Lookbehind { regex: "[0-9]+".parse().unwrap(), negated: true };
+        let s = NaiveParser::with_regexes(vec![Regex::new(vec![unbounded, RegexComponent::Dot])]);
+        let error = s.parse("a", backtrack).unwrap_err();
+        assert!(matches!(&error, ParseError::Unsupported { index: 0, .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn test_naive_parser_grammar_nested_parentheses() {
+        // list -> "(" list ")" list | ε
+        let mut grammar = Grammar::new("list");
+        grammar.add_production(
+            "list",
+            vec![literal("("), nonterminal("list"), literal(")"), nonterminal("list")],
+        );
+        grammar.add_production("list", vec![]);
+
+        let s = NaiveParser::with_grammar(grammar);
+        match s.parse("(()) ()", None) {
+            Ok(r) => {
+                assert_eq!(r, vec!["(", "(", ")", ")", "(", ")"]);
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+        assert!(s.parse("(()", None).is_err());
+    }
+
+    #[test]
+    fn test_naive_parser_grammar_left_recursion() {
+        // e -> e "+" "id" | "id"
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("e"), literal("+"), literal("id")]);
+        grammar.add_production("e", vec![literal("id")]);
+
+        let mut s = NaiveParser::new();
+        s.set_grammar(grammar);
+        match s.parse("id + id+id", None) {
+            Ok(r) => {
+                assert_eq!(r, vec!["id", "+", "id", "+", "id"]);
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_naive_parser_tokens() {
+        let keyword = RegexComponent::Keyword("let".to_string());
+        let space = RegexComponent::Literal(" ".to_string());
+        let name = RegexComponent::Identifier("x".to_string());
+        let letters = RegexComponent::ZeroOrMore(Regex::new(vec![RegexComponent::Literal("a".to_string())]));
+        let mut s = NaiveParser::new();
+        s.add_regex(Regex::new(vec![
+            keyword.clone(),
+            space.clone(),
+            name.clone(),
+            space.clone(),
+            letters.clone(),
+        ]));
+        match s.tokenize("  let x aaa", None) {
+            Ok(r) => {
+                assert_eq!(
+                    r,
+                    vec![
+                        Token::new(keyword, "let", 2..5),
+                        Token::new(space.clone(), " ", 5..6),
+                        Token::new(name, "x", 6..7),
+                        Token::new(space, " ", 7..8),
+                        Token::new(letters, "aaa", 8..11),
+                    ]
+                );
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_naive_parser_token_slices() {
+        let word = RegexComponent::OneOrMore(Regex::new(vec![RegexComponent::Literal("ab".to_string())]));
+        let mut s = NaiveParser::new();
+        s.add_regex(Regex::new(vec![
+            RegexComponent::Literal("(".to_string()),
+            word.clone(),
+            RegexComponent::Literal(")".to_string()),
+        ]));
+        let input = "(".to_string() + &"ab".repeat(1000) + ")";
+        let tokens = s.tokenize_slices(&input, None).unwrap();
+        assert_eq!(tokens.len(), 3);
+        // The matched text is borrowed from the input, not copied
+        assert_eq!(tokens[1].text.as_ptr(), input[1..].as_ptr());
+        assert_eq!(tokens[1].span, 1..2001);
+        assert_eq!(tokens[1].kind, &s.get_regexes()[0].components[1]);
+        assert_eq!(tokens[2].to_token(), Token::new(RegexComponent::Literal(")".to_string()), ")", 2001..2002));
+    }
+
+    #[test]
+    fn test_naive_parser_grammar_tokens() {
+        // e -> e "+" "id" | "id"
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("e"), literal("+"), literal("id")]);
+        grammar.add_production("e", vec![literal("id")]);
+
+        let s = NaiveParser::with_grammar(grammar);
+        let tokens = s.tokenize(" id +id", None).unwrap();
+        let spans: Vec<_> = tokens.iter().map(|token| token.span.clone()).collect();
+        assert_eq!(spans, vec![1..3, 4..5, 5..7]);
+        assert_eq!(tokens[1].kind, RegexComponent::Literal("+".to_string()));
+    }
+
+    #[test]
+    fn test_naive_parser_classes() {
+        // An identifier made of letters, in a Regex and as a terminal of a grammar
+        let letters = RegexComponent::Class(CharClass::letters());
+        let identifier = Regex::new(vec![RegexComponent::OneOrMore(Regex::new(vec![letters.clone()]))]);
+        let s = NaiveParser::with_regexes(vec![identifier]);
+        for engine in [Engine::Dfa, Engine::Nfa, Engine::Derivative, Engine::Backtrack { budget: 1000 }] {
+            assert_eq!(s.parse("abcXYZ", Some(engine)).unwrap(), vec!["abcXYZ"]);
+            assert!(s.parse("abc1", Some(engine)).is_err());
+        }
+
+        // list -> letter ("," letter)*
+        let mut grammar = Grammar::new("list");
+        grammar.add_production(
+            "list",
+            vec![
+                Symbol::Terminal(letters.clone()),
+                Symbol::Terminal(RegexComponent::ZeroOrMore(Regex::new(vec![
+                    RegexComponent::Literal(",".to_string()),
+                    letters,
+                ]))),
+            ],
+        );
+        let s = NaiveParser::with_grammar(grammar);
+        assert_eq!(s.parse("a, b,C", None).unwrap(), vec!["a", ",", "b", ",", "C"]);
+        assert!(s.parse("a, 1", None).is_err());
+    }
+
+    #[test]
+    fn test_naive_parser_unicode() {
+        // identifier := \p{Alpha} [\p{Alpha}\p{N}_]*
+        let identifier: Regex = "\\p{Alpha} [\\p{Alpha}\\p{N}_]* = \\d+".parse().unwrap();
+        let s = NaiveParser::with_regexes(vec![identifier]);
+        let engines = [
+            Engine::Dfa,
+            Engine::LazyDfa { budget: 1 << 12 },
+            Engine::Nfa,
+            Engine::Derivative,
+            Engine::Backtrack { budget: 1000 },
+        ];
+        for engine in engines {
+            assert_eq!(s.parse("Größe_2=42", Some(engine)).unwrap(), vec!["G", "röße_2", "=", "42"]);
+            assert_eq!(s.parse("λ=1", Some(engine)).unwrap(), vec!["λ", "=", "1"]);
+            assert!(s.parse("2x=1", Some(engine)).is_err());
+        }
+
+        // Failures after multi-byte characters report the start of the literal, in characters
+        let s = NaiveParser::with_regexes(vec!["é ab".parse().unwrap()]);
+        let error = s.parse("éaé", None).unwrap_err();
+        assert_eq!(error.position().map(|position| (position.offset, position.column)), Some((2, 2)));
+        assert!(s.parse("\u{1f600}", None).is_err());
+    }
+
+    #[test]
+    fn test_naive_parser_regexes_before_grammar() {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("ab")]);
+
+        let mut s = NaiveParser::with_grammar(grammar);
+        s.add_regex(Regex::new(vec![
+            RegexComponent::Literal("a".to_string()),
+            RegexComponent::Literal("b".to_string()),
+        ]));
+        assert_eq!(s.parse("ab", None).unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_naive_parser_error() {
+        let regex_1 = Regex::new(vec![
+            RegexComponent::Literal("a".to_string()),
+            RegexComponent::Literal("b".to_string()),
+        ]);
+        let regex_2 = Regex::new(vec![
+            RegexComponent::Literal("a".to_string()),
+            RegexComponent::Literal("c".to_string()),
+            RegexComponent::Literal("d".to_string()),
+        ]);
+
+        let s = NaiveParser::with_regexes(vec![regex_1.clone(), regex_2]);
+        match s.parse("  ax", None) {
+            Err(ParseError::Mismatch { position, found, expected, context, .. }) => {
+                assert_eq!(position, Position { offset: 3, line: 1, column: 4 });
+                assert_eq!(found, Some("x".to_string()));
+                assert_eq!(expected, vec!["\"b\"", "\"c\""]);
+                assert_eq!(context.as_deref(), Some(&ParseContext::Regex { index: 0, regex: regex_1 }));
+            }
+            r => panic!("Expected a mismatch, got {:?}", r),
+        }
+        assert_eq!(
+            s.parse("acdd", None).unwrap_err().to_string(),
+            "unexpected `d` at line 1, column 4, expected one of: end of input, \
+             while matching regex 1 `a c d`"
+        );
+
+        // The failure is reported at the start of the keyword, which was matched up to the `x`
+        let s = NaiveParser::with_regexes(vec!["select \\* from".parse().unwrap()]);
+        for engine in [Engine::Dfa, Engine::Backtrack { budget: 1000 }] {
+            let error = s.parse(" select*frxm", Some(engine)).unwrap_err();
+            assert_eq!(error.position(), Some(Position { offset: 8, line: 1, column: 9 }));
+            assert_eq!(error.furthest(), Some(Position { offset: 10, line: 1, column: 11 }));
+            assert_eq!(error.expected(), ["\"from\""]);
+        }
+    }
+
+    #[test]
+    fn test_naive_parser_grammar_error() {
+        // s -> "(" s ")" | "x"
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("("), nonterminal("s"), literal(")")]);
+        grammar.add_production("s", vec![literal("x")]);
+
+        let s = NaiveParser::with_grammar(grammar.clone());
+        let error = s.parse("((x)", None).unwrap_err();
+        assert_eq!(error.position().map(|p| p.offset), Some(4));
+        assert_eq!(error.expected(), ["\")\""]);
+        assert_eq!(
+            error.context(),
+            Some(&ParseContext::Production(grammar.get_productions()[0].clone()))
+        );
+        assert_eq!(s.parse("x)", None).unwrap_err().expected(), ["end of input"]);
+
+        let s = NaiveParser::with_grammar(Grammar::new("s"));
+        assert!(matches!(s.parse("x", None), Err(ParseError::InvalidGrammar(_))));
+    }
 }
@@ -1 +1,731 @@
-// use crate::parser::parser::Parser;
\ No newline at end of file
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use crate::components::grammar::{Analysis, Grammar, GrammarError, Production, Sym};
+use crate::components::token::TokenSlice;
+use crate::parser::error::{ParseContext, ParseError, Position};
+use crate::parser::Parser;
+
+/// SLRParser:
+/// Shift-reduce parser driven by ACTION and GOTO tables. By default the tables are built from the
+/// LR(0) item automaton of the grammar, and a production is reduced on every terminal of the FOLLOW
+/// set of its left-hand side. Left recursive grammars, which LLParser rejects, are handled naturally.
+/// Grammars that are not SLR(1) can use canonical LR(1) or LALR(1) tables instead, see TableAlgorithm.
+///
+/// ```rust
+/// use pilator::{
+///     components::{grammar::{Grammar, Symbol}, items::RegexComponent},
+///     parser::{slrparsers::SLRParser, Parser},
+/// };
+///
+/// // e -> e "+" "id" | "id"
+/// let mut grammar = Grammar::new("e");
+/// grammar.add_production("e", vec![
+///     Symbol::NonTerminal("e".to_string()),
+///     Symbol::Terminal(RegexComponent::Operator("+".to_string())),
+///     Symbol::Terminal(RegexComponent::Identifier("id".to_string())),
+/// ]);
+/// grammar.add_production("e", vec![Symbol::Terminal(RegexComponent::Identifier("id".to_string()))]);
+///
+/// let parser = SLRParser::new(&grammar).unwrap();
+/// assert_eq!(parser.parse("id + id", None).unwrap(), vec!["id", "+", "id"]);
+/// assert!(parser.parse("id +", None).is_err());
+/// ```
+pub struct SLRParser {
+    analysis: Analysis,
+    table: ParseTable,
+    /// Production being matched in every state, reported when parsing fails in that state
+    contexts: Vec<Option<usize>>,
+}
+
+/// Implemented methods:
+/// - new: Builds the SLR(1) tables of the grammar, failing if they contain conflicts
+/// - with_algorithm: Builds the tables of the grammar with the given algorithm, failing if they contain conflicts
+/// - get_table: Returns a reference to the ACTION and GOTO tables
+impl SLRParser {
+    pub fn new(grammar: &Grammar) -> Result<SLRParser, LRError> {
+        Self::with_algorithm(grammar, TableAlgorithm::Slr)
+    }
+
+    pub fn with_algorithm(grammar: &Grammar, algorithm: TableAlgorithm) -> Result<SLRParser, LRError> {
+        let analysis = Analysis::new(grammar).map_err(LRError::Grammar)?;
+        let states = match algorithm {
+            TableAlgorithm::Slr => {
+                let mut states = lr0_automaton(&analysis);
+                for state in states.iter_mut() {
+                    for item in state.items.iter_mut() {
+                        if item.dot == analysis.production(item.production).1.len() {
+                            item.lookaheads = follow_of(&analysis, item.production);
+                        }
+                    }
+                }
+                states
+            }
+            TableAlgorithm::Lr1 => lr1_automaton(&analysis),
+            TableAlgorithm::Lalr => lalr_merge(lr1_automaton(&analysis)),
+        };
+        let table = ParseTable::build(&analysis, &states)?;
+        let contexts = states
+            .iter()
+            .map(|state| {
+                state
+                    .items
+                    .iter()
+                    .find(|item| item.dot > 0 && item.production < analysis.productions.len())
+                    .map(|item| item.production)
+            })
+            .collect();
+        Ok(SLRParser {
+            analysis,
+            table,
+            contexts,
+        })
+    }
+
+    pub fn get_table(&self) -> &ParseTable {
+        &self.table
+    }
+}
+
+/// Parser trait implementation for SLRParser, using the grammar it was built from
+impl Parser for SLRParser {
+    type Config = ();
+
+    fn tokenize_slices<'a>(
+        &'a self,
+        input: &'a str,
+        _config: Option<Self::Config>,
+    ) -> Result<Vec<TokenSlice<'a>>, ParseError> {
+        let end = self.analysis.end();
+        let tokens = self.analysis.scan(input).map_err(|offset| ParseError::UnknownToken {
+            position: Position::new(input, offset),
+        })?;
+
+        let mut result: Vec<TokenSlice<'a>> = vec![];
+        let mut stack: Vec<usize> = vec![0];
+        let mut position = 0;
+        loop {
+            let state = *stack.last().unwrap_or(&0);
+            let lookahead = tokens.get(position).map_or(end, |token| token.0);
+            match self.table.action[state].get(&lookahead) {
+                Some(Action::Shift(next)) => {
+                    result.push(self.analysis.token(input, tokens[position]));
+                    stack.push(*next);
+                    position += 1;
+                }
+                Some(Action::Reduce(production)) => {
+                    let (lhs, rhs) = self.analysis.production(*production);
+                    stack.truncate(stack.len() - rhs.len());
+                    let state = *stack.last().unwrap_or(&0);
+                    // The tables are built together, so a reduction always has a goto entry
+                    stack.push(self.table.goto[state][&lhs]);
+                }
+                Some(Action::Accept) => return Ok(result),
+                None => {
+                    let (offset, found) = match tokens.get(position) {
+                        Some(&(_, start, stop)) => (start, Some(input[start..stop].to_string())),
+                        None => (input.len(), None),
+                    };
+                    // Tokens are shifted in order, up to the first one without an action: the
+                    // furthest point reached is where the parsing fails
+                    let position = Position::new(input, offset);
+                    return Err(ParseError::Mismatch {
+                        position,
+                        furthest: position,
+                        found,
+                        expected: self.table.action[state]
+                            .keys()
+                            .map(|t| self.analysis.terminal_name(*t))
+                            .collect(),
+                        context: self.contexts[state].map(|production| {
+                            Box::new(ParseContext::Production(
+                                self.analysis.grammar.get_productions()[production].clone(),
+                            ))
+                        }),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// enum TableAlgorithm
+/// Algorithm used to build the ACTION and GOTO tables of an SLRParser:
+/// - Slr: LR(0) automaton, reducing on the FOLLOW set of the production left-hand side
+/// - Lr1: canonical LR(1) automaton, the most powerful but with the largest number of states
+/// - Lalr: LR(1) automaton with the states sharing the same LR(0) items merged, as many states as Slr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableAlgorithm {
+    #[default]
+    Slr,
+    Lr1,
+    Lalr,
+}
+
+/// enum Action
+/// Entry of the ACTION table: shift to a state, reduce by a production (index in the grammar) or
+/// accept the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+/// ParseTable:
+/// ACTION and GOTO tables of a shift-reduce parser, indexed by state. ACTION maps a terminal index
+/// to an action, GOTO maps a nonterminal index to the next state. The terminal index equal to the
+/// number of terminals of the grammar is the end of input marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTable {
+    pub action: Vec<BTreeMap<usize, Action>>,
+    pub goto: Vec<BTreeMap<usize, usize>>,
+}
+
+impl ParseTable {
+    /// Fills the tables from an item automaton whose complete items carry their reduce lookaheads
+    fn build(analysis: &Analysis, states: &[State]) -> Result<ParseTable, LRError> {
+        let augmented = analysis.productions.len();
+        let mut table = ParseTable {
+            action: vec![BTreeMap::new(); states.len()],
+            goto: vec![BTreeMap::new(); states.len()],
+        };
+        let mut conflicts: Vec<LRConflict> = vec![];
+
+        for (index, state) in states.iter().enumerate() {
+            for (symbol, next) in state.transitions.iter() {
+                match *symbol {
+                    Sym::T(t) => {
+                        table.action[index].insert(t, Action::Shift(*next));
+                    }
+                    Sym::N(n) => {
+                        table.goto[index].insert(n, *next);
+                    }
+                }
+            }
+            for item in state.items.iter() {
+                if item.dot < analysis.production(item.production).1.len() {
+                    continue;
+                }
+                if item.production == augmented {
+                    table.action[index].insert(analysis.end(), Action::Accept);
+                    continue;
+                }
+                for lookahead in item.lookaheads.iter() {
+                    let reduce = Action::Reduce(item.production);
+                    match table.action[index].get(lookahead) {
+                        None => {
+                            table.action[index].insert(*lookahead, reduce);
+                        }
+                        Some(existing) if *existing == reduce => (),
+                        Some(existing) => {
+                            let mut productions = vec![];
+                            let kind = match existing {
+                                Action::Reduce(other) => {
+                                    productions.push(analysis.grammar.get_productions()[*other].clone());
+                                    ConflictKind::ReduceReduce
+                                }
+                                _ => ConflictKind::ShiftReduce,
+                            };
+                            productions.push(analysis.grammar.get_productions()[item.production].clone());
+                            conflicts.push(LRConflict {
+                                state: index,
+                                kind,
+                                lookahead: analysis.terminal_name(*lookahead),
+                                productions,
+                                items: state
+                                    .items
+                                    .iter()
+                                    .map(|item| item.display(analysis))
+                                    .collect(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(LRError::Conflicts(conflicts));
+        }
+        Ok(table)
+    }
+}
+
+/// enum ConflictKind
+/// Kind of conflicting entry found in the ACTION table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+/// LRConflict:
+/// Conflicting entry of the ACTION table: the state with its items, the lookahead terminal, and the
+/// productions that could be reduced on it (for a shift/reduce conflict, only the reduced one).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LRConflict {
+    pub state: usize,
+    pub kind: ConflictKind,
+    pub lookahead: String,
+    pub productions: Vec<Production>,
+    pub items: Vec<String>,
+}
+
+impl fmt::Display for LRConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let productions: Vec<String> = self
+            .productions
+            .iter()
+            .map(|production| format!("`{}`", production))
+            .collect();
+        match self.kind {
+            ConflictKind::ShiftReduce => write!(
+                f,
+                "shift/reduce conflict in state {} on {}: shift or reduce {}",
+                self.state,
+                self.lookahead,
+                productions.join(", ")
+            )?,
+            ConflictKind::ReduceReduce => write!(
+                f,
+                "reduce/reduce conflict in state {} on {}: reduce {}",
+                self.state,
+                self.lookahead,
+                productions.join(" or ")
+            )?,
+        }
+        for item in self.items.iter() {
+            write!(f, "\n    {}", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// enum LRError
+/// Errors returned while building a shift-reduce parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LRError {
+    /// The grammar is not well formed
+    Grammar(GrammarError),
+    /// The ACTION table has conflicting entries: every conflict is reported
+    Conflicts(Vec<LRConflict>),
+}
+
+impl fmt::Display for LRError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LRError::Grammar(error) => write!(f, "invalid grammar: {}", error),
+            LRError::Conflicts(conflicts) => {
+                write!(f, "grammar has conflicts")?;
+                for conflict in conflicts.iter() {
+                    write!(f, "\n  {}", conflict)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LRError {}
+
+/// Item of the automaton: production index, position of the dot in the right-hand side, and the
+/// terminals on which the item is reduced once complete
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Item {
+    production: usize,
+    dot: usize,
+    lookaheads: BTreeSet<usize>,
+}
+
+impl Item {
+    fn display(&self, analysis: &Analysis) -> String {
+        let (lhs, rhs) = analysis.production(self.production);
+        let mut text = match lhs {
+            n if n < analysis.nonterminals.len() => format!("{} ->", analysis.nonterminals[n]),
+            _ => format!("{}' ->", analysis.nonterminals[analysis.start]),
+        };
+        for (i, symbol) in rhs.iter().enumerate() {
+            if i == self.dot {
+                text.push_str(" .");
+            }
+            text.push(' ');
+            text.push_str(&analysis.symbol_name(*symbol));
+        }
+        if self.dot == rhs.len() {
+            text.push_str(" .");
+        }
+        text
+    }
+}
+
+/// State of the automaton: its closed item set and the transitions to other states
+#[derive(Debug, Clone)]
+struct State {
+    items: Vec<Item>,
+    transitions: BTreeMap<Sym, usize>,
+}
+
+/// FOLLOW set of the left-hand side of a production, only the end of input for the augmented one
+fn follow_of(analysis: &Analysis, production: usize) -> BTreeSet<usize> {
+    let (lhs, _) = analysis.production(production);
+    match analysis.follow.get(lhs) {
+        Some(follow) => follow.clone(),
+        None => [analysis.end()].into(),
+    }
+}
+
+/// Closure of a set of LR(0) items
+fn lr0_closure(analysis: &Analysis, kernel: &BTreeSet<(usize, usize)>) -> BTreeSet<(usize, usize)> {
+    let mut closure = kernel.clone();
+    let mut pending: Vec<(usize, usize)> = kernel.iter().copied().collect();
+    while let Some((production, dot)) = pending.pop() {
+        if let Some(Sym::N(n)) = analysis.production(production).1.get(dot) {
+            for (index, (lhs, _)) in analysis.productions.iter().enumerate() {
+                if lhs == n && closure.insert((index, 0)) {
+                    pending.push((index, 0));
+                }
+            }
+        }
+    }
+    closure
+}
+
+/// Canonical collection of LR(0) item sets of the augmented grammar
+fn lr0_automaton(analysis: &Analysis) -> Vec<State> {
+    let start: BTreeSet<(usize, usize)> = [(analysis.productions.len(), 0)].into();
+    let mut kernels: Vec<BTreeSet<(usize, usize)>> = vec![start.clone()];
+    let mut ids: HashMap<BTreeSet<(usize, usize)>, usize> = HashMap::from([(start, 0)]);
+    let mut states: Vec<State> = vec![];
+
+    while states.len() < kernels.len() {
+        let items = lr0_closure(analysis, &kernels[states.len()]);
+        let mut successors: BTreeMap<Sym, BTreeSet<(usize, usize)>> = BTreeMap::new();
+        for (production, dot) in items.iter() {
+            if let Some(symbol) = analysis.production(*production).1.get(*dot) {
+                successors
+                    .entry(*symbol)
+                    .or_default()
+                    .insert((*production, dot + 1));
+            }
+        }
+        let mut transitions = BTreeMap::new();
+        for (symbol, kernel) in successors {
+            let next = *ids.entry(kernel.clone()).or_insert_with(|| {
+                kernels.push(kernel);
+                kernels.len() - 1
+            });
+            transitions.insert(symbol, next);
+        }
+        states.push(State {
+            items: items
+                .into_iter()
+                .map(|(production, dot)| Item {
+                    production,
+                    dot,
+                    lookaheads: BTreeSet::new(),
+                })
+                .collect(),
+            transitions,
+        });
+    }
+    states
+}
+
+/// Closure of a set of LR(1) items, each one carrying a single lookahead terminal
+fn lr1_closure(
+    analysis: &Analysis,
+    kernel: &BTreeSet<(usize, usize, usize)>,
+) -> BTreeSet<(usize, usize, usize)> {
+    let mut closure = kernel.clone();
+    let mut pending: Vec<(usize, usize, usize)> = kernel.iter().copied().collect();
+    while let Some((production, dot, lookahead)) = pending.pop() {
+        let rhs = analysis.production(production).1;
+        if let Some(Sym::N(n)) = rhs.get(dot) {
+            let (mut lookaheads, nullable) = analysis.first_of(&rhs[dot + 1..]);
+            if nullable {
+                lookaheads.insert(lookahead);
+            }
+            for (index, (lhs, _)) in analysis.productions.iter().enumerate() {
+                if lhs != n {
+                    continue;
+                }
+                for terminal in lookaheads.iter() {
+                    if closure.insert((index, 0, *terminal)) {
+                        pending.push((index, 0, *terminal));
+                    }
+                }
+            }
+        }
+    }
+    closure
+}
+
+/// Canonical collection of LR(1) item sets of the augmented grammar, with the items sharing the
+/// same production and dot grouped together
+fn lr1_automaton(analysis: &Analysis) -> Vec<State> {
+    let start: BTreeSet<(usize, usize, usize)> =
+        [(analysis.productions.len(), 0, analysis.end())].into();
+    let mut kernels: Vec<BTreeSet<(usize, usize, usize)>> = vec![start.clone()];
+    let mut ids: HashMap<BTreeSet<(usize, usize, usize)>, usize> = HashMap::from([(start, 0)]);
+    let mut states: Vec<State> = vec![];
+
+    while states.len() < kernels.len() {
+        let items = lr1_closure(analysis, &kernels[states.len()]);
+        let mut successors: BTreeMap<Sym, BTreeSet<(usize, usize, usize)>> = BTreeMap::new();
+        let mut grouped: BTreeMap<(usize, usize), BTreeSet<usize>> = BTreeMap::new();
+        for (production, dot, lookahead) in items.into_iter() {
+            if let Some(symbol) = analysis.production(production).1.get(dot) {
+                successors
+                    .entry(*symbol)
+                    .or_default()
+                    .insert((production, dot + 1, lookahead));
+            }
+            grouped.entry((production, dot)).or_default().insert(lookahead);
+        }
+        let mut transitions = BTreeMap::new();
+        for (symbol, kernel) in successors {
+            let next = *ids.entry(kernel.clone()).or_insert_with(|| {
+                kernels.push(kernel);
+                kernels.len() - 1
+            });
+            transitions.insert(symbol, next);
+        }
+        states.push(State {
+            items: grouped
+                .into_iter()
+                .map(|((production, dot), lookaheads)| Item {
+                    production,
+                    dot,
+                    lookaheads,
+                })
+                .collect(),
+            transitions,
+        });
+    }
+    states
+}
+
+/// Merges the LR(1) states having the same LR(0) items, joining their lookaheads
+fn lalr_merge(states: Vec<State>) -> Vec<State> {
+    let core = |state: &State| -> Vec<(usize, usize)> {
+        state.items.iter().map(|item| (item.production, item.dot)).collect()
+    };
+    let mut ids: HashMap<Vec<(usize, usize)>, usize> = HashMap::new();
+    let mut merged_id: Vec<usize> = vec![];
+    for state in states.iter() {
+        let next = ids.len();
+        merged_id.push(*ids.entry(core(state)).or_insert(next));
+    }
+
+    let mut merged: Vec<Option<State>> = vec![None; ids.len()];
+    for (index, state) in states.into_iter().enumerate() {
+        let transitions: BTreeMap<Sym, usize> = state
+            .transitions
+            .iter()
+            .map(|(symbol, next)| (*symbol, merged_id[*next]))
+            .collect();
+        match &mut merged[merged_id[index]] {
+            Some(existing) => {
+                for (item, other) in existing.items.iter_mut().zip(state.items) {
+                    item.lookaheads.extend(other.lookaheads);
+                }
+            }
+            slot => {
+                *slot = Some(State {
+                    items: state.items,
+                    transitions,
+                })
+            }
+        }
+    }
+    merged.into_iter().flatten().collect()
+}
+
+// Unit tests for the created structures
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::grammar::test_symbols::{literal, nonterminal};
+    use crate::components::items::RegexComponent;
+
+    /// e -> e "+" t | t; t -> t "*" f | f; f -> "id" | "(" e ")"
+    fn expression_grammar() -> Grammar {
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("e"), literal("+"), nonterminal("t")]);
+        grammar.add_production("e", vec![nonterminal("t")]);
+        grammar.add_production("t", vec![nonterminal("t"), literal("*"), nonterminal("f")]);
+        grammar.add_production("t", vec![nonterminal("f")]);
+        grammar.add_production("f", vec![literal("id")]);
+        grammar.add_production("f", vec![literal("("), nonterminal("e"), literal(")")]);
+        grammar
+    }
+
+    #[test]
+    fn test_slr_parser_expression() {
+        let parser = SLRParser::new(&expression_grammar()).unwrap();
+        assert_eq!(parser.get_table().action.len(), 12);
+        match parser.parse("id * (id + id) + id", None) {
+            Ok(r) => {
+                assert_eq!(
+                    r,
+                    vec!["id", "*", "(", "id", "+", "id", ")", "+", "id"]
+                );
+            }
+            Err(e) => panic!("Error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_slr_parser_tokens() {
+        let parser = SLRParser::new(&expression_grammar()).unwrap();
+        let tokens = parser.tokenize("(id)*id", None).unwrap();
+        let spans: Vec<_> = tokens.iter().map(|token| token.span.clone()).collect();
+        assert_eq!(spans, vec![0..1, 1..3, 3..4, 4..5, 5..7]);
+        assert_eq!(tokens[3].kind, RegexComponent::Literal("*".to_string()));
+    }
+
+    #[test]
+    fn test_slr_parser_syntax_error() {
+        let parser = SLRParser::new(&expression_grammar()).unwrap();
+        match parser.parse("id + )", None) {
+            Err(ParseError::Mismatch { position, found, expected, context, .. }) => {
+                assert_eq!(position.offset, 5);
+                assert_eq!(found, Some(")".to_string()));
+                assert_eq!(expected, vec!["\"id\"", "\"(\""]);
+                assert_eq!(
+                    context.as_deref(),
+                    Some(&ParseContext::Production(expression_grammar().get_productions()[0].clone()))
+                );
+            }
+            r => panic!("Expected a mismatch, got {:?}", r),
+        }
+        assert_eq!(
+            parser.parse("(id + id", None).unwrap_err().to_string(),
+            "unexpected end of input at line 1, column 9, expected one of: \"+\", \")\", \
+             while matching production `e -> e \"+\" t`"
+        );
+    }
+
+    #[test]
+    fn test_slr_parser_epsilon() {
+        // list -> "(" list ")" list | ε
+        let mut grammar = Grammar::new("list");
+        grammar.add_production(
+            "list",
+            vec![literal("("), nonterminal("list"), literal(")"), nonterminal("list")],
+        );
+        grammar.add_production("list", vec![]);
+        let parser = SLRParser::new(&grammar).unwrap();
+        assert_eq!(parser.parse("", None).unwrap(), Vec::<String>::new());
+        assert_eq!(parser.parse("(()) ()", None).unwrap(), vec!["(", "(", ")", ")", "(", ")"]);
+        assert!(parser.parse("())", None).is_err());
+    }
+
+    #[test]
+    fn test_slr_parser_shift_reduce_conflict() {
+        // e -> e "+" e | "id" is ambiguous
+        let mut grammar = Grammar::new("e");
+        grammar.add_production("e", vec![nonterminal("e"), literal("+"), nonterminal("e")]);
+        grammar.add_production("e", vec![literal("id")]);
+        match SLRParser::new(&grammar) {
+            Err(LRError::Conflicts(conflicts)) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].kind, ConflictKind::ShiftReduce);
+                assert_eq!(conflicts[0].lookahead, "\"+\"");
+                assert_eq!(conflicts[0].productions, vec![grammar.get_productions()[0].clone()]);
+                assert_eq!(
+                    conflicts[0].items,
+                    vec!["e -> e . \"+\" e", "e -> e \"+\" e ."]
+                );
+            }
+            _ => panic!("Expected a conflict"),
+        }
+    }
+
+    #[test]
+    fn test_slr_parser_reduce_reduce_conflict() {
+        // s -> a | b; a -> "x"; b -> "x"
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![nonterminal("a")]);
+        grammar.add_production("s", vec![nonterminal("b")]);
+        grammar.add_production("a", vec![literal("x")]);
+        grammar.add_production("b", vec![literal("x")]);
+        match SLRParser::new(&grammar) {
+            Err(LRError::Conflicts(conflicts)) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].kind, ConflictKind::ReduceReduce);
+                assert_eq!(conflicts[0].lookahead, "end of input");
+                assert_eq!(
+                    conflicts[0].productions,
+                    vec![
+                        grammar.get_productions()[2].clone(),
+                        grammar.get_productions()[3].clone()
+                    ]
+                );
+            }
+            _ => panic!("Expected a conflict"),
+        }
+    }
+
+    /// s -> l "=" r | r; l -> "*" r | "id"; r -> l
+    fn assignment_grammar() -> Grammar {
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![nonterminal("l"), literal("="), nonterminal("r")]);
+        grammar.add_production("s", vec![nonterminal("r")]);
+        grammar.add_production("l", vec![literal("*"), nonterminal("r")]);
+        grammar.add_production("l", vec![literal("id")]);
+        grammar.add_production("r", vec![nonterminal("l")]);
+        grammar
+    }
+
+    #[test]
+    fn test_slr_parser_assignment_conflict() {
+        match SLRParser::new(&assignment_grammar()) {
+            Err(LRError::Conflicts(conflicts)) => {
+                assert_eq!(conflicts.len(), 1);
+                assert_eq!(conflicts[0].kind, ConflictKind::ShiftReduce);
+                assert_eq!(conflicts[0].lookahead, "\"=\"");
+                assert_eq!(conflicts[0].items, vec!["s -> l . \"=\" r", "r -> l ."]);
+            }
+            _ => panic!("Expected a conflict"),
+        }
+    }
+
+    #[test]
+    fn test_lalr_parser_assignment() {
+        let lalr = SLRParser::with_algorithm(&assignment_grammar(), TableAlgorithm::Lalr).unwrap();
+        let lr1 = SLRParser::with_algorithm(&assignment_grammar(), TableAlgorithm::Lr1).unwrap();
+        assert_eq!(lalr.get_table().action.len(), 10);
+        assert_eq!(lr1.get_table().action.len(), 14);
+        for parser in [lalr, lr1] {
+            assert_eq!(parser.parse("*id = id", None).unwrap(), vec!["*", "id", "=", "id"]);
+            assert_eq!(parser.parse("**id", None).unwrap(), vec!["*", "*", "id"]);
+            assert!(parser.parse("id = id = id", None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_lalr_parser_reduce_reduce_conflict() {
+        // s -> "a" a "d" | "b" b "d" | "a" b "e" | "b" a "e"; a -> "c"; b -> "c"
+        let mut grammar = Grammar::new("s");
+        grammar.add_production("s", vec![literal("a"), nonterminal("a"), literal("d")]);
+        grammar.add_production("s", vec![literal("b"), nonterminal("b"), literal("d")]);
+        grammar.add_production("s", vec![literal("a"), nonterminal("b"), literal("e")]);
+        grammar.add_production("s", vec![literal("b"), nonterminal("a"), literal("e")]);
+        grammar.add_production("a", vec![literal("c")]);
+        grammar.add_production("b", vec![literal("c")]);
+
+        let lr1 = SLRParser::with_algorithm(&grammar, TableAlgorithm::Lr1).unwrap();
+        assert_eq!(lr1.parse("a c e", None).unwrap(), vec!["a", "c", "e"]);
+        match SLRParser::with_algorithm(&grammar, TableAlgorithm::Lalr) {
+            Err(LRError::Conflicts(conflicts)) => {
+                assert_eq!(conflicts.len(), 2);
+                assert!(conflicts.iter().all(|c| c.kind == ConflictKind::ReduceReduce));
+                assert_eq!(conflicts[0].lookahead, "\"d\"");
+                assert_eq!(conflicts[0].items, vec!["a -> \"c\" .", "b -> \"c\" ."]);
+            }
+            _ => panic!("Expected a conflict"),
+        }
+    }
+}